@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use micronaut::{FormState, RatatuiRenderer, Renderer, ViewState};
+
+const LINES: usize = 10_000;
+const RUNS: u32 = 20;
+
+fn main() {
+    let mut page = String::from(">Files\n");
+    for i in 0..LINES {
+        page.push_str(&format!(
+            " -  `F0f0`_`[file_{i:05}_with_a_long_name.mp3`:/file/{i}.mp3]`_`f ({} KiB) uploaded by someone with a long description ✓\n",
+            i * 7 % 9000
+        ));
+    }
+    let doc = micronaut::parse(&page);
+
+    for width in [40, 80] {
+        let mut best = f64::MAX;
+        for _ in 0..RUNS {
+            // a fresh renderer each run so the line layout cache doesn't help
+            let start = Instant::now();
+            RatatuiRenderer::new().render(
+                &doc,
+                width,
+                0,
+                &FormState::default(),
+                &HashMap::new(),
+                &ViewState::default(),
+            );
+            best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+        }
+        println!(
+            "{} lines at width {}: {:.1} ms (best of {})",
+            LINES, width, best, RUNS
+        );
+    }
+}
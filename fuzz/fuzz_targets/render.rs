@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use micronaut::{Browser, RatatuiRenderer};
+
+fuzz_target!(|data: (u8, u8, &str)| {
+    let (width, height, input) = data;
+    let mut browser = Browser::new(RatatuiRenderer::new());
+    browser.resize(u16::from(width), u16::from(height));
+    browser.set_content("fuzz", input);
+    let _ = browser.render();
+    browser.select_next();
+    let _ = browser.interact();
+    browser.scroll_by(i32::from(height));
+    let _ = browser.render();
+});
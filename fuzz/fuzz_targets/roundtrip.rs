@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use micronaut::{Element, Line};
+
+// Neighbouring text of the same style serializes as one run, which the
+// parser doesn't always split the same way, so compare joined runs.
+fn text_runs(lines: &[Line]) -> Vec<Line> {
+    let mut lines = lines.to_vec();
+    for line in &mut lines {
+        let mut elements: Vec<Element> = Vec::new();
+        for element in line.elements.drain(..) {
+            match (elements.last_mut(), element) {
+                (Some(Element::Text(last)), Element::Text(text)) if last.style == text.style => {
+                    last.text.push_str(&text.text)
+                }
+                (_, element) => elements.push(element),
+            }
+        }
+        line.elements = elements;
+    }
+    lines
+}
+
+fuzz_target!(|input: &str| {
+    let doc = micronaut::parse(input);
+    let again = micronaut::parse(&doc.to_string());
+    assert_eq!(
+        text_runs(&doc.lines),
+        text_runs(&again.lines),
+        "serializing changes the meaning of {:?}",
+        input
+    );
+});
@@ -0,0 +1,118 @@
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use micronaut::{Browser, Document, ParseOptions, RatatuiRenderer};
+
+const USAGE: &str = "\
+Usage: micron <COMMAND> [FILE]...
+
+Commands:
+  fmt [-w] <FILE>...       Print the canonical form of each file (-w rewrites in place)
+  html <FILE>              Export as an HTML fragment
+  txt [-W N] <FILE>        Render as plain text at width N (default 80)
+  check <FILE>...          Report validation issues
+
+Use - as FILE to read from stdin.";
+
+fn read_source(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        Ok(source)
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+fn render_text(doc: &Document, width: u16) -> String {
+    let mut browser = Browser::new(RatatuiRenderer::new());
+    browser.resize(width, 1);
+    browser.set_document("-", doc.clone());
+    let height = browser.viewport().content_height.max(1);
+    browser.resize(width, height);
+    let Some(paragraph) = browser.render() else {
+        return String::new();
+    };
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    paragraph.clone().render(area, &mut buf);
+    (0..height)
+        .map(|y| {
+            let row: String = (0..width).map(|x| buf[(x, y)].symbol()).collect();
+            row.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn run(args: Vec<String>) -> Result<bool, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(USAGE.to_string());
+    };
+    let mut write = false;
+    let mut width = 80;
+    let mut files = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-w" if command == "fmt" => write = true,
+            "-W" | "--width" if command == "txt" => {
+                width = rest
+                    .next()
+                    .and_then(|w| w.parse().ok())
+                    .ok_or("--width expects a number")?;
+            }
+            _ => files.push(arg.as_str()),
+        }
+    }
+    if files.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut ok = true;
+    let options = ParseOptions {
+        literal_markers: true,
+        ..Default::default()
+    };
+    for path in files {
+        let source = read_source(path).map_err(|err| format!("{}: {}", path, err))?;
+        let doc = micronaut::parse_with(&source, &options);
+        if command == "fmt" && micronaut::parse_with(&doc.to_string(), &options).lines != doc.lines
+        {
+            return Err(format!(
+                "{}: formatting would change the page, left as is",
+                path
+            ));
+        }
+        match command.as_str() {
+            "fmt" if write && path != "-" => {
+                std::fs::write(path, format!("{}\n", doc))
+                    .map_err(|err| format!("{}: {}", path, err))?;
+            }
+            "fmt" => println!("{}", doc),
+            "html" => print!("{}", doc.to_html()),
+            "txt" => println!("{}", render_text(&doc, width)),
+            "check" => {
+                for issue in doc.validate() {
+                    ok = false;
+                    println!("{}: {}", path, issue);
+                }
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(ok)
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
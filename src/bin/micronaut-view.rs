@@ -0,0 +1,479 @@
+use std::io::{self, stdout};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use ratatui::crossterm::{
+    ExecutableCommand,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEvent,
+        MouseEventKind,
+    },
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    buffer::Buffer,
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use micronaut::{
+    Action, Browser, BrowserEvent, BrowserWidget, ColorMode, ColorVision, EditOverlay, FileLoader,
+    Interaction, KeyMap, KeymapHelp, Link, PageLoader, PendingLoad, RatatuiRenderer, Theme,
+    block_on, file_url,
+};
+
+const USAGE: &str = "\
+Usage: micronaut-view [OPTIONS] [PATH|URL]
+
+Options:
+  --theme <default|high-contrast>   Color theme
+  --color <truecolor|256|mono>      Terminal color support
+  --vision <normal|protanopia|deuteranopia|tritanopia>
+                                    Remap page colors for color vision deficiency
+  --keys <default|vim>              Keybinding profile
+  -h, --help                        Print this help";
+
+struct Options {
+    target: Option<String>,
+    theme: Theme,
+    color_mode: ColorMode,
+    color_vision: ColorVision,
+    keymap: KeyMap,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        target: None,
+        theme: Theme::Default,
+        color_mode: ColorMode::TrueColor,
+        color_vision: ColorVision::Normal,
+        keymap: KeyMap::default(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            "--theme" => {
+                options.theme = match value("--theme")?.as_str() {
+                    "default" => Theme::Default,
+                    "high-contrast" => Theme::HighContrast,
+                    other => return Err(format!("unknown theme: {}", other)),
+                }
+            }
+            "--color" => {
+                options.color_mode = match value("--color")?.as_str() {
+                    "truecolor" => ColorMode::TrueColor,
+                    "256" => ColorMode::Indexed,
+                    "mono" => ColorMode::Mono,
+                    other => return Err(format!("unknown color mode: {}", other)),
+                }
+            }
+            "--vision" => {
+                options.color_vision = match value("--vision")?.as_str() {
+                    "normal" => ColorVision::Normal,
+                    "protanopia" => ColorVision::Protanopia,
+                    "deuteranopia" => ColorVision::Deuteranopia,
+                    "tritanopia" => ColorVision::Tritanopia,
+                    other => return Err(format!("unknown color vision: {}", other)),
+                }
+            }
+            "--keys" => {
+                options.keymap = match value("--keys")?.as_str() {
+                    "default" => KeyMap::default(),
+                    "vim" => KeyMap::default()
+                        .bind(KeyCode::Char('j'), KeyModifiers::NONE, Action::Down)
+                        .bind(KeyCode::Char('k'), KeyModifiers::NONE, Action::Up)
+                        .bind(KeyCode::Char('h'), KeyModifiers::NONE, Action::Back)
+                        .bind(KeyCode::Char('l'), KeyModifiers::NONE, Action::Forward)
+                        .bind(KeyCode::Char('n'), KeyModifiers::NONE, Action::SelectNext)
+                        .bind(KeyCode::Char('N'), KeyModifiers::SHIFT, Action::SelectPrev)
+                        .bind(KeyCode::Char('g'), KeyModifiers::NONE, Action::Top)
+                        .bind(KeyCode::Char('G'), KeyModifiers::SHIFT, Action::Bottom),
+                    other => return Err(format!("unknown keybinding profile: {}", other)),
+                }
+            }
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option: {}\n\n{}", arg, USAGE));
+            }
+            _ => options.target = Some(arg),
+        }
+    }
+    Ok(options)
+}
+
+struct Loaders {
+    file: FileLoader,
+    #[cfg(feature = "http")]
+    http: micronaut::HttpLoader,
+}
+
+impl Loaders {
+    fn new() -> Self {
+        Self {
+            file: FileLoader::new(),
+            #[cfg(feature = "http")]
+            http: micronaut::HttpLoader::new(),
+        }
+    }
+
+    fn resolve(&self, base: &str, url: &str) -> String {
+        if url.contains("://") {
+            return url.to_string();
+        }
+        self.file
+            .resolve(base, url)
+            .unwrap_or_else(|| url.to_string())
+    }
+}
+
+impl PageLoader for Loaders {
+    type Error = String;
+
+    async fn load(&self, link: &Link) -> Result<String, String> {
+        if link.url.starts_with(micronaut::FILE_SCHEME) {
+            return self.file.load(link).await.map_err(|e| e.to_string());
+        }
+        #[cfg(feature = "http")]
+        if link.url.starts_with("http://") || link.url.starts_with("https://") {
+            return self.http.load(link).await.map_err(|e| e.to_string());
+        }
+        Err(format!("no loader for {}", link.url))
+    }
+}
+
+enum Mode {
+    Browse,
+    Help,
+    Navigate { link: Link },
+}
+
+struct Modal<'a> {
+    title: &'a str,
+    content: Vec<Line<'a>>,
+    buttons: Vec<(&'a str, Color)>,
+    border_color: Color,
+}
+
+impl<'a> Modal<'a> {
+    fn render(&self, area: Rect, buf: &mut Buffer) -> (Rect, Vec<Rect>) {
+        let width = 50u16.min(area.width.saturating_sub(4));
+        let height = (self.content.len() as u16 + 4).min(area.height.saturating_sub(4));
+        let popup = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        Clear.render(popup, buf);
+
+        let block = Block::default()
+            .title(format!(" {} ", self.title))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.border_color));
+
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let content_height = inner.height.saturating_sub(2);
+        Paragraph::new(self.content.clone()).render(
+            Rect::new(inner.x, inner.y, inner.width, content_height),
+            buf,
+        );
+
+        let button_y = inner.y + inner.height.saturating_sub(1);
+        let mut button_rects = Vec::new();
+        let total_len: usize = self.buttons.iter().map(|(l, _)| l.len() + 2).sum();
+        let spacing = 2usize;
+        let total = total_len + spacing * self.buttons.len().saturating_sub(1);
+        let mut x = inner.x + (inner.width.saturating_sub(total as u16)) / 2;
+
+        for (label, color) in &self.buttons {
+            let label_text = format!(" {} ", label);
+            let len = label_text.len() as u16;
+            buf.set_string(
+                x,
+                button_y,
+                &label_text,
+                Style::default().fg(Color::Black).bg(*color),
+            );
+            button_rects.push(Rect::new(x, button_y, len, 1));
+            x += len + spacing as u16;
+        }
+
+        (popup, button_rects)
+    }
+}
+
+fn follow(
+    browser: &mut Browser<RatatuiRenderer>,
+    loaders: &Loaders,
+    link: &Link,
+) -> Option<PendingLoad> {
+    let base = browser.url().unwrap_or_default().to_string();
+    let link = Link {
+        url: loaders.resolve(&base, &link.url),
+        ..link.clone()
+    };
+    browser.begin_follow(&link)
+}
+
+fn main() -> io::Result<()> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(if message == USAGE { 0 } else { 2 });
+        }
+    };
+    let file_path: Option<PathBuf> = options
+        .target
+        .as_deref()
+        .filter(|target| !target.contains("://"))
+        .map(|p| std::fs::canonicalize(p).unwrap_or_else(|_| PathBuf::from(p)));
+    let start_url = match (&file_path, &options.target) {
+        (Some(path), _) => Some(file_url(path)),
+        (None, target) => target.clone(),
+    };
+    let loaders = Loaders::new();
+
+    let (watch_tx, watch_rx) = mpsc::channel();
+    let _watcher: Option<RecommendedWatcher> = file_path.as_ref().and_then(|path| {
+        let tx = watch_tx.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
+                if let Ok(event) = res
+                    && event.kind.is_modify()
+                {
+                    let _ = tx.send(());
+                }
+            })
+            .ok()?;
+        watcher.watch(path, RecursiveMode::NonRecursive).ok()?;
+        Some(watcher)
+    });
+
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableMouseCapture)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let mut browser = Browser::new(
+        RatatuiRenderer::new()
+            .theme(options.theme)
+            .color_mode(options.color_mode)
+            .color_vision(options.color_vision),
+    );
+    browser.set_keymap(options.keymap);
+    browser.set_submit_on_enter(true);
+    // Loads run right after the next frame is drawn, so it shows the loading state
+    let mut pending = match &start_url {
+        Some(url) => browser.begin_follow(&Link::new(url.as_str())),
+        None => {
+            browser.set_content("file://example.mu", include_str!("../../tests/example.mu"));
+            None
+        }
+    };
+    let mut mode = Mode::Browse;
+    let overlay = EditOverlay::new();
+    let widget = BrowserWidget::new()
+        .scrollbar(true)
+        .edit_overlay(overlay.clone());
+    let mut button_rects: Vec<Rect> = Vec::new();
+
+    'runloop: loop {
+        terminal.draw(|frame| {
+            let area = frame.area();
+            frame.render_stateful_widget(widget.clone(), area, &mut browser);
+            if let Some(cursor) = overlay.cursor_position(area, &browser) {
+                frame.set_cursor_position(cursor);
+            }
+
+            match &mode {
+                Mode::Browse => {
+                    button_rects.clear();
+                }
+                Mode::Help => {
+                    let help = KeymapHelp::new(browser.keymap())
+                        .entry("t", "Toggle theme")
+                        .entry("v", "Cycle color vision mode")
+                        .entry("?", "Show this help")
+                        .entry("q, Esc", "Quit");
+                    frame.render_widget(help, area);
+                }
+                Mode::Navigate { link } => {
+                    let url_display = if link.url.chars().count() > 44 {
+                        let head: String = link.url.chars().take(41).collect();
+                        format!("{}...", head)
+                    } else {
+                        link.url.clone()
+                    };
+
+                    let mut lines = vec![Line::from(vec![
+                        Span::styled("URL: ", Style::default().fg(Color::DarkGray)),
+                        Span::styled(url_display, Style::default().fg(Color::White)),
+                    ])];
+
+                    if !link.form_data.is_empty() {
+                        lines.push(Line::from(""));
+                        lines.push(Line::styled(
+                            "Form data:",
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                        for (k, v) in &link.form_data {
+                            lines.push(Line::from(vec![
+                                Span::styled(
+                                    format!("  {}: ", k),
+                                    Style::default().fg(Color::Cyan),
+                                ),
+                                Span::styled(v, Style::default().fg(Color::White)),
+                            ]));
+                        }
+                    }
+
+                    let modal = Modal {
+                        title: "Navigate",
+                        content: lines,
+                        buttons: vec![("Cancel", Color::DarkGray), ("Go", Color::Green)],
+                        border_color: Color::Yellow,
+                    };
+
+                    let (_, btns) = modal.render(area, frame.buffer_mut());
+                    button_rects = btns;
+                }
+            }
+        })?;
+
+        if let Some(load) = pending.take() {
+            let result = block_on(loaders.load(&load.link));
+            let _ = browser.finish_load(load, result);
+            continue;
+        }
+
+        if watch_rx.try_recv().is_ok() && file_path.is_some() {
+            let _ = block_on(browser.reload(&loaders));
+        }
+
+        // Sleep until input arrives (or a smooth scroll needs its next
+        // frame), then drain whatever else is queued before redrawing.
+        let mut timeout = if browser.is_scrolling() {
+            Duration::from_millis(16)
+        } else {
+            Duration::from_millis(250)
+        };
+        while event::poll(timeout)? {
+            timeout = Duration::ZERO;
+            let evt = event::read()?;
+            match &mode {
+                Mode::Browse if browser.editing().is_some() => match &evt {
+                    Event::Key(key) if key.code == KeyCode::Esc => {
+                        browser.cancel_editing();
+                    }
+                    Event::Key(key) => {
+                        if let BrowserEvent::Interaction(Interaction::Link(link)) =
+                            browser.handle_key(*key)
+                        {
+                            mode = Mode::Navigate { link };
+                        }
+                    }
+                    Event::Paste(text) => {
+                        browser.handle_paste(text);
+                    }
+                    Event::Mouse(mouse) => {
+                        widget.handle_mouse(&mut browser, *mouse);
+                    }
+                    _ => {}
+                },
+                Mode::Help => {
+                    if matches!(
+                        evt,
+                        Event::Key(_)
+                            | Event::Mouse(MouseEvent {
+                                kind: MouseEventKind::Down(_),
+                                ..
+                            })
+                    ) {
+                        mode = Mode::Browse;
+                    }
+                }
+                Mode::Browse => {
+                    let event = match &evt {
+                        Event::Key(key) => match browser.handle_key(*key) {
+                            BrowserEvent::Ignored => {
+                                match key.code {
+                                    KeyCode::Char('q') | KeyCode::Esc => break 'runloop,
+                                    KeyCode::Char('?') => mode = Mode::Help,
+                                    KeyCode::Char('t') => browser.update_renderer(|r| {
+                                        r.theme = match r.theme {
+                                            Theme::Default => Theme::HighContrast,
+                                            Theme::HighContrast => Theme::Default,
+                                        }
+                                    }),
+                                    KeyCode::Char('v') => browser.update_renderer(|r| {
+                                        r.color_vision = match r.color_vision {
+                                            ColorVision::Normal => ColorVision::Protanopia,
+                                            ColorVision::Protanopia => ColorVision::Deuteranopia,
+                                            ColorVision::Deuteranopia => ColorVision::Tritanopia,
+                                            ColorVision::Tritanopia => ColorVision::Normal,
+                                        }
+                                    }),
+                                    _ => {}
+                                }
+                                BrowserEvent::Ignored
+                            }
+                            event => event,
+                        },
+                        Event::Mouse(mouse) => widget.handle_mouse(&mut browser, *mouse),
+                        _ => BrowserEvent::Ignored,
+                    };
+                    match event {
+                        BrowserEvent::Interaction(Interaction::Link(link)) => {
+                            mode = Mode::Navigate { link };
+                        }
+                        BrowserEvent::Interaction(Interaction::EditField(field)) => {
+                            browser.begin_editing(&field.name);
+                        }
+                        _ => {}
+                    }
+                }
+                Mode::Navigate { link } => match &evt {
+                    Event::Key(key) => match key.code {
+                        KeyCode::Enter | KeyCode::Char('y') => {
+                            pending = follow(&mut browser, &loaders, link);
+                            mode = Mode::Browse;
+                        }
+                        KeyCode::Esc | KeyCode::Char('n') => {
+                            mode = Mode::Browse;
+                        }
+                        _ => {}
+                    },
+                    Event::Mouse(mouse) => {
+                        if let MouseEventKind::Down(_) = mouse.kind {
+                            let (x, y) = (mouse.column, mouse.row);
+                            if button_rects
+                                .first()
+                                .is_some_and(|r| r.contains((x, y).into()))
+                            {
+                                mode = Mode::Browse;
+                            } else if button_rects
+                                .get(1)
+                                .is_some_and(|r| r.contains((x, y).into()))
+                            {
+                                pending = follow(&mut browser, &loaders, link);
+                                mode = Mode::Browse;
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    stdout().execute(DisableMouseCapture)?;
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}
@@ -1,8 +1,14 @@
 #[derive(Debug, Clone, PartialEq)]
 pub struct Document {
+    pub metadata: Metadata,
     pub lines: Vec<Line>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Metadata {
+    pub entries: Vec<(String, String)>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Line {
     pub kind: LineKind,
@@ -11,15 +17,20 @@ pub struct Line {
     pub elements: Vec<Element>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineKind {
     Normal,
     Heading(u8),
     Divider(char),
     Comment,
+    Directive,
+    LiteralStart,
+    LiteralEnd,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Alignment {
     #[default]
     Left,
@@ -33,6 +44,7 @@ pub enum Element {
     Link(LinkElement),
     Field(Field),
     Partial(Partial),
+    Progress(Progress),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -41,7 +53,8 @@ pub struct StyledText {
     pub style: Style,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Style {
     pub fg: Option<Color>,
     pub bg: Option<Color>,
@@ -50,7 +63,8 @@ pub struct Style {
     pub underline: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -62,6 +76,10 @@ pub struct LinkElement {
     pub label: String,
     pub url: String,
     pub fields: Vec<String>,
+    /// Names in `fields` that must be filled in before the link submits,
+    /// written `!name` in the field list. This is a micronaut extension:
+    /// NomadNet reads the `!` as part of the field name.
+    pub required: Vec<String>,
     pub style: Style,
 }
 
@@ -71,14 +89,38 @@ pub struct Field {
     pub default: String,
     pub width: Option<u16>,
     pub masked: bool,
+    pub tab_index: Option<u16>,
     pub kind: FieldKind,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FieldKind {
     Text,
-    Checkbox { checked: bool },
-    Radio { value: String, checked: bool },
+    Checkbox {
+        value: String,
+        checked: bool,
+    },
+    Radio {
+        value: String,
+        checked: bool,
+    },
+    /// A whole number moved by `step` within `min..=max`, for settings that
+    /// would otherwise be free text.
+    Number {
+        min: Option<i64>,
+        max: Option<i64>,
+        step: i64,
+    },
+    /// A calendar date, submitted as `YYYY-MM-DD`.
+    Date,
+}
+
+/// A bar filled to `value` out of `max`, e.g. a node's disk usage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub value: f64,
+    pub max: f64,
+    pub label: String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -87,3 +129,12 @@ pub struct Partial {
     pub refresh: Option<u32>,
     pub fields: Vec<String>,
 }
+
+/// `value` as a whole percentage of `max`, clamped to `0..=100`.
+pub(crate) fn progress_percent(value: f64, max: f64) -> u32 {
+    if max > 0.0 {
+        (value / max * 100.0).clamp(0.0, 100.0).round() as u32
+    } else {
+        0
+    }
+}
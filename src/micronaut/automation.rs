@@ -0,0 +1,346 @@
+use std::fmt::{self, Display};
+
+use crate::micronaut::browser::Browser;
+use crate::micronaut::loader::PageLoader;
+use crate::micronaut::parser::parse;
+use crate::micronaut::render::Renderer;
+use crate::{DomLine, Interaction, RunTarget};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Navigate(String),
+    /// Focus the named field and start editing it if it takes text.
+    Focus(String),
+    /// Type into the field being edited.
+    Type(String),
+    /// Follow the first link whose label or url contains the pattern.
+    Activate(String),
+    /// Fail unless the page text, partials and field values included,
+    /// contains this.
+    Expect(String),
+}
+
+/// A sequence of steps to drive a `Browser` through, built in code or
+/// parsed from one `step argument` per line:
+///
+/// ```text
+/// navigate :/page/index.mu
+/// focus query
+/// type rust
+/// activate Search
+/// expect Results
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    pub steps: Vec<Step>,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(source: &str) -> Result<Self, ScriptError<String>> {
+        let mut script = Script::new();
+        for (idx, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+            let argument = argument.trim().to_string();
+            script.steps.push(match command {
+                "navigate" => Step::Navigate(argument),
+                "focus" => Step::Focus(argument),
+                "type" => Step::Type(argument),
+                "activate" => Step::Activate(argument),
+                "expect" => Step::Expect(argument),
+                _ => {
+                    return Err(ScriptError::UnknownCommand {
+                        line: idx + 1,
+                        command: command.to_string(),
+                    });
+                }
+            });
+        }
+        Ok(script)
+    }
+
+    pub fn navigate(mut self, url: &str) -> Self {
+        self.steps.push(Step::Navigate(url.to_string()));
+        self
+    }
+
+    pub fn focus(mut self, field: &str) -> Self {
+        self.steps.push(Step::Focus(field.to_string()));
+        self
+    }
+
+    pub fn type_text(mut self, text: &str) -> Self {
+        self.steps.push(Step::Type(text.to_string()));
+        self
+    }
+
+    pub fn activate(mut self, pattern: &str) -> Self {
+        self.steps.push(Step::Activate(pattern.to_string()));
+        self
+    }
+
+    pub fn expect(mut self, text: &str) -> Self {
+        self.steps.push(Step::Expect(text.to_string()));
+        self
+    }
+
+    /// Runs every step in order, stopping at the first failure. `step` in
+    /// errors is the index into `steps`.
+    pub async fn run<R: Renderer, L: PageLoader>(
+        &self,
+        browser: &mut Browser<R>,
+        loader: &L,
+    ) -> Result<(), ScriptError<L::Error>> {
+        for (step, action) in self.steps.iter().enumerate() {
+            match action {
+                Step::Navigate(url) => browser
+                    .navigate(loader, url)
+                    .await
+                    .map_err(|error| ScriptError::Load { step, error })?,
+                Step::Focus(name) => {
+                    browser.stop_editing();
+                    if !browser.focus_field(name) {
+                        return Err(ScriptError::NoField {
+                            step,
+                            name: name.clone(),
+                        });
+                    }
+                    if let Some(Interaction::EditField(field)) = browser.interact() {
+                        browser.begin_editing(&field.name);
+                    }
+                }
+                Step::Type(text) => {
+                    if browser.editing().is_none() {
+                        return Err(ScriptError::NotEditing { step });
+                    }
+                    browser.input_str(text);
+                }
+                Step::Activate(pattern) => {
+                    browser.stop_editing();
+                    let url = page_links(browser)
+                        .into_iter()
+                        .find(|(label, url)| label.contains(pattern) || url.contains(pattern))
+                        .map(|(_, url)| url);
+                    let Some(url) = url.filter(|url| browser.focus_link_by_url(url)) else {
+                        return Err(ScriptError::NoLink {
+                            step,
+                            pattern: pattern.clone(),
+                        });
+                    };
+                    if let Some(Interaction::Link(link)) = browser.interact() {
+                        browser
+                            .follow(loader, &link)
+                            .await
+                            .map_err(|error| ScriptError::Load { step, error })?;
+                    } else if !url.starts_with("p:") {
+                        return Err(ScriptError::Blocked { step, url });
+                    }
+                }
+                Step::Expect(text) => {
+                    let page = page_text(browser);
+                    if !page.contains(text.as_str()) {
+                        return Err(ScriptError::TextMissing {
+                            step,
+                            text: text.clone(),
+                            page,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError<E> {
+    UnknownCommand {
+        line: usize,
+        command: String,
+    },
+    Load {
+        step: usize,
+        error: E,
+    },
+    NoField {
+        step: usize,
+        name: String,
+    },
+    NotEditing {
+        step: usize,
+    },
+    NoLink {
+        step: usize,
+        pattern: String,
+    },
+    /// The link was found but following it was refused, e.g. by
+    /// `on_navigate` or a missing required field.
+    Blocked {
+        step: usize,
+        url: String,
+    },
+    TextMissing {
+        step: usize,
+        text: String,
+        page: String,
+    },
+}
+
+impl<E: Display> Display for ScriptError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::UnknownCommand { line, command } => {
+                write!(f, "line {}: unknown command {:?}", line, command)
+            }
+            ScriptError::Load { step, error } => write!(f, "step {}: {}", step, error),
+            ScriptError::NoField { step, name } => {
+                write!(f, "step {}: no field named {:?}", step, name)
+            }
+            ScriptError::NotEditing { step } => {
+                write!(f, "step {}: no field is being edited", step)
+            }
+            ScriptError::NoLink { step, pattern } => {
+                write!(f, "step {}: no link matching {:?}", step, pattern)
+            }
+            ScriptError::Blocked { step, url } => {
+                write!(f, "step {}: following {} was blocked", step, url)
+            }
+            ScriptError::TextMissing { step, text, page } => {
+                write!(f, "step {}: {:?} not found in:\n{}", step, text, page)
+            }
+        }
+    }
+}
+
+/// The text of every line on the page with partial content and current
+/// field values filled in.
+pub fn page_text<R: Renderer>(browser: &Browser<R>) -> String {
+    let mut out = String::new();
+    if let Some(doc) = browser.document() {
+        push_lines(browser, &doc.to_dom(), &mut out);
+    }
+    out
+}
+
+fn push_lines<R: Renderer>(browser: &Browser<R>, lines: &[DomLine], out: &mut String) {
+    for line in lines {
+        for run in &line.runs {
+            match &run.target {
+                Some(RunTarget::Field { name, masked, .. }) => {
+                    let value = browser.field_value(name).unwrap_or(&run.text);
+                    if *masked {
+                        out.extend(value.chars().map(|_| '*'));
+                    } else {
+                        out.push_str(value);
+                    }
+                }
+                Some(RunTarget::Partial { url, .. }) => {
+                    if let Some(content) = browser.partial_content(url) {
+                        push_lines(browser, &parse(content).to_dom(), out);
+                    }
+                }
+                _ => out.push_str(&run.text),
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// `(label, url)` for every link on the page, partials included.
+fn page_links<R: Renderer>(browser: &Browser<R>) -> Vec<(String, String)> {
+    fn collect<R: Renderer>(
+        browser: &Browser<R>,
+        lines: &[DomLine],
+        out: &mut Vec<(String, String)>,
+    ) {
+        for run in lines.iter().flat_map(|line| &line.runs) {
+            match &run.target {
+                Some(RunTarget::Link { url, .. }) => out.push((run.text.clone(), url.clone())),
+                Some(RunTarget::Partial { url, .. }) => {
+                    if let Some(content) = browser.partial_content(url) {
+                        collect(browser, &parse(content).to_dom(), out);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut links = Vec::new();
+    if let Some(doc) = browser.document() {
+        collect(browser, &doc.to_dom(), &mut links);
+    }
+    links
+}
+
+#[cfg(all(test, feature = "ratatui-renderer"))]
+mod tests {
+    use super::*;
+    use crate::micronaut::loader::block_on;
+    use crate::{Link, RatatuiRenderer};
+    use std::collections::HashMap;
+
+    struct Pages(HashMap<&'static str, &'static str>);
+
+    impl PageLoader for Pages {
+        type Error = String;
+
+        async fn load(&self, link: &Link) -> Result<String, String> {
+            if let Some(query) = link.form_data.get("field_q") {
+                return Ok(format!("Results for {}", query));
+            }
+            self.0
+                .get(link.url.as_str())
+                .map(|content| content.to_string())
+                .ok_or_else(|| format!("not found: {}", link.url))
+        }
+    }
+
+    #[test]
+    fn runs_a_search_flow() {
+        let loader = Pages(HashMap::from([(
+            "/index.mu",
+            "Search `<12|q`>\n`[Go`/search.mu`q]",
+        )]));
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.resize(40, 10);
+
+        let script = Script::parse(
+            "# search for rust\nnavigate /index.mu\nfocus q\ntype rust\nexpect Search rust\nactivate Go\nexpect Results for rust",
+        )
+        .unwrap();
+        assert_eq!(
+            script,
+            Script::new()
+                .navigate("/index.mu")
+                .focus("q")
+                .type_text("rust")
+                .expect("Search rust")
+                .activate("Go")
+                .expect("Results for rust")
+        );
+        block_on(script.run(&mut browser, &loader)).unwrap();
+        assert_eq!(browser.url(), Some("/search.mu"));
+
+        let err = block_on(Script::new().activate("Nope").run(&mut browser, &loader));
+        assert_eq!(
+            err,
+            Err(ScriptError::NoLink {
+                step: 0,
+                pattern: "Nope".to_string()
+            })
+        );
+        assert!(matches!(
+            Script::parse("jump /x"),
+            Err(ScriptError::UnknownCommand { line: 1, .. })
+        ));
+    }
+}
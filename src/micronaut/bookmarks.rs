@@ -0,0 +1,139 @@
+use crate::{Document, Line, LinkElement};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Bookmarks {
+    items: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, url: impl Into<String>, title: impl Into<String>) -> bool {
+        let url = url.into();
+        if self.contains(&url) {
+            return false;
+        }
+        self.items.push(Bookmark {
+            url,
+            title: title.into(),
+        });
+        true
+    }
+
+    pub fn remove(&mut self, url: &str) -> Option<Bookmark> {
+        let idx = self.items.iter().position(|b| b.url == url)?;
+        Some(self.items.remove(idx))
+    }
+
+    pub fn rename(&mut self, url: &str, title: impl Into<String>) -> bool {
+        match self.items.iter_mut().find(|b| b.url == url) {
+            Some(bookmark) => {
+                bookmark.title = title.into();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Option<&Bookmark> {
+        self.items.iter().find(|b| b.url == url)
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.get(url).is_some()
+    }
+
+    pub fn list(&self) -> &[Bookmark] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn to_document(&self) -> Document {
+        let mut doc = Document::new();
+        doc.push(Line::heading(1).text("Bookmarks"));
+        if self.items.is_empty() {
+            doc.push(Line::normal().italic("No bookmarks yet"));
+        }
+        for bookmark in &self.items {
+            let label = if bookmark.title.is_empty() {
+                &bookmark.url
+            } else {
+                &bookmark.title
+            };
+            doc.push(Line::normal().link(LinkElement::new(&bookmark.url).label(label)));
+        }
+        doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_rename_remove() {
+        let mut bookmarks = Bookmarks::new();
+        assert!(bookmarks.add("/index.mu", "Home"));
+        assert!(bookmarks.add("/docs.mu", "Docs"));
+        assert!(!bookmarks.add("/index.mu", "Duplicate"));
+        assert_eq!(bookmarks.len(), 2);
+
+        assert!(bookmarks.rename("/docs.mu", "Documentation"));
+        assert!(!bookmarks.rename("/missing.mu", "Missing"));
+        assert_eq!(bookmarks.get("/docs.mu").unwrap().title, "Documentation");
+
+        let removed = bookmarks.remove("/index.mu").unwrap();
+        assert_eq!(removed.title, "Home");
+        assert!(!bookmarks.contains("/index.mu"));
+        assert_eq!(bookmarks.list().len(), 1);
+    }
+
+    #[test]
+    fn bookmarks_page() {
+        let mut bookmarks = Bookmarks::new();
+        assert_eq!(
+            bookmarks.to_document().to_string(),
+            ">Bookmarks\n`*No bookmarks yet"
+        );
+
+        bookmarks.add("/index.mu", "Home");
+        bookmarks.add("/docs.mu", "");
+        assert_eq!(
+            bookmarks.to_document().to_string(),
+            ">Bookmarks\n`[Home`/index.mu]\n`[/docs.mu]"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.add("/index.mu", "Home");
+
+        let json = serde_json::to_string(&bookmarks).unwrap();
+        assert_eq!(json, r#"[{"url":"/index.mu","title":"Home"}]"#);
+        let restored: Bookmarks = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, bookmarks);
+    }
+}
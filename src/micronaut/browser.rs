@@ -1,48 +1,270 @@
-use crate::micronaut::ast::{Document, Element, Partial as AstPartial};
-use crate::micronaut::parser::parse;
+use crate::micronaut::ast::{
+    Color, Document, Element, FieldKind, Line, LineKind, LinkElement, Partial as AstPartial, Style,
+};
+use crate::micronaut::date::{format_date, from_days, parse_date, step_date};
+use crate::micronaut::expand::IncludeResolver;
+#[cfg(feature = "ratatui")]
+use crate::micronaut::keys::KeyMap;
+use crate::micronaut::loader::{PageLoader, join_all};
+use crate::micronaut::parser::{ParseOptions, parse_metadata, parse_with, split_tab_index};
+use crate::micronaut::render::{Renderer, line_at_row, line_range};
 use crate::micronaut::types::{
-    FormState, Hitbox, Interactable, Interaction, Link, PartialInfo, PartialState, TextField,
+    DateSegment, FieldCursor, FormState, Heading, Hint, HistoryItem, HistoryLimits, HistoryRecord,
+    Hitbox, Interactable, Interaction, Link, PageEvent, PageLink, PageState, PartialInfo,
+    PartialLimits, PartialState, PendingLoad, PrefetchLimits, RadioDefault, SavedHistory,
+    SelectedElement, TabFilter, TextField, TextSelection, UncheckedCheckbox, ViewState, Viewport,
 };
-use std::collections::HashMap;
+use crate::micronaut::user_style::UserStyle;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::ops::Range;
+use std::time::{Duration, SystemTime};
+
+const SMOOTH_SCROLL_RATE: f32 = 12.0;
+const HINT_CHARS: &str = "asdfghjkl";
+
+type NavigateHook = Box<dyn FnMut(&mut Link) -> bool + Send>;
+
+#[derive(Debug, Clone, Copy)]
+struct ScrollAnimation {
+    position: f32,
+    target: u16,
+}
 
 #[derive(Debug, Clone)]
 struct HistoryEntry {
     url: String,
-    content: String,
+    content: Option<String>,
+    content_hash: Option<u64>,
     scroll: u16,
+    last_used: u64,
+    title: Option<String>,
+    visited: Option<SystemTime>,
+}
+
+impl HistoryEntry {
+    fn record(&self) -> HistoryRecord {
+        HistoryRecord {
+            url: self.url.clone(),
+            scroll: self.scroll,
+            content_hash: self
+                .content
+                .as_deref()
+                .map(content_hash)
+                .or(self.content_hash),
+        }
+    }
+
+    fn restore(record: HistoryRecord) -> Self {
+        Self {
+            url: record.url,
+            content: None,
+            content_hash: record.content_hash,
+            scroll: record.scroll,
+            last_used: 0,
+            title: None,
+            visited: None,
+        }
+    }
+
+    fn item(&self, offset: isize) -> HistoryItem {
+        HistoryItem {
+            url: self.url.clone(),
+            title: self.title.clone(),
+            visited: self.visited,
+            offset,
+        }
+    }
+
+    fn drop_content(&mut self) -> usize {
+        let Some(content) = self.content.take() else {
+            return 0;
+        };
+        self.content_hash = Some(content_hash(&content));
+        content.len()
+    }
+}
+
+#[derive(Debug, Default)]
+struct CachedPage {
+    content: String,
+    last_used: u64,
+    expires: Option<SystemTime>,
+}
+
+impl CachedPage {
+    fn is_fresh(&self) -> bool {
+        match (self.expires, now()) {
+            (Some(expires), Some(now)) => now < expires,
+            _ => true,
+        }
+    }
+}
+
+/// Prefetched pages, evicting the least recently used once over budget and
+/// dropping any past the lifetime its `#!c=` header gave it.
+#[derive(Debug, Default)]
+struct PageCache {
+    pages: HashMap<String, CachedPage>,
+    clock: u64,
+    bytes: usize,
+}
+
+impl PageCache {
+    fn get(&self, url: &str) -> Option<&str> {
+        self.pages
+            .get(url)
+            .filter(|page| page.is_fresh())
+            .map(|page| page.content.as_str())
+    }
+
+    fn touch(&mut self, url: &str) -> Option<&str> {
+        self.clock += 1;
+        if self.pages.get(url).is_some_and(|page| !page.is_fresh()) {
+            self.remove(url);
+        }
+        let page = self.pages.get_mut(url)?;
+        page.last_used = self.clock;
+        Some(page.content.as_str())
+    }
+
+    fn insert(&mut self, url: String, content: String, expires: Option<SystemTime>) {
+        self.clock += 1;
+        self.bytes += content.len();
+        let page = CachedPage {
+            content,
+            last_used: self.clock,
+            expires,
+        };
+        if let Some(old) = self.pages.insert(url, page) {
+            self.bytes -= old.content.len();
+        }
+    }
+
+    fn prune(&mut self, max_bytes: usize) {
+        while self.bytes > max_bytes {
+            let Some(oldest) = self
+                .pages
+                .iter()
+                .min_by_key(|(_, page)| page.last_used)
+                .map(|(url, _)| url.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+
+    fn remove(&mut self, url: &str) {
+        if let Some(page) = self.pages.remove(url) {
+            self.bytes -= page.content.len();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pages.clear();
+        self.bytes = 0;
+    }
 }
 
 pub struct Browser<R: Renderer> {
     pub url: Option<String>,
     pub content: Option<String>,
+    parsed: Option<(u64, Document)>,
+    expected_hash: Option<u64>,
+    state: PageState,
+    scroll_before_error: u16,
     scroll: u16,
+    scroll_x: u16,
+    smooth_scroll: bool,
+    scroll_animation: Option<ScrollAnimation>,
     back_stack: Vec<HistoryEntry>,
     forward_stack: Vec<HistoryEntry>,
+    history_limits: HistoryLimits,
+    history_clock: u64,
+    prefetch: Option<PrefetchLimits>,
+    page_cache: PageCache,
     selected: usize,
+    hovered: Option<usize>,
+    selection: Option<TextSelection>,
+    editing: Option<FieldCursor>,
+    hints: Option<Vec<Hint>>,
+    hint_typed: String,
     hitboxes: Vec<Hitbox>,
+    headings: Vec<Heading>,
+    line_rows: Vec<usize>,
     field_values: HashMap<String, String>,
     checkbox_states: HashMap<String, bool>,
+    checkbox_values: HashMap<String, String>,
+    unchecked_checkbox: UncheckedCheckbox,
     radio_states: HashMap<String, String>,
+    radio_default: RadioDefault,
+    revealed_fields: HashSet<String>,
+    tab_filter: TabFilter,
+    form_touched: bool,
+    drafts: Option<HashMap<String, FormState>>,
+    reading_positions: Option<HashMap<String, u16>>,
+    submit_on_enter: bool,
+    events: Option<Vec<PageEvent>>,
+    linkify: bool,
+    tab_indices: bool,
+    user_style: Option<UserStyle>,
+    page_styles: Vec<(String, UserStyle)>,
     partials: HashMap<String, PartialState>,
+    partial_limits: PartialLimits,
+    partial_loads: Vec<u64>,
+    partial_seeds: HashMap<String, String>,
+    visited: Option<SystemTime>,
+    origin: (u16, u16),
     width: u16,
     height: u16,
     content_height: u16,
+    content_width: u16,
     renderer: R,
     cached_output: Option<R::Output>,
     render_dirty: bool,
+    redraw_pending: bool,
+    on_redraw: Option<Box<dyn FnMut() + Send>>,
+    on_navigate: Option<NavigateHook>,
+    #[cfg(feature = "ratatui")]
+    pub(crate) keymap: KeyMap,
+    #[cfg(feature = "clipboard")]
+    clipboard: Option<arboard::Clipboard>,
 }
 
-pub trait Renderer {
-    type Output;
-    fn render(
-        &self,
-        doc: &Document,
-        width: u16,
-        scroll: u16,
-        form_state: &FormState,
-        partial_contents: &HashMap<String, String>,
-        selected_interactable: Option<usize>,
-    ) -> RenderOutput<Self::Output>;
+fn now() -> Option<SystemTime> {
+    // SystemTime::now panics on wasm32-unknown-unknown
+    if cfg!(target_arch = "wasm32") {
+        None
+    } else {
+        Some(SystemTime::now())
+    }
+}
+
+/// Gives a built document's fields the tab indices a reparse of its
+/// serialized form would, since `#n` name suffixes are only read when
+/// enabled.
+fn match_tab_indices(doc: &mut Document, enabled: bool) {
+    let fields = doc
+        .lines
+        .iter_mut()
+        .flat_map(|line| &mut line.elements)
+        .filter_map(|element| match element {
+            Element::Field(field) => Some(field),
+            _ => None,
+        });
+    for field in fields {
+        if !enabled {
+            if let Some(index) = field.tab_index.take() {
+                field.name = format!("{}#{}", field.name, index);
+            }
+        } else if field.tab_index.is_none()
+            && let (name, Some(index)) = split_tab_index(&field.name, true)
+        {
+            field.name = name.to_string();
+            field.tab_index = Some(index);
+        }
+    }
 }
 
 fn compute_partial_id(partial: &AstPartial) -> String {
@@ -56,10 +278,78 @@ fn compute_partial_id(partial: &AstPartial) -> String {
     format!("{:016x}", hasher.finish())
 }
 
-pub struct RenderOutput<T> {
-    pub content: T,
-    pub hitboxes: Vec<Hitbox>,
-    pub height: u16,
+fn mark_partial_due(state: &mut PartialState, events: &mut Option<Vec<PageEvent>>) {
+    if state.last_updated_secs.take().is_some()
+        && let Some(events) = events
+    {
+        events.push(PageEvent::PartialRefreshDue(state.info.clone()));
+    }
+}
+
+/// A stable offset in `0..=max` so a partial's refreshes drift apart from
+/// others sharing its interval without a random number generator.
+fn partial_jitter(id: &str, updated: u64, max: u32) -> u64 {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    if max == 0 {
+        return 0;
+    }
+    let mut hasher = DefaultHasher::new();
+    (id, updated).hash(&mut hasher);
+    hasher.finish() % (max as u64 + 1)
+}
+
+fn today() -> (i32, u32, u32) {
+    let days = now()
+        .and_then(|now| now.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_secs() / 86_400);
+    from_days(days as i64)
+}
+
+fn is_download(url: &str) -> bool {
+    url.contains(":/file/") || url.starts_with("/file/")
+}
+
+fn column_gap(a: &Hitbox, b: &Hitbox) -> usize {
+    if b.col_end <= a.col_start {
+        a.col_start - b.col_end
+    } else {
+        b.col_start.saturating_sub(a.col_end)
+    }
+}
+
+fn error_document(url: &str, message: &str) -> Document {
+    let mut doc = Document::new();
+    doc.push(Line::heading(1).text("Page failed to load"));
+    doc.push(Line::normal());
+    doc.push(
+        Line::normal()
+            .text("Could not load ")
+            .styled(url, Style::new().bold()),
+    );
+    doc.push_styled(message, Style::new().fg(Color::hex(0xff5555)));
+    doc.push(Line::normal());
+    doc.push(Line::normal().link(LinkElement::new(url).label("Retry")));
+    doc
+}
+
+fn heading_text(line: &Line) -> String {
+    let text: String = line
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            Element::Text(text) => Some(text.text.as_str()),
+            Element::Link(link) => Some(link.label.as_str()),
+            _ => None,
+        })
+        .collect();
+    text.trim().to_string()
+}
+
+fn content_hash(content: &str) -> u64 {
+    content.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
 }
 
 impl<R: Renderer> Browser<R> {
@@ -67,913 +357,4968 @@ impl<R: Renderer> Browser<R> {
         Self {
             url: None,
             content: None,
+            parsed: None,
+            expected_hash: None,
+            state: PageState::Ok,
+            scroll_before_error: 0,
             scroll: 0,
+            scroll_x: 0,
+            smooth_scroll: false,
+            scroll_animation: None,
             back_stack: Vec::new(),
             forward_stack: Vec::new(),
+            history_limits: HistoryLimits::default(),
+            history_clock: 0,
+            prefetch: None,
+            page_cache: PageCache::default(),
             selected: 0,
+            hovered: None,
+            selection: None,
+            editing: None,
+            hints: None,
+            hint_typed: String::new(),
             hitboxes: Vec::new(),
+            headings: Vec::new(),
+            line_rows: Vec::new(),
             field_values: HashMap::new(),
             checkbox_states: HashMap::new(),
+            checkbox_values: HashMap::new(),
+            unchecked_checkbox: UncheckedCheckbox::default(),
             radio_states: HashMap::new(),
+            radio_default: RadioDefault::default(),
+            revealed_fields: HashSet::new(),
+            tab_filter: TabFilter::default(),
+            form_touched: false,
+            drafts: None,
+            reading_positions: None,
+            submit_on_enter: false,
+            events: None,
+            linkify: false,
+            tab_indices: false,
+            user_style: None,
+            page_styles: Vec::new(),
             partials: HashMap::new(),
+            partial_limits: PartialLimits::default(),
+            partial_loads: Vec::new(),
+            partial_seeds: HashMap::new(),
+            visited: None,
+            origin: (0, 0),
             width: 80,
             height: 24,
             content_height: 0,
+            content_width: 0,
             renderer,
             cached_output: None,
             render_dirty: false,
+            redraw_pending: false,
+            on_redraw: None,
+            on_navigate: None,
+            #[cfg(feature = "ratatui")]
+            keymap: KeyMap::default(),
+            #[cfg(feature = "clipboard")]
+            clipboard: None,
         }
     }
 
     pub fn set_content(&mut self, url: &str, content: &str) {
-        if let (Some(old_url), Some(old_content)) = (self.url.take(), self.content.take()) {
-            self.back_stack.push(HistoryEntry {
-                url: old_url,
-                content: old_content,
-                scroll: self.scroll,
-            });
+        if let Some(entry) = self.take_current() {
+            self.back_stack.push(entry);
         }
         self.forward_stack.clear();
-        self.url = Some(url.to_string());
-        self.content = Some(content.to_string());
-        self.scroll = 0;
-        self.clear_form_state();
-        self.rebuild();
+        let scroll = self
+            .reading_positions
+            .as_ref()
+            .and_then(|positions| positions.get(url))
+            .copied()
+            .unwrap_or(0);
+        self.enter(HistoryEntry {
+            url: url.to_string(),
+            content: Some(content.to_string()),
+            content_hash: None,
+            scroll,
+            last_used: 0,
+            title: None,
+            visited: now(),
+        });
     }
 
-    pub fn url(&self) -> Option<&str> {
-        self.url.as_deref()
+    /// Shows an already parsed page without parsing it again. The browser's
+    /// linkify, tab index, width class and style settings still apply, as if
+    /// the page had come through [`set_content`](Self::set_content).
+    pub fn set_document(&mut self, url: &str, mut document: Document) {
+        let content = document.to_string();
+        match_tab_indices(&mut document, self.tab_indices);
+        let document = self.prepare_document(document, url);
+        self.parsed = Some((content_hash(&content), document));
+        self.set_content(url, &content);
     }
 
-    pub fn clear(&mut self) {
-        if let (Some(old_url), Some(old_content)) = (self.url.take(), self.content.take()) {
-            self.back_stack.push(HistoryEntry {
-                url: old_url,
-                content: old_content,
-                scroll: self.scroll,
-            });
+    fn take_current(&mut self) -> Option<HistoryEntry> {
+        self.save_draft();
+        self.save_reading_position();
+        let title = self.title();
+        let url = self.url.take()?;
+        self.history_clock += 1;
+        let mut entry = HistoryEntry {
+            url,
+            content: self.content.take(),
+            content_hash: self.expected_hash.take(),
+            scroll: self.page_scroll(),
+            last_used: self.history_clock,
+            title,
+            visited: self.visited.take(),
+        };
+        if !self.history_limits.store_content {
+            entry.drop_content();
         }
-        self.scroll = 0;
-        self.hitboxes.clear();
-        self.content_height = 0;
-        self.cached_output = None;
-        self.render_dirty = false;
-        self.clear_form_state();
+        Some(entry)
     }
 
-    fn clear_form_state(&mut self) {
-        self.field_values.clear();
-        self.checkbox_states.clear();
-        self.radio_states.clear();
-        self.partials.clear();
-        self.selected = 0;
+    pub fn history_limits(&self) -> HistoryLimits {
+        self.history_limits
     }
 
-    fn form_state(&self) -> FormState {
-        FormState {
-            fields: self.field_values.clone(),
-            checkboxes: self.checkbox_states.clone(),
-            radios: self.radio_states.clone(),
+    pub fn set_history_limits(&mut self, limits: HistoryLimits) {
+        self.history_limits = limits;
+        if !limits.store_content {
+            for entry in self.back_stack.iter_mut().chain(&mut self.forward_stack) {
+                entry.drop_content();
+            }
         }
+        self.prune_history();
     }
 
-    fn partial_contents(&self) -> HashMap<String, String> {
-        self.partials
-            .iter()
-            .filter_map(|(id, state)| state.content.as_ref().map(|c| (id.clone(), c.clone())))
-            .collect()
+    pub fn history_len(&self) -> usize {
+        self.back_stack.len() + self.forward_stack.len()
     }
 
-    fn rebuild(&mut self) {
-        let Some(ref content) = self.content else {
-            self.hitboxes.clear();
-            self.content_height = 0;
-            self.cached_output = None;
-            self.render_dirty = false;
-            return;
-        };
-
-        let doc = parse(content);
-        self.detect_partials(&doc);
-
-        let selected_interactable = self
-            .hitboxes
-            .get(self.selected)
-            .map(|hb| hb.interactable_idx);
-        let output = self.renderer.render(
-            &doc,
-            self.width,
-            self.scroll,
-            &self.form_state(),
-            &self.partial_contents(),
-            selected_interactable,
-        );
-        self.hitboxes = output.hitboxes;
-        self.content_height = output.height;
-        self.cached_output = Some(output.content);
-        self.render_dirty = false;
+    pub fn history_bytes(&self) -> usize {
+        self.back_stack
+            .iter()
+            .chain(&self.forward_stack)
+            .filter_map(|entry| entry.content.as_ref())
+            .map(|content| content.len())
+            .sum()
+    }
 
-        for hitbox in &self.hitboxes {
-            match &hitbox.interactable {
-                Interactable::TextField { name, default, .. } => {
-                    self.field_values
-                        .entry(name.clone())
-                        .or_insert_with(|| default.clone());
-                }
-                Interactable::Checkbox { name } => {
-                    self.checkbox_states.entry(name.clone()).or_insert(false);
-                }
-                Interactable::Radio { name, value } => {
-                    self.radio_states
-                        .entry(name.clone())
-                        .or_insert_with(|| value.clone());
+    fn prune_history(&mut self) {
+        if let Some(max_entries) = self.history_limits.max_entries {
+            while self.history_len() > max_entries {
+                let oldest_back = self.back_stack.first().map(|entry| entry.last_used);
+                let oldest_forward = self.forward_stack.first().map(|entry| entry.last_used);
+                match (oldest_back, oldest_forward) {
+                    (Some(back), Some(forward)) if forward < back => {
+                        self.forward_stack.remove(0);
+                    }
+                    (Some(_), _) => {
+                        self.back_stack.remove(0);
+                    }
+                    (None, _) => {
+                        self.forward_stack.remove(0);
+                    }
                 }
-                Interactable::Link { .. } => {}
             }
         }
-    }
 
-    fn detect_partials(&mut self, doc: &Document) {
-        for line in &doc.lines {
-            for element in &line.elements {
-                if let Element::Partial(partial) = element {
-                    let id = compute_partial_id(partial);
-                    self.partials
-                        .entry(id.clone())
-                        .or_insert_with(|| PartialState {
-                            info: PartialInfo {
-                                id,
-                                url: partial.url.clone(),
-                                refresh: partial.refresh,
-                                fields: partial.fields.clone(),
-                            },
-                            content: None,
-                            last_updated_secs: None,
-                        });
+        if let Some(max_bytes) = self.history_limits.max_bytes {
+            let mut total = self.history_bytes();
+            if total <= max_bytes {
+                return;
+            }
+            let mut entries: Vec<&mut HistoryEntry> = self
+                .back_stack
+                .iter_mut()
+                .chain(&mut self.forward_stack)
+                .filter(|entry| entry.content.is_some())
+                .collect();
+            entries.sort_by_key(|entry| entry.last_used);
+            for entry in entries {
+                if total <= max_bytes {
+                    break;
                 }
+                total -= entry.drop_content();
             }
         }
     }
 
-    fn rerender(&mut self) {
-        let Some(ref content) = self.content else {
-            return;
-        };
-        let doc = parse(content);
-        let selected_interactable = self
-            .hitboxes
-            .get(self.selected)
-            .map(|hb| hb.interactable_idx);
-        let output = self.renderer.render(
-            &doc,
-            self.width,
-            self.scroll,
-            &self.form_state(),
-            &self.partial_contents(),
-            selected_interactable,
-        );
-        self.cached_output = Some(output.content);
-        self.render_dirty = false;
+    fn page_scroll(&self) -> u16 {
+        match self.state {
+            PageState::Error { .. } => self.scroll_before_error,
+            _ => self.scroll,
+        }
     }
 
-    pub fn resize(&mut self, width: u16, height: u16) {
-        let width_changed = self.width != width;
-        self.width = width;
-        self.height = height;
-        if width_changed && self.content.is_some() {
-            self.rebuild();
-        }
+    fn enter(&mut self, entry: HistoryEntry) {
+        self.state = PageState::Ok;
+        self.url = Some(entry.url);
+        self.visited = entry.visited;
+        self.expected_hash = match entry.content {
+            Some(_) => None,
+            None => entry.content_hash,
+        };
+        self.content = entry.content;
+        self.scroll = entry.scroll;
+        self.scroll_x = 0;
+        self.clear_form_state();
+        self.rebuild();
+        self.prune_history();
     }
 
-    pub fn render(&mut self) -> Option<&R::Output> {
-        if self.render_dirty {
-            self.rerender();
+    pub fn needs_content(&self) -> Option<&str> {
+        match (&self.url, &self.content) {
+            (Some(url), None) => Some(url),
+            _ => None,
         }
-        self.cached_output.as_ref()
     }
 
-    pub fn back(&mut self) -> bool {
-        let Some(entry) = self.back_stack.pop() else {
-            return false;
-        };
-        if let (Some(url), Some(content)) = (self.url.take(), self.content.take()) {
-            self.forward_stack.push(HistoryEntry {
-                url,
-                content,
-                scroll: self.scroll,
-            });
+    pub fn provide_content(&mut self, content: &str) {
+        if self.expected_hash.take() != Some(content_hash(content)) {
+            self.scroll = 0;
         }
-        self.url = Some(entry.url);
-        self.content = Some(entry.content);
-        self.scroll = entry.scroll;
+        self.state = PageState::Ok;
+        self.content = Some(content.to_string());
         self.clear_form_state();
         self.rebuild();
-        true
     }
 
-    pub fn forward(&mut self) -> bool {
-        let Some(entry) = self.forward_stack.pop() else {
-            return false;
-        };
-        if let (Some(url), Some(content)) = (self.url.take(), self.content.take()) {
-            self.back_stack.push(HistoryEntry {
-                url,
-                content,
-                scroll: self.scroll,
-            });
+    pub fn save_history(&self, max_entries: usize) -> SavedHistory {
+        let back_start = self.back_stack.len().saturating_sub(max_entries);
+        let forward_start = self.forward_stack.len().saturating_sub(max_entries);
+        SavedHistory {
+            back: self.back_stack[back_start..]
+                .iter()
+                .map(HistoryEntry::record)
+                .collect(),
+            current: self.url.as_ref().map(|url| HistoryRecord {
+                url: url.clone(),
+                scroll: self.page_scroll(),
+                content_hash: self
+                    .content
+                    .as_deref()
+                    .map(content_hash)
+                    .or(self.expected_hash),
+            }),
+            forward: self.forward_stack[forward_start..]
+                .iter()
+                .map(HistoryEntry::record)
+                .collect(),
         }
-        self.url = Some(entry.url);
-        self.content = Some(entry.content);
-        self.scroll = entry.scroll;
-        self.clear_form_state();
-        self.rebuild();
-        true
     }
 
-    pub fn can_go_back(&self) -> bool {
-        !self.back_stack.is_empty()
+    pub fn restore_history(&mut self, history: SavedHistory) {
+        self.back_stack = history
+            .back
+            .into_iter()
+            .map(HistoryEntry::restore)
+            .collect();
+        self.forward_stack = history
+            .forward
+            .into_iter()
+            .map(HistoryEntry::restore)
+            .collect();
+        match history.current {
+            Some(record) => self.enter(HistoryEntry::restore(record)),
+            None => {
+                self.url = None;
+                self.content = None;
+                self.expected_hash = None;
+                self.scroll = 0;
+                self.clear_form_state();
+                self.rebuild();
+            }
+        }
     }
 
-    pub fn can_go_forward(&self) -> bool {
-        !self.forward_stack.is_empty()
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
     }
 
-    pub fn scroll_to(&mut self, y: u16) {
-        let max = self.content_height.saturating_sub(self.height);
-        let new_scroll = y.min(max);
-        if self.scroll != new_scroll {
-            self.scroll = new_scroll;
-            self.render_dirty = true;
-        }
+    pub async fn navigate<L: PageLoader>(&mut self, loader: &L, url: &str) -> Result<(), L::Error> {
+        self.follow(loader, &Link::new(url)).await
     }
 
-    pub fn scroll_by(&mut self, delta: i32) {
-        let new = (self.scroll as i32).saturating_add(delta);
-        self.scroll_to(new.max(0) as u16);
+    pub async fn follow<L: PageLoader>(&mut self, loader: &L, link: &Link) -> Result<(), L::Error> {
+        let Some(pending) = self.begin_follow(link) else {
+            return Ok(());
+        };
+        let result = loader.load(&pending.link).await;
+        self.finish_load(pending, result)
     }
 
-    pub fn scroll(&self) -> u16 {
-        self.scroll
+    pub async fn reload<L: PageLoader>(&mut self, loader: &L) -> Result<(), L::Error> {
+        let Some(pending) = self.begin_reload() else {
+            return Ok(());
+        };
+        let result = loader.load(&pending.link).await;
+        self.finish_load(pending, result)
     }
 
-    pub fn select_next(&mut self) {
-        if !self.hitboxes.is_empty() {
-            self.selected = (self.selected + 1) % self.hitboxes.len();
-            self.ensure_selected_visible();
-            self.render_dirty = true;
+    /// First half of [`follow`](Self::follow): shows a cached page right
+    /// away and returns `None`, or enters `PageState::Loading` and returns
+    /// the load to run. Apps that draw between the two halves show the
+    /// loading state while the page is fetched. Also `None` when the
+    /// [`on_navigate`](Self::on_navigate) interceptor cancels the link. A
+    /// link the interceptor changed always goes to the loader.
+    pub fn begin_follow(&mut self, link: &Link) -> Option<PendingLoad> {
+        let pending = self.intercept(link.clone(), false)?;
+        if pending.link == *link
+            && pending.link.form_data.is_empty()
+            && let Some(content) = self.page_cache.touch(&pending.link.url)
+        {
+            let content = content.to_string();
+            self.set_content(&pending.url, &content);
+            return None;
         }
+        self.set_loading(&link.url, None);
+        Some(pending)
     }
 
-    pub fn select_prev(&mut self) {
-        if !self.hitboxes.is_empty() {
-            self.selected = self
-                .selected
-                .checked_sub(1)
-                .unwrap_or(self.hitboxes.len() - 1);
-            self.ensure_selected_visible();
-            self.render_dirty = true;
+    /// First half of [`reload`](Self::reload); `None` when there's no page
+    /// or the interceptor cancels it.
+    pub fn begin_reload(&mut self) -> Option<PendingLoad> {
+        let url = self.url.clone()?;
+        let pending = self.intercept(Link::new(url.as_str()), true)?;
+        self.set_loading(&url, None);
+        Some(pending)
+    }
+
+    /// Every navigation and reload passes through here, so the interceptor
+    /// sees each load exactly once.
+    fn intercept(&mut self, mut link: Link, reload: bool) -> Option<PendingLoad> {
+        let url = link.url.clone();
+        if let Some(interceptor) = &mut self.on_navigate
+            && !interceptor(&mut link)
+        {
+            return None;
         }
+        Some(PendingLoad { link, url, reload })
     }
 
-    fn ensure_selected_visible(&mut self) {
-        if let Some(hitbox) = self.hitboxes.get(self.selected) {
-            let line = hitbox.line as u16;
-            if line < self.scroll {
-                self.scroll = line;
-            } else if line >= self.scroll + self.height {
-                self.scroll = line.saturating_sub(self.height) + 1;
+    /// Second half of [`follow`](Self::follow) and [`reload`](Self::reload):
+    /// swaps in the loaded page, or shows the error.
+    pub fn finish_load<E: Display>(
+        &mut self,
+        pending: PendingLoad,
+        result: Result<String, E>,
+    ) -> Result<(), E> {
+        let url = pending.url;
+        match result {
+            Ok(content) if pending.reload => {
+                self.reload_content(&content);
+                Ok(())
+            }
+            Ok(content) => {
+                self.set_content(&url, &content);
+                Ok(())
+            }
+            Err(err) => {
+                self.set_error(&url, &err.to_string());
+                Err(err)
             }
         }
     }
 
-    pub fn interact(&mut self) -> Option<Interaction> {
-        let hitbox = self.hitboxes.get(self.selected)?;
+    pub fn prefetch(&self) -> Option<PrefetchLimits> {
+        self.prefetch
+    }
 
-        match &hitbox.interactable {
-            Interactable::Link { url, fields } => {
-                if let Some(rest) = url.strip_prefix("p:") {
-                    let partial_ids: Vec<String> = rest.split(':').map(|s| s.to_string()).collect();
-                    Some(Interaction::RefreshPartials(partial_ids))
-                } else {
-                    Some(Interaction::Link(Link {
-                        url: url.clone(),
-                        fields: fields.clone(),
-                        form_data: self.collect_form_data(fields),
-                    }))
-                }
-            }
-            Interactable::TextField { name, masked, .. } => {
-                let value = self.field_values.get(name).cloned().unwrap_or_default();
-                Some(Interaction::EditField(TextField {
-                    name: name.clone(),
-                    value,
-                    masked: *masked,
-                }))
-            }
-            Interactable::Checkbox { name } => {
-                let current = self.checkbox_states.get(name).copied().unwrap_or(false);
-                self.checkbox_states.insert(name.clone(), !current);
-                self.render_dirty = true;
-                None
+    pub fn set_prefetch(&mut self, limits: Option<PrefetchLimits>) {
+        self.prefetch = limits;
+        self.prune_page_cache();
+    }
+
+    pub fn cached_page(&self, url: &str) -> Option<&str> {
+        self.page_cache.get(url)
+    }
+
+    pub fn clear_page_cache(&mut self) {
+        self.page_cache.clear();
+    }
+
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, PageState::Ok)
+            && self.editing.is_none()
+            && self.scroll_animation.is_none()
+    }
+
+    pub fn prefetch_candidates(&self) -> Vec<Link> {
+        let Some(limits) = self.prefetch else {
+            return Vec::new();
+        };
+        if !self.is_idle() {
+            return Vec::new();
+        }
+        let mut links: Vec<Link> = Vec::new();
+        for hitbox in self.visible_hitboxes() {
+            if links.len() >= limits.max_concurrent {
+                break;
             }
-            Interactable::Radio { name, value } => {
-                self.radio_states.insert(name.clone(), value.clone());
-                self.render_dirty = true;
-                None
+            let Interactable::Link { url, fields, .. } = &hitbox.interactable else {
+                continue;
+            };
+            if !fields.is_empty()
+                || url.starts_with("p:")
+                || is_download(url)
+                || self.url.as_deref() == Some(url.as_str())
+                || self.cached_page(url).is_some()
+                || links.iter().any(|link| link.url == *url)
+            {
+                continue;
             }
+            links.push(Link::new(url.as_str()));
         }
+        links
     }
 
-    pub fn click(&mut self, x: u16, y: u16) -> Option<Interaction> {
-        let doc_y = (y as usize).saturating_add(self.scroll as usize);
-        let doc_x = x as usize;
+    /// Loads [`prefetch_candidates`](Self::prefetch_candidates) without
+    /// borrowing the browser, so it can keep handling input meanwhile; hand
+    /// the result to [`cache_prefetched`](Self::cache_prefetched).
+    pub fn prefetch_idle<'l, L: PageLoader>(
+        &self,
+        loader: &'l L,
+    ) -> impl Future<Output = Vec<(String, String)>> + use<'l, L, R> {
+        let links = self.prefetch_candidates();
+        async move {
+            let results = join_all(links.iter().map(|link| loader.load(link))).await;
+            links
+                .into_iter()
+                .zip(results)
+                .filter_map(|(link, result)| Some((link.url, result.ok()?)))
+                .collect()
+        }
+    }
 
-        for (idx, hitbox) in self.hitboxes.iter().enumerate() {
-            if hitbox.line == doc_y && doc_x >= hitbox.col_start && doc_x < hitbox.col_end {
-                self.selected = idx;
-                self.render_dirty = true;
-                return self.interact();
+    /// Caches pages loaded by [`prefetch_idle`](Self::prefetch_idle),
+    /// returning how many fit. A page whose header says `#!c=0` isn't kept.
+    pub fn cache_prefetched(&mut self, pages: Vec<(String, String)>) -> usize {
+        let mut cached = 0;
+        for (url, content) in pages {
+            if self.cache_page(url, content) {
+                cached += 1;
             }
         }
-        None
+        cached
     }
 
-    fn collect_form_data(&self, field_specs: &[String]) -> HashMap<String, String> {
-        let mut data = HashMap::new();
-        if field_specs.is_empty() {
-            return data;
+    fn cache_page(&mut self, url: String, content: String) -> bool {
+        let Some(limits) = self.prefetch else {
+            return false;
+        };
+        if content.len() > limits.max_bytes {
+            return false;
         }
+        let expires = match parse_metadata(&content).cache() {
+            Some(0) => return false,
+            Some(secs) => now().map(|now| now + Duration::from_secs(secs.into())),
+            None => None,
+        };
+        self.page_cache.insert(url, content, expires);
+        self.prune_page_cache();
+        true
+    }
 
-        let include_all = field_specs.iter().any(|f| f == "*");
-        let mut requested: Vec<&str> = Vec::new();
+    fn prune_page_cache(&mut self) {
+        let max_bytes = self.prefetch.map_or(0, |limits| limits.max_bytes);
+        self.page_cache.prune(max_bytes);
+    }
 
-        for spec in field_specs {
-            if let Some((key, value)) = spec.split_once('=') {
-                data.insert(format!("var_{}", key), value.to_string());
-            } else if spec != "*" {
-                requested.push(spec);
-            }
+    pub fn state(&self) -> &PageState {
+        &self.state
+    }
+
+    pub fn set_loading(&mut self, url: &str, progress: Option<f32>) {
+        let was_error = matches!(self.state, PageState::Error { .. });
+        self.scroll = self.page_scroll();
+        self.state = PageState::Loading {
+            url: url.to_string(),
+            progress: progress.map(|p| p.clamp(0.0, 1.0)),
+        };
+        if was_error {
+            self.rebuild();
+        } else {
+            self.mark_dirty();
         }
+    }
 
-        for (name, value) in &self.field_values {
-            if include_all || requested.iter().any(|f| f == name) {
-                data.insert(format!("field_{}", name), value.clone());
-            }
+    pub fn cancel_loading(&mut self) -> bool {
+        if !self.is_loading() {
+            return false;
+        }
+        self.state = PageState::Ok;
+        self.mark_dirty();
+        true
+    }
+
+    pub fn set_error(&mut self, url: &str, message: &str) {
+        self.scroll_before_error = self.page_scroll();
+        self.state = PageState::Error {
+            url: url.to_string(),
+            message: message.to_string(),
+        };
+        self.scroll = 0;
+        self.selected = 0;
+        self.hovered = None;
+        self.selection = None;
+        self.editing = None;
+        self.rebuild();
+    }
+
+    pub fn clear_error(&mut self) -> bool {
+        if !matches!(self.state, PageState::Error { .. }) {
+            return false;
+        }
+        self.state = PageState::Ok;
+        self.scroll = self.scroll_before_error;
+        self.selected = 0;
+        self.rebuild();
+        true
+    }
+
+    pub fn update_content(&mut self, url: &str, content: &str) -> bool {
+        if self.url.as_deref() != Some(url) || !matches!(self.state, PageState::Ok) {
+            self.set_content(url, content);
+            return true;
+        }
+        let Some(old_doc) = self.take_document() else {
+            self.set_content(url, content);
+            return true;
+        };
+        let new_doc = self.parse_content(content);
+
+        let (old, new) = (&old_doc.lines, &new_doc.lines);
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix == old.len() && prefix == new.len() {
+            self.keep_document(old_doc);
+            return false;
+        }
+        let changed_end = old.len() - suffix;
+        let line_delta = new.len() as isize - old.len() as isize;
+
+        let source_line = |rows: &[usize], row: usize| rows.partition_point(|&r| r <= row);
+        let anchor = self.scroll_anchor();
+        let selected = self.hitboxes.get(self.selected).map(|hitbox| {
+            let line = source_line(&self.line_rows, hitbox.line).saturating_sub(1);
+            (hitbox.interactable_idx, line >= changed_end)
+        });
+        let old_interactables = self.interactable_count();
+
+        self.content = Some(content.to_string());
+        self.parsed = Some((content_hash(content), new_doc));
+        self.hovered = None;
+        self.rebuild();
+
+        let names: Vec<&str> = self
+            .hitboxes
+            .iter()
+            .filter_map(|hitbox| match &hitbox.interactable {
+                Interactable::TextField { name, .. }
+                | Interactable::Checkbox { name, .. }
+                | Interactable::Radio { name, .. } => Some(name.as_str()),
+                Interactable::Link { .. } => None,
+            })
+            .collect();
+        self.field_values
+            .retain(|name, _| names.contains(&name.as_str()));
+        self.checkbox_states
+            .retain(|name, _| names.contains(&name.as_str()));
+        self.radio_states
+            .retain(|name, _| names.contains(&name.as_str()));
+
+        if let Some((idx, after_change)) = selected {
+            let idx = if after_change {
+                (idx as isize + self.interactable_count() as isize - old_interactables as isize)
+                    .max(0) as usize
+            } else {
+                idx
+            };
+            self.selected = self
+                .hitboxes
+                .iter()
+                .position(|hitbox| hitbox.interactable_idx >= idx)
+                .unwrap_or(self.hitboxes.len().saturating_sub(1));
+        }
+        if let Some((line, offset)) = anchor {
+            let line = if line >= changed_end {
+                (line as isize + line_delta) as usize
+            } else {
+                line
+            };
+            self.restore_scroll_anchor(line, offset);
         }
+        self.mark_dirty();
+        true
+    }
+
+    fn interactable_count(&self) -> usize {
+        self.hitboxes
+            .last()
+            .map(|hitbox| hitbox.interactable_idx + 1)
+            .unwrap_or(0)
+    }
+
+    pub fn reload_content(&mut self, content: &str) {
+        let form = FormState {
+            fields: std::mem::take(&mut self.field_values),
+            checkboxes: std::mem::take(&mut self.checkbox_states),
+            radios: std::mem::take(&mut self.radio_states),
+            revealed: HashSet::new(),
+        };
+        let touched = self.form_touched;
+        let partials = std::mem::take(&mut self.partials);
+        let selected = self.selected;
+
+        self.scroll = self.page_scroll();
+        self.state = PageState::Ok;
+        self.content = Some(content.to_string());
+        self.expected_hash = None;
+        self.clear_form_state();
+        self.rebuild();
 
-        for (name, checked) in &self.checkbox_states {
-            if *checked && (include_all || requested.iter().any(|f| f == name)) {
-                data.insert(format!("field_{}", name), "1".to_string());
+        self.apply_form_state(form);
+        self.form_touched = touched;
+        for (id, state) in partials {
+            if let Some(current) = self.partials.get_mut(&id) {
+                *current = state;
             }
         }
+        self.selected = selected.min(self.hitboxes.len().saturating_sub(1));
+        self.scroll = self.scroll.min(self.max_scroll());
+        self.rebuild();
+    }
 
-        for (name, value) in &self.radio_states {
-            if include_all || requested.iter().any(|f| f == name) {
-                data.insert(format!("field_{}", name), value.clone());
+    pub fn title(&self) -> Option<String> {
+        let parsed;
+        let doc = match self.document() {
+            Some(doc) => doc,
+            None => {
+                parsed = self.parse_content(self.content.as_ref()?);
+                &parsed
             }
+        };
+        if let Some(title) = doc.metadata.title() {
+            return Some(title.to_string());
         }
+        let heading = doc
+            .lines
+            .iter()
+            .find(|line| matches!(line.kind, LineKind::Heading(_)))?;
+        let title = heading_text(heading);
+        (!title.is_empty()).then_some(title)
+    }
 
-        data
+    /// The current page as screen reader announcements, with form fields
+    /// read out at their current values.
+    pub fn speech(&self) -> Vec<String> {
+        let parsed;
+        let doc = match self.document() {
+            Some(doc) => doc,
+            None => match &self.content {
+                Some(content) => {
+                    parsed = self.parse_content(content);
+                    &parsed
+                }
+                None => return Vec::new(),
+            },
+        };
+        doc.to_speech_with(&self.form_snapshot())
     }
 
-    pub fn set_field_value(&mut self, name: &str, value: String) {
-        self.field_values.insert(name.to_string(), value);
-        self.render_dirty = true;
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
     }
 
-    pub fn selected_link(&self) -> Option<&str> {
-        let hitbox = self.hitboxes.get(self.selected)?;
-        match &hitbox.interactable {
-            Interactable::Link { url, .. } => Some(url),
+    pub fn scroll_to_heading(&mut self, index: usize) -> bool {
+        let Some(heading) = self.headings.get(index) else {
+            return false;
+        };
+        self.scroll_to(heading.row.min(u16::MAX as usize) as u16);
+        true
+    }
+
+    /// Jumps to the first heading below the current scroll position, skipping
+    /// headings too close to the end of the page to scroll any further to.
+    pub fn next_heading(&mut self) -> bool {
+        let scroll = self.scroll as usize;
+        let max = self.max_scroll() as usize;
+        match self.headings.iter().position(|h| h.row.min(max) > scroll) {
+            Some(index) => self.scroll_to_heading(index),
+            None => false,
+        }
+    }
+
+    pub fn prev_heading(&mut self) -> bool {
+        let scroll = self.scroll as usize;
+        let max = self.max_scroll() as usize;
+        match self.headings.iter().rposition(|h| h.row.min(max) < scroll) {
+            Some(index) => self.scroll_to_heading(index),
+            None => false,
+        }
+    }
+
+    pub fn loading(&self) -> Option<&str> {
+        match &self.state {
+            PageState::Loading { url, .. } => Some(url),
             _ => None,
         }
     }
 
-    pub fn selected_link_fields(&self) -> Option<Vec<(&str, String)>> {
-        let hitbox = self.hitboxes.get(self.selected)?;
-        match &hitbox.interactable {
-            Interactable::Link { fields, .. } if !fields.is_empty() => {
-                let mut result = Vec::new();
-                for spec in fields {
-                    if let Some((key, value)) = spec.split_once('=') {
-                        result.push((key, value.to_string()));
-                    } else if spec != "*" {
-                        let value = self
-                            .field_values
-                            .get(spec)
-                            .or_else(|| self.radio_states.get(spec))
-                            .cloned()
-                            .or_else(|| {
-                                self.checkbox_states
-                                    .get(spec)
-                                    .map(|&c| if c { "1" } else { "0" }.to_string())
-                            })
-                            .unwrap_or_default();
-                        result.push((spec.as_str(), value));
-                    }
-                }
-                Some(result)
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, PageState::Loading { .. })
+    }
+
+    pub fn clear(&mut self) {
+        if let Some(entry) = self.take_current() {
+            self.back_stack.push(entry);
+            self.prune_history();
+        }
+        self.state = PageState::Ok;
+        self.scroll = 0;
+        self.hitboxes.clear();
+        self.content_height = 0;
+        self.cached_output = None;
+        self.render_dirty = false;
+        self.request_redraw();
+        self.clear_form_state();
+    }
+
+    fn apply_form_state(&mut self, form: FormState) {
+        for (name, value) in form.fields {
+            if let Some(current) = self.field_values.get_mut(&name) {
+                *current = value;
+            }
+        }
+        for (name, checked) in form.checkboxes {
+            if let Some(current) = self.checkbox_states.get_mut(&name) {
+                *current = checked;
+            }
+        }
+        for (name, value) in form.radios {
+            if let Some(current) = self.radio_states.get_mut(&name) {
+                *current = value;
+            }
+        }
+    }
+
+    pub fn set_save_drafts(&mut self, enabled: bool) {
+        self.drafts = enabled.then(|| self.drafts.take().unwrap_or_default());
+    }
+
+    pub fn saves_drafts(&self) -> bool {
+        self.drafts.is_some()
+    }
+
+    /// Remembers the scroll position of every page left, and starts pages
+    /// opened with `set_content` / `navigate` where they were last read.
+    pub fn set_remember_positions(&mut self, enabled: bool) {
+        self.reading_positions = enabled.then(|| self.reading_positions.take().unwrap_or_default());
+    }
+
+    pub fn remembers_positions(&self) -> bool {
+        self.reading_positions.is_some()
+    }
+
+    /// Remembered positions including the current page's, for saving across
+    /// sessions. Pages read from the top are left out.
+    pub fn reading_positions(&self) -> HashMap<String, u16> {
+        let mut positions = self.reading_positions.clone().unwrap_or_default();
+        if let (true, Some(url)) = (self.remembers_positions(), &self.url) {
+            match self.page_scroll() {
+                0 => positions.remove(url),
+                scroll => positions.insert(url.clone(), scroll),
+            };
+        }
+        positions
+    }
+
+    pub fn restore_reading_positions(&mut self, positions: HashMap<String, u16>) {
+        self.reading_positions = Some(positions);
+    }
+
+    fn save_reading_position(&mut self) {
+        if !matches!(self.state, PageState::Ok) {
+            return;
+        }
+        let scroll = self.page_scroll();
+        if let (Some(positions), Some(url)) = (self.reading_positions.as_mut(), self.url.as_ref()) {
+            if scroll == 0 {
+                positions.remove(url);
+            } else {
+                positions.insert(url.clone(), scroll);
             }
+        }
+    }
+
+    pub fn set_linkify(&mut self, enabled: bool) {
+        if self.linkify != enabled {
+            self.linkify = enabled;
+            self.parsed = None;
+            self.rebuild();
+        }
+    }
+
+    pub fn linkifies(&self) -> bool {
+        self.linkify
+    }
+
+    /// Honors `#N` tab indices on field names, like `` `<user#2`> ``. Off by
+    /// default, since to other clients `#2` is part of the name.
+    pub fn set_tab_indices(&mut self, enabled: bool) {
+        if self.tab_indices != enabled {
+            self.tab_indices = enabled;
+            self.parsed = None;
+            self.rebuild();
+        }
+    }
+
+    pub fn tab_indices(&self) -> bool {
+        self.tab_indices
+    }
+
+    /// Restyles every page, e.g. to drop backgrounds or enforce a minimum
+    /// contrast.
+    pub fn set_user_style(&mut self, style: Option<UserStyle>) {
+        self.user_style = style;
+        self.parsed = None;
+        self.rebuild();
+    }
+
+    pub fn user_style(&self) -> Option<&UserStyle> {
+        self.user_style.as_ref()
+    }
+
+    /// Uses `style` instead of the user style on urls starting with
+    /// `prefix`; `None` removes the override.
+    pub fn set_page_style(&mut self, prefix: &str, style: Option<UserStyle>) {
+        self.page_styles.retain(|(existing, _)| existing != prefix);
+        if let Some(style) = style {
+            self.page_styles.push((prefix.to_string(), style));
+        }
+        self.parsed = None;
+        self.rebuild();
+    }
+
+    pub fn draft(&self, url: &str) -> Option<&FormState> {
+        self.drafts.as_ref()?.get(url)
+    }
+
+    pub fn has_draft(&self) -> bool {
+        self.url
+            .as_deref()
+            .is_some_and(|url| self.draft(url).is_some())
+    }
+
+    pub fn restore_draft(&mut self) -> bool {
+        let Some(url) = self.url.as_deref() else {
+            return false;
+        };
+        let Some(draft) = self.drafts.as_mut().and_then(|drafts| drafts.remove(url)) else {
+            return false;
+        };
+        self.apply_form_state(draft);
+        self.form_touched = true;
+        self.mark_dirty();
+        true
+    }
+
+    pub fn discard_draft(&mut self, url: &str) -> bool {
+        self.drafts
+            .as_mut()
+            .is_some_and(|drafts| drafts.remove(url).is_some())
+    }
+
+    fn save_draft(&mut self) {
+        if !self.form_touched || !matches!(self.state, PageState::Ok) {
+            return;
+        }
+        let snapshot = FormState {
+            revealed: HashSet::new(),
+            ..self.form_snapshot()
+        };
+        if let (Some(drafts), Some(url)) = (self.drafts.as_mut(), self.url.as_ref()) {
+            drafts.insert(url.clone(), snapshot);
+        }
+    }
+
+    fn clear_form_state(&mut self) {
+        self.form_touched = false;
+        self.field_values.clear();
+        self.checkbox_states.clear();
+        self.checkbox_values.clear();
+        self.radio_states.clear();
+        self.revealed_fields.clear();
+        self.partials.clear();
+        self.selected = 0;
+        self.hovered = None;
+        self.selection = None;
+        self.editing = None;
+        self.hints = None;
+        self.hint_typed.clear();
+    }
+
+    pub fn form_snapshot(&self) -> FormState {
+        FormState {
+            fields: self.field_values.clone(),
+            checkboxes: self.checkbox_states.clone(),
+            radios: self.radio_states.clone(),
+            revealed: self.revealed_fields.clone(),
+        }
+    }
+
+    fn view_state(&self) -> ViewState {
+        ViewState {
+            selected_interactable: self
+                .hitboxes
+                .get(self.selected)
+                .map(|hb| hb.interactable_idx),
+            hovered_interactable: self
+                .hovered
+                .and_then(|idx| self.hitboxes.get(idx))
+                .map(|hb| hb.interactable_idx),
+            selection: self.selection,
+            editing: self.editing.clone(),
+            page_state: self.state.clone(),
+            scroll_x: self.scroll_x,
+            hints: self
+                .hints
+                .iter()
+                .flatten()
+                .filter(|hint| hint.label.starts_with(&self.hint_typed))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn partial_contents(&self) -> HashMap<String, String> {
+        self.partials
+            .iter()
+            .filter_map(|(id, state)| state.content.as_ref().map(|c| (id.clone(), c.clone())))
+            .collect()
+    }
+
+    pub fn document(&self) -> Option<&Document> {
+        let content = self.content.as_deref()?;
+        match &self.parsed {
+            Some((hash, doc)) if *hash == content_hash(content) => Some(doc),
             _ => None,
         }
     }
 
-    pub fn partials_needing_update(&self, now_secs: u64) -> Vec<PartialInfo> {
-        self.partials
-            .values()
-            .filter(
-                |state| match (state.last_updated_secs, state.info.refresh) {
-                    (None, _) => true,
-                    (Some(updated), Some(refresh)) => now_secs >= updated + refresh as u64,
-                    (Some(_), None) => false,
-                },
-            )
-            .map(|state| state.info.clone())
-            .collect()
+    fn take_document(&mut self) -> Option<Document> {
+        if let PageState::Error { url, message } = &self.state {
+            return Some(error_document(url, message));
+        }
+        let content = self.content.as_deref()?;
+        let hash = content_hash(content);
+        match self.parsed.take() {
+            Some((cached, doc)) if cached == hash => Some(doc),
+            _ => Some(self.parse_content(content)),
+        }
+    }
+
+    fn parse_content(&self, content: &str) -> Document {
+        // Keep the literal markers long enough for linkify to skip them.
+        let options = ParseOptions {
+            literal_markers: self.linkify,
+            tab_indices: self.tab_indices,
+            ..Default::default()
+        };
+        let doc = parse_with(content, &options);
+        self.prepare_document(doc, self.url.as_deref().unwrap_or_default())
+    }
+
+    /// Everything [`parse_content`](Self::parse_content) does after parsing.
+    fn prepare_document(&self, mut doc: Document, url: &str) -> Document {
+        if self.linkify {
+            doc.linkify();
+            doc.lines
+                .retain(|line| !matches!(line.kind, LineKind::LiteralStart | LineKind::LiteralEnd));
+        }
+        doc = doc.for_width(doc.width_class(self.width));
+        if let Some(style) = self.user_style_for(url) {
+            doc.apply_user_style(style);
+        }
+        doc
+    }
+
+    /// The page style registered for the longest prefix of `url`, or else
+    /// the user style.
+    fn user_style_for(&self, url: &str) -> Option<&UserStyle> {
+        self.page_styles
+            .iter()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, style)| style)
+            .or(self.user_style.as_ref())
+    }
+
+    fn keep_document(&mut self, doc: Document) {
+        if matches!(self.state, PageState::Error { .. }) {
+            return;
+        }
+        if let Some(content) = &self.content {
+            self.parsed = Some((content_hash(content), doc));
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let Some(doc) = self.take_document() else {
+            self.hitboxes.clear();
+            self.headings.clear();
+            self.line_rows.clear();
+            self.content_height = 0;
+            self.cached_output = None;
+            self.render_dirty = false;
+            self.request_redraw();
+            return;
+        };
+
+        self.detect_partials(&doc);
+
+        let output = self.renderer.render(
+            &doc,
+            self.width,
+            self.scroll,
+            &self.form_snapshot(),
+            &self.partial_contents(),
+            &self.view_state(),
+        );
+        self.headings = doc
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, line)| match line.kind {
+                LineKind::Heading(level) => Some(Heading {
+                    level,
+                    text: heading_text(line),
+                    row: output.line_rows.get(idx).copied().unwrap_or(idx),
+                }),
+                _ => None,
+            })
+            .collect();
+        self.hitboxes = output.hitboxes;
+        self.line_rows = output.line_rows;
+        self.content_height = output.height;
+        self.content_width = output.width;
+        self.scroll_x = self.scroll_x.min(self.max_scroll_x());
+        self.cached_output = Some(output.content);
+        self.render_dirty = false;
+        self.request_redraw();
+        self.keep_document(doc);
+
+        let mut radio_defaults: Vec<(&String, &String, bool)> = Vec::new();
+        for hitbox in &self.hitboxes {
+            match &hitbox.interactable {
+                Interactable::TextField { name, default, .. } => {
+                    self.field_values
+                        .entry(name.clone())
+                        .or_insert_with(|| default.clone());
+                }
+                Interactable::Checkbox {
+                    name,
+                    value,
+                    checked,
+                } => {
+                    self.checkbox_states.entry(name.clone()).or_insert(*checked);
+                    self.checkbox_values.insert(name.clone(), value.clone());
+                }
+                Interactable::Radio {
+                    name,
+                    value,
+                    checked,
+                } => radio_defaults.push((name, value, *checked)),
+                Interactable::Link { .. } => {}
+            }
+        }
+        for &(name, value, _) in &radio_defaults {
+            if self.radio_states.contains_key(name) {
+                continue;
+            }
+            let declared = radio_defaults
+                .iter()
+                .find(|(group, _, checked)| *group == name && *checked)
+                .map(|(_, value, _)| *value);
+            let default = match self.radio_default {
+                RadioDefault::None => None,
+                RadioDefault::First => declared.or(Some(value)),
+                RadioDefault::PageDeclared => declared,
+            };
+            self.radio_states
+                .insert(name.clone(), default.cloned().unwrap_or_default());
+        }
+    }
+
+    fn detect_partials(&mut self, doc: &Document) {
+        for state in self.partials.values_mut() {
+            state.lines.clear();
+        }
+        for (idx, line) in doc.lines.iter().enumerate() {
+            for element in &line.elements {
+                if let Element::Partial(partial) = element {
+                    let id = compute_partial_id(partial);
+                    let state = self
+                        .partials
+                        .entry(id.clone())
+                        .or_insert_with(|| PartialState {
+                            info: PartialInfo {
+                                id,
+                                url: partial.url.clone(),
+                                refresh: partial.refresh,
+                                fields: partial.fields.clone(),
+                            },
+                            content: self.partial_seeds.get(&partial.url).cloned(),
+                            last_updated_secs: None,
+                            lines: Vec::new(),
+                            debounce: None,
+                        });
+                    state.lines.push(idx);
+                    if let (None, Some(events)) = (state.last_updated_secs, &mut self.events)
+                        && !events.iter().any(|event| {
+                            matches!(event, PageEvent::PartialRefreshDue(info) if info.id == state.info.id)
+                        })
+                    {
+                        events.push(PageEvent::PartialRefreshDue(state.info.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn rerender(&mut self) {
+        let Some(doc) = self.take_document() else {
+            return;
+        };
+        let output = self.renderer.render(
+            &doc,
+            self.width,
+            self.scroll,
+            &self.form_snapshot(),
+            &self.partial_contents(),
+            &self.view_state(),
+        );
+        self.cached_output = Some(output.content);
+        self.render_dirty = false;
+        self.keep_document(doc);
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let width_changed = self.width != width;
+        if self
+            .document()
+            .is_some_and(|doc| doc.width_class(self.width) != doc.width_class(width))
+        {
+            self.parsed = None;
+        }
+        self.width = width;
+        self.height = height;
+        if width_changed {
+            let anchor = self.scroll_anchor();
+            self.rebuild();
+            if let Some((line, offset)) = anchor {
+                self.restore_scroll_anchor(line, offset);
+            }
+        }
+    }
+
+    fn scroll_anchor(&self) -> Option<(usize, usize)> {
+        let scroll = self.scroll as usize;
+        let line = self
+            .line_rows
+            .partition_point(|&row| row <= scroll)
+            .checked_sub(1)?;
+        Some((line, scroll - self.line_rows[line]))
+    }
+
+    fn restore_scroll_anchor(&mut self, line: usize, offset: usize) {
+        let Some(&row) = self.line_rows.get(line) else {
+            return;
+        };
+        let next = self
+            .line_rows
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.content_height as usize);
+        let offset = offset.min(next.saturating_sub(row + 1));
+        self.scroll_to((row + offset) as u16);
+    }
+
+    pub fn renderer(&self) -> &R {
+        &self.renderer
+    }
+
+    pub fn update_renderer(&mut self, f: impl FnOnce(&mut R)) {
+        f(&mut self.renderer);
+        self.rebuild();
+    }
+
+    pub fn render(&mut self) -> Option<&R::Output> {
+        if self.render_dirty {
+            self.rerender();
+        }
+        self.redraw_pending = false;
+        self.cached_output.as_ref()
+    }
+
+    pub fn needs_redraw(&self) -> bool {
+        self.redraw_pending
+    }
+
+    pub fn on_redraw(&mut self, callback: impl FnMut() + Send + 'static) {
+        self.on_redraw = Some(Box::new(callback));
+    }
+
+    pub fn clear_on_redraw(&mut self) {
+        self.on_redraw = None;
+    }
+
+    fn mark_dirty(&mut self) {
+        self.render_dirty = true;
+        self.request_redraw();
+    }
+
+    fn request_redraw(&mut self) {
+        if self.redraw_pending {
+            return;
+        }
+        self.redraw_pending = true;
+        if let Some(callback) = &mut self.on_redraw {
+            callback();
+        }
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.clear_error() {
+            return true;
+        }
+        let Some(entry) = self.back_stack.pop() else {
+            return false;
+        };
+        if let Some(current) = self.take_current() {
+            self.forward_stack.push(current);
+        }
+        self.enter(entry);
+        true
+    }
+
+    pub fn forward(&mut self) -> bool {
+        let Some(entry) = self.forward_stack.pop() else {
+            return false;
+        };
+        if let Some(current) = self.take_current() {
+            self.back_stack.push(current);
+        }
+        self.enter(entry);
+        true
+    }
+
+    pub fn go(&mut self, offset: isize) -> bool {
+        let steps = offset.unsigned_abs();
+        let available = match offset < 0 {
+            true => self.back_stack.len(),
+            false => self.forward_stack.len(),
+        };
+        if steps == 0 || steps > available {
+            return false;
+        }
+        self.clear_error();
+        let current = self.take_current();
+        let (from, to) = match offset < 0 {
+            true => (&mut self.back_stack, &mut self.forward_stack),
+            false => (&mut self.forward_stack, &mut self.back_stack),
+        };
+        to.extend(current);
+        for _ in 1..steps {
+            to.extend(from.pop());
+        }
+        let Some(entry) = from.pop() else {
+            return false;
+        };
+        self.enter(entry);
+        true
+    }
+
+    pub fn history(&self) -> Vec<HistoryItem> {
+        let back = self.back_stack.len() as isize;
+        let mut items: Vec<HistoryItem> = self
+            .back_stack
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| entry.item(idx as isize - back))
+            .collect();
+        if let Some(url) = &self.url {
+            items.push(HistoryItem {
+                url: url.clone(),
+                title: self.title(),
+                visited: self.visited,
+                offset: 0,
+            });
+        }
+        items.extend(
+            self.forward_stack
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(idx, entry)| entry.item(idx as isize + 1)),
+        );
+        items
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back_stack.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward_stack.is_empty()
+    }
+
+    pub fn scroll_to(&mut self, y: u16) {
+        let new_scroll = y.min(self.max_scroll());
+        if self.scroll != new_scroll {
+            self.scroll = new_scroll;
+            self.mark_dirty();
+        }
+    }
+
+    pub fn scroll_by(&mut self, delta: i32) {
+        if !self.smooth_scroll {
+            let new = (self.scroll as i32).saturating_add(delta);
+            self.scroll_to(new.max(0) as u16);
+            return;
+        }
+
+        let current = self.active_scroll_animation().unwrap_or(ScrollAnimation {
+            position: self.scroll as f32,
+            target: self.scroll,
+        });
+        let target = (current.target as i32)
+            .saturating_add(delta)
+            .clamp(0, self.max_scroll() as i32) as u16;
+        self.scroll_animation = Some(ScrollAnimation { target, ..current });
+        self.request_redraw();
+    }
+
+    pub fn set_smooth_scroll(&mut self, enabled: bool) {
+        self.smooth_scroll = enabled;
+        if !enabled && let Some(animation) = self.scroll_animation.take() {
+            self.scroll_to(animation.target);
+        }
+    }
+
+    pub fn smooth_scroll(&self) -> bool {
+        self.smooth_scroll
+    }
+
+    pub fn is_scrolling(&self) -> bool {
+        self.active_scroll_animation().is_some()
+    }
+
+    pub fn scroll_offset(&self) -> f32 {
+        self.active_scroll_animation()
+            .map(|animation| animation.position)
+            .unwrap_or(self.scroll as f32)
+    }
+
+    /// Advances smooth scrolling and field debounces for partials. Returns
+    /// whether either still needs ticks.
+    pub fn tick(&mut self, dt: Duration) -> bool {
+        let debouncing = self.tick_partial_debounce(dt);
+        self.tick_scroll(dt) || debouncing
+    }
+
+    fn tick_scroll(&mut self, dt: Duration) -> bool {
+        let Some(mut animation) = self.active_scroll_animation() else {
+            self.scroll_animation = None;
+            return false;
+        };
+        let target = animation.target.min(self.max_scroll()) as f32;
+        let t = 1.0 - (-dt.as_secs_f32() * SMOOTH_SCROLL_RATE).exp();
+        animation.position += (target - animation.position) * t;
+        if (target - animation.position).abs() < 0.5 {
+            animation.position = target;
+        }
+
+        let done = animation.position == target;
+        self.scroll_animation = (!done).then_some(animation);
+        let new_scroll = animation.position.round() as u16;
+        if self.scroll != new_scroll {
+            self.scroll = new_scroll;
+            self.mark_dirty();
+        }
+        !done
+    }
+
+    fn active_scroll_animation(&self) -> Option<ScrollAnimation> {
+        self.scroll_animation
+            .filter(|animation| animation.position.round() as u16 == self.scroll)
+    }
+
+    pub fn scroll_page_up(&mut self) {
+        self.scroll_by(-(self.height.max(1) as i32));
+    }
+
+    pub fn scroll_page_down(&mut self) {
+        self.scroll_by(self.height.max(1) as i32);
+    }
+
+    pub fn scroll_home(&mut self) {
+        self.scroll_to(0);
+    }
+
+    pub fn scroll_end(&mut self) {
+        self.scroll_to(self.max_scroll());
+    }
+
+    pub fn scroll_fraction(&mut self, fraction: f32) {
+        let max = self.max_scroll() as f32;
+        self.scroll_to((max * fraction.clamp(0.0, 1.0)).round() as u16);
+    }
+
+    pub fn scroll_percent(&self) -> u8 {
+        let max = self.max_scroll();
+        if max == 0 {
+            return 100;
+        }
+        (self.scroll.min(max) as u32 * 100 / max as u32) as u8
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        Viewport {
+            width: self.width,
+            height: self.height,
+            content_height: self.content_height,
+            scroll: self.scroll,
+            max_scroll: self.max_scroll(),
+        }
+    }
+
+    pub fn set_origin(&mut self, x: u16, y: u16) {
+        self.origin = (x, y);
+    }
+
+    pub fn origin(&self) -> (u16, u16) {
+        self.origin
+    }
+
+    pub fn to_local(&self, x: u16, y: u16) -> Option<(u16, u16)> {
+        let local_x = x.checked_sub(self.origin.0)?;
+        let local_y = y.checked_sub(self.origin.1)?;
+        (local_x < self.width && local_y < self.height).then_some((local_x, local_y))
+    }
+
+    fn max_scroll(&self) -> u16 {
+        self.content_height.saturating_sub(self.height)
+    }
+
+    pub fn scroll_x(&self) -> u16 {
+        self.scroll_x
+    }
+
+    pub fn scroll_x_to(&mut self, x: u16) {
+        let new_scroll = x.min(self.max_scroll_x());
+        if self.scroll_x != new_scroll {
+            self.scroll_x = new_scroll;
+            self.mark_dirty();
+        }
+    }
+
+    pub fn scroll_horizontal_by(&mut self, delta: i32) {
+        let new = (self.scroll_x as i32).saturating_add(delta);
+        self.scroll_x_to(new.max(0) as u16);
+    }
+
+    fn max_scroll_x(&self) -> u16 {
+        self.content_width.saturating_sub(self.width)
+    }
+
+    pub fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    /// The rendered rows of `document().lines[line]`. Indices follow the
+    /// parsed page after `#@if width` blocks are resolved, so they drift from
+    /// source line numbers past metadata, literal markers and hidden blocks.
+    pub fn document_line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range(&self.line_rows, self.content_height as usize, line)
+    }
+
+    /// The index into `document().lines` rendered on content row `row`.
+    pub fn document_line_at_row(&self, row: usize) -> Option<usize> {
+        line_at_row(&self.line_rows, self.content_height as usize, row)
+    }
+
+    /// Scrolls `document().lines[line]` to the top of the view.
+    pub fn scroll_to_line(&mut self, line: usize) -> bool {
+        let Some(rows) = self.document_line_range(line) else {
+            return false;
+        };
+        self.scroll_to(rows.start.min(u16::MAX as usize) as u16);
+        true
+    }
+
+    pub fn select_next(&mut self) {
+        self.step_tab_order(true);
+    }
+
+    pub fn select_prev(&mut self) {
+        self.step_tab_order(false);
+    }
+
+    pub fn tab_filter(&self) -> TabFilter {
+        self.tab_filter
+    }
+
+    pub fn set_tab_filter(&mut self, filter: TabFilter) {
+        self.tab_filter = filter;
+    }
+
+    fn tab_order(&self) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        let mut order: Vec<usize> = self
+            .hitboxes
+            .iter()
+            .enumerate()
+            .filter(|(_, hb)| {
+                hb.col_end > hb.col_start
+                    && self.tab_filter.allows(&hb.interactable)
+                    && seen.insert(hb.interactable_idx)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        order.sort_by_key(|&idx| match self.hitboxes[idx].tab_index {
+            Some(tab_index) => (0, tab_index),
+            None => (1, 0),
+        });
+        order
+    }
+
+    fn step_tab_order(&mut self, forward: bool) {
+        let order = self.tab_order();
+        if order.is_empty() {
+            return;
+        }
+        let current = self
+            .hitboxes
+            .get(self.selected)
+            .map(|hb| hb.interactable_idx);
+        let position = order
+            .iter()
+            .position(|&idx| Some(self.hitboxes[idx].interactable_idx) == current);
+        let len = order.len();
+        let next = match (position, forward) {
+            (Some(pos), true) => (pos + 1) % len,
+            (Some(pos), false) => (pos + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len - 1,
+        };
+        self.selected = order[next];
+        self.ensure_selected_visible();
+        self.mark_dirty();
+    }
+
+    pub fn focus_field(&mut self, name: &str) -> bool {
+        self.focus_where(|interactable| match interactable {
+            Interactable::TextField { name: field, .. }
+            | Interactable::Checkbox { name: field, .. }
+            | Interactable::Radio { name: field, .. } => field == name,
+            Interactable::Link { .. } => false,
+        })
+    }
+
+    pub fn focus_link_by_url(&mut self, url: &str) -> bool {
+        self.focus_where(
+            |interactable| matches!(interactable, Interactable::Link { url: link, .. } if link == url),
+        )
+    }
+
+    pub fn links(&self) -> Vec<PageLink> {
+        let mut links = Vec::new();
+        let mut last_idx = None;
+        for hitbox in &self.hitboxes {
+            if let Interactable::Link { url, fields, .. } = &hitbox.interactable
+                && last_idx != Some(hitbox.interactable_idx)
+            {
+                last_idx = Some(hitbox.interactable_idx);
+                links.push(PageLink {
+                    url: url.clone(),
+                    fields: fields.clone(),
+                    row: hitbox.line,
+                });
+            }
+        }
+        links
+    }
+
+    /// Labels every interactable in view, `a`, `s`, `d`.. or `aa`, `as`.. when
+    /// there are more than nine, for picking with `hint_input`. Returns
+    /// false when nothing is in view.
+    pub fn enter_hint_mode(&mut self) -> bool {
+        let mut seen = HashSet::new();
+        let targets: Vec<usize> = self
+            .visible_hitboxes()
+            .iter()
+            .map(|hb| hb.interactable_idx)
+            .filter(|&idx| seen.insert(idx))
+            .collect();
+        if targets.is_empty() {
+            return false;
+        }
+        let alphabet: Vec<char> = HINT_CHARS.chars().collect();
+        let mut len = 1;
+        while alphabet.len().pow(len) < targets.len() {
+            len += 1;
+        }
+        let hints = targets
+            .into_iter()
+            .enumerate()
+            .map(|(n, interactable_idx)| {
+                let mut label: Vec<char> = (0..len)
+                    .scan(n, |rest, _| {
+                        let c = alphabet[*rest % alphabet.len()];
+                        *rest /= alphabet.len();
+                        Some(c)
+                    })
+                    .collect();
+                label.reverse();
+                Hint {
+                    label: label.into_iter().collect(),
+                    interactable_idx,
+                }
+            })
+            .collect();
+        self.editing = None;
+        self.hints = Some(hints);
+        self.hint_typed.clear();
+        self.mark_dirty();
+        true
+    }
+
+    pub fn exit_hint_mode(&mut self) {
+        if self.hints.take().is_some() {
+            self.hint_typed.clear();
+            self.mark_dirty();
+        }
+    }
+
+    pub fn in_hint_mode(&self) -> bool {
+        self.hints.is_some()
+    }
+
+    /// Every hint, including ones ruled out by what has been typed.
+    pub fn hints(&self) -> &[Hint] {
+        self.hints.as_deref().unwrap_or_default()
+    }
+
+    /// Narrows the hints by one typed character. Once a label is complete
+    /// its interactable is focused and activated; a character matching no
+    /// label leaves hint mode.
+    pub fn hint_input(&mut self, c: char) -> Option<Interaction> {
+        let hints = self.hints.as_ref()?;
+        self.hint_typed.push(c);
+        let mut matching = hints
+            .iter()
+            .filter(|hint| hint.label.starts_with(&self.hint_typed));
+        let first = matching.next().cloned();
+        let more = matching.next().is_some();
+        match first {
+            Some(hint) if !more && hint.label == self.hint_typed => {
+                self.exit_hint_mode();
+                self.focus_nth_interactable(hint.interactable_idx);
+                self.interact()
+            }
+            Some(_) => {
+                self.mark_dirty();
+                None
+            }
+            None => {
+                self.exit_hint_mode();
+                None
+            }
+        }
+    }
+
+    pub fn select_link_matching(&mut self, predicate: impl Fn(&str) -> bool) -> bool {
+        self.focus_where(
+            |interactable| matches!(interactable, Interactable::Link { url, .. } if predicate(url)),
+        )
+    }
+
+    pub fn focus_nth_interactable(&mut self, n: usize) -> bool {
+        let Some(idx) = self.hitboxes.iter().position(|hb| hb.interactable_idx == n) else {
+            return false;
+        };
+        self.focus(idx);
+        true
+    }
+
+    fn focus_where(&mut self, predicate: impl Fn(&Interactable) -> bool) -> bool {
+        let Some(idx) = self
+            .hitboxes
+            .iter()
+            .position(|hb| predicate(&hb.interactable))
+        else {
+            return false;
+        };
+        self.focus(idx);
+        true
+    }
+
+    fn focus(&mut self, idx: usize) {
+        self.selected = idx;
+        self.ensure_selected_visible();
+        self.mark_dirty();
+    }
+
+    pub fn select_up(&mut self) -> bool {
+        self.select_nearest(|current, hb| {
+            (hb.line < current.line).then(|| (current.line - hb.line, column_gap(current, hb)))
+        })
+    }
+
+    pub fn select_down(&mut self) -> bool {
+        self.select_nearest(|current, hb| {
+            (hb.line > current.line).then(|| (hb.line - current.line, column_gap(current, hb)))
+        })
+    }
+
+    pub fn select_left(&mut self) -> bool {
+        self.select_nearest(|current, hb| {
+            (hb.line == current.line && hb.col_end <= current.col_start)
+                .then(|| (0, current.col_start - hb.col_end))
+        })
+    }
+
+    pub fn select_right(&mut self) -> bool {
+        self.select_nearest(|current, hb| {
+            (hb.line == current.line && hb.col_start >= current.col_end)
+                .then(|| (0, hb.col_start - current.col_end))
+        })
+    }
+
+    fn select_nearest(
+        &mut self,
+        distance: impl Fn(&Hitbox, &Hitbox) -> Option<(usize, usize)>,
+    ) -> bool {
+        let Some(current) = self.hitboxes.get(self.selected) else {
+            return false;
+        };
+        let nearest = self
+            .hitboxes
+            .iter()
+            .enumerate()
+            .filter(|(_, hb)| hb.interactable_idx != current.interactable_idx)
+            .filter_map(|(idx, hb)| distance(current, hb).map(|d| (d, idx)))
+            .min();
+        let Some((_, idx)) = nearest else {
+            return false;
+        };
+        self.focus(idx);
+        true
+    }
+
+    fn ensure_selected_visible(&mut self) {
+        if let Some(hitbox) = self.hitboxes.get(self.selected) {
+            let line = hitbox.line as u16;
+            if line < self.scroll {
+                self.scroll = line;
+            } else if line >= self.scroll + self.height {
+                self.scroll = line.saturating_sub(self.height) + 1;
+            }
+            let (start, end) = (hitbox.col_start as u16, hitbox.col_end as u16);
+            if start < self.scroll_x {
+                self.scroll_x = start;
+            } else if end > self.scroll_x + self.width {
+                self.scroll_x = end.saturating_sub(self.width).min(start);
+            }
+        }
+    }
+
+    pub fn set_selection(&mut self, selection: TextSelection) {
+        self.selection = Some(selection);
+        self.mark_dirty();
+    }
+
+    pub fn start_selection(&mut self, x: u16, y: u16) {
+        let point = self.document_position(x, y);
+        self.set_selection(TextSelection::new(point, point));
+    }
+
+    pub fn extend_selection(&mut self, x: u16, y: u16) {
+        let point = self.document_position(x, y);
+        let start = self.selection.map(|s| s.start).unwrap_or(point);
+        self.set_selection(TextSelection::new(start, point));
+    }
+
+    pub fn clear_selection(&mut self) {
+        if self.selection.take().is_some() {
+            self.mark_dirty();
+        }
+    }
+
+    pub fn selection(&self) -> Option<TextSelection> {
+        self.selection
+    }
+
+    pub fn hitboxes(&self) -> &[Hitbox] {
+        &self.hitboxes
+    }
+
+    pub fn visible_hitboxes(&self) -> Vec<Hitbox> {
+        let top = self.scroll as usize;
+        let bottom = top + self.height as usize;
+        let left = self.scroll_x as usize;
+        let right = left + self.width as usize;
+        self.hitboxes
+            .iter()
+            .filter(|hitbox| {
+                (top..bottom).contains(&hitbox.line)
+                    && hitbox.col_end > left
+                    && hitbox.col_start < right
+            })
+            .map(|hitbox| Hitbox {
+                line: hitbox.line - top,
+                col_start: hitbox.col_start.saturating_sub(left),
+                col_end: hitbox.col_end.min(right) - left,
+                ..hitbox.clone()
+            })
+            .collect()
+    }
+
+    fn document_position(&self, x: u16, y: u16) -> (usize, usize) {
+        (
+            (y as usize).saturating_add(self.scroll as usize),
+            (x as usize).saturating_add(self.scroll_x as usize),
+        )
+    }
+
+    /// Sees every link `navigate`, `follow` and `reload` are about to load,
+    /// and can rewrite it or add form data and headers (the page keeps its
+    /// original url), or return `false` to cancel the load.
+    pub fn on_navigate(&mut self, interceptor: impl FnMut(&mut Link) -> bool + Send + 'static) {
+        self.on_navigate = Some(Box::new(interceptor));
+    }
+
+    pub fn clear_on_navigate(&mut self) {
+        self.on_navigate = None;
+    }
+
+    pub fn interact(&mut self) -> Option<Interaction> {
+        let hitbox = self.hitboxes.get(self.selected)?;
+
+        match &hitbox.interactable {
+            Interactable::Link {
+                url,
+                fields,
+                required,
+            } => {
+                if let Some(rest) = url.strip_prefix("p:") {
+                    let partial_ids: Vec<String> = rest.split(':').map(|s| s.to_string()).collect();
+                    Some(Interaction::RefreshPartials(partial_ids))
+                } else {
+                    let missing = self.missing_required_fields(required);
+                    if let Some(first) = missing.first().cloned() {
+                        self.focus_field(&first);
+                        return Some(Interaction::SubmitBlocked { missing });
+                    }
+                    let link = Link {
+                        url: url.clone(),
+                        fields: fields.clone(),
+                        form_data: self.collect_form_data(fields),
+                        headers: HashMap::new(),
+                    };
+                    if let Some(events) = &mut self.events {
+                        events.push(if is_download(&link.url) {
+                            PageEvent::DownloadRequested(link.clone())
+                        } else {
+                            PageEvent::NavigationRequested(link.clone())
+                        });
+                    }
+                    Some(Interaction::Link(link))
+                }
+            }
+            Interactable::TextField { name, masked, .. } => {
+                let value = self.field_values.get(name).cloned().unwrap_or_default();
+                Some(Interaction::EditField(TextField {
+                    name: name.clone(),
+                    value,
+                    masked: *masked,
+                }))
+            }
+            Interactable::Checkbox { name, .. } => {
+                let name = name.clone();
+                let checked = self.toggle_checkbox(&name);
+                Some(Interaction::Toggled {
+                    name,
+                    value: None,
+                    checked,
+                })
+            }
+            Interactable::Radio { name, value, .. } => {
+                let (name, value) = (name.clone(), value.clone());
+                self.set_radio(&name, &value);
+                Some(Interaction::Toggled {
+                    name,
+                    value: Some(value),
+                    checked: true,
+                })
+            }
+        }
+    }
+
+    pub fn click(&mut self, x: u16, y: u16) -> Option<Interaction> {
+        let idx = self.hitbox_at(x, y)?;
+        self.selected = idx;
+        self.mark_dirty();
+        self.interact()
+    }
+
+    pub fn hover(&mut self, x: u16, y: u16) -> bool {
+        let hovered = self.hitbox_at(x, y);
+        if self.hovered == hovered {
+            return false;
+        }
+        self.hovered = hovered;
+        self.mark_dirty();
+        true
+    }
+
+    pub fn clear_hover(&mut self) -> bool {
+        if self.hovered.take().is_none() {
+            return false;
+        }
+        self.mark_dirty();
+        true
+    }
+
+    pub fn hovered_link(&self) -> Option<&str> {
+        let hitbox = self.hitboxes.get(self.hovered?)?;
+        match &hitbox.interactable {
+            Interactable::Link { url, .. } => Some(url),
+            _ => None,
+        }
+    }
+
+    /// The url of the hovered link, or else the selected one, resolved
+    /// against the current page. With the `clipboard` feature it is also
+    /// copied to the system clipboard when one is available.
+    pub fn copy_selected_url(&mut self) -> Option<String> {
+        let url = self.hovered_link().or_else(|| self.selected_link())?;
+        let url = self.resolve_url(url);
+        #[cfg(feature = "clipboard")]
+        {
+            if self.clipboard.is_none() {
+                self.clipboard = arboard::Clipboard::new().ok();
+            }
+            if let Some(clipboard) = &mut self.clipboard {
+                let _ = clipboard.set_text(url.clone());
+            }
+        }
+        Some(url)
+    }
+
+    /// Gives node-local `:/path` links the destination of the current
+    /// NomadNet page; other urls are returned unchanged.
+    pub fn resolve_url(&self, url: &str) -> String {
+        let Some(current) = self.url.as_deref().filter(|_| url.starts_with(':')) else {
+            return url.to_string();
+        };
+        let rest = current.strip_prefix("nomadnetwork://").unwrap_or(current);
+        match rest.split_once(':') {
+            Some((hash, path))
+                if !hash.is_empty()
+                    && hash.chars().all(|c| c.is_ascii_hexdigit())
+                    && path.starts_with('/') =>
+            {
+                format!("{}{}", &current[..current.len() - path.len() - 1], url)
+            }
+            _ => url.to_string(),
+        }
+    }
+
+    fn hitbox_at(&self, x: u16, y: u16) -> Option<usize> {
+        let (doc_y, doc_x) = self.document_position(x, y);
+        self.hitboxes.iter().position(|hitbox| {
+            hitbox.line == doc_y && doc_x >= hitbox.col_start && doc_x < hitbox.col_end
+        })
+    }
+
+    fn missing_required_fields(&self, required: &[String]) -> Vec<String> {
+        required
+            .iter()
+            .filter(|name| {
+                let filled = self
+                    .field_values
+                    .get(*name)
+                    .is_some_and(|value| !value.trim().is_empty())
+                    || self.checkbox_states.get(*name).copied().unwrap_or(false)
+                    || self.radio(name).is_some();
+                !filled
+            })
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    pub fn collect_form_data(&self, field_specs: &[String]) -> HashMap<String, String> {
+        let mut data = HashMap::new();
+        if field_specs.is_empty() {
+            return data;
+        }
+
+        let include_all = field_specs.iter().any(|f| f == "*");
+        let mut requested: Vec<&str> = Vec::new();
+
+        for spec in field_specs {
+            if let Some((key, value)) = spec.split_once('=') {
+                data.insert(format!("var_{}", key), value.to_string());
+            } else if spec != "*" {
+                requested.push(spec);
+            }
+        }
+
+        if include_all {
+            requested = self
+                .field_values
+                .keys()
+                .chain(self.checkbox_states.keys())
+                .chain(self.radio_states.keys())
+                .map(String::as_str)
+                .collect();
+        }
+        for name in requested {
+            if let Some(value) = self.submitted_value(name) {
+                data.insert(format!("field_{}", name), value);
+            }
+        }
+
+        data
+    }
+
+    /// What a form submission sends for the field `name`, if anything: a
+    /// picked radio, then a checkbox, then a text field.
+    fn submitted_value(&self, name: &str) -> Option<String> {
+        if let Some(value) = self
+            .radio_states
+            .get(name)
+            .filter(|value| !value.is_empty())
+        {
+            return Some(value.clone());
+        }
+        match self.checkbox_states.get(name) {
+            Some(true) => {
+                let value = self
+                    .checkbox_values
+                    .get(name)
+                    .filter(|value| !value.is_empty())
+                    .map_or("1", String::as_str);
+                return Some(value.to_string());
+            }
+            Some(false) if self.unchecked_checkbox == UncheckedCheckbox::Empty => {
+                return Some(String::new());
+            }
+            _ => {}
+        }
+        let value = self.field_values.get(name)?;
+        Some(match self.field_kind(name) {
+            Some(FieldKind::Date) => parse_date(value).map_or(value.clone(), format_date),
+            _ => value.clone(),
+        })
+    }
+
+    pub fn set_field_value(&mut self, name: &str, value: String) {
+        self.field_values.insert(name.to_string(), value);
+        self.form_changed(name);
+    }
+
+    pub fn field_value(&self, name: &str) -> Option<&str> {
+        self.field_values.get(name).map(|value| value.as_str())
+    }
+
+    /// Moves the number field `name` by `steps` of its step, clamped to its
+    /// range, or the date field `name` by `steps` days. A value that isn't a
+    /// number starts over from the minimum, or 0; a date that isn't one from
+    /// today.
+    pub fn step_field(&mut self, name: &str, steps: i64) -> bool {
+        match self.field_kind(name) {
+            Some(FieldKind::Number { min, max, step }) => {
+                let (min, max, step) = (*min, *max, *step);
+                let current = self
+                    .field_values
+                    .get(name)
+                    .and_then(|value| value.trim().parse::<i64>().ok());
+                let next = match current {
+                    Some(current) => current.saturating_add(steps.saturating_mul(step)),
+                    None => min.unwrap_or(0),
+                };
+                let next = next
+                    .max(min.unwrap_or(i64::MIN))
+                    .min(max.unwrap_or(i64::MAX));
+                self.set_stepped_value(name, next.to_string());
+                true
+            }
+            Some(FieldKind::Date) => {
+                let steps = steps.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+                self.step_date(name, DateSegment::Day, steps)
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves one segment of the date field `name`, keeping the day within
+    /// the new month.
+    pub fn step_date(&mut self, name: &str, segment: DateSegment, amount: i32) -> bool {
+        if self.field_kind(name) != Some(&FieldKind::Date) {
+            return false;
+        }
+        let Some(current) = self
+            .field_values
+            .get(name)
+            .and_then(|value| parse_date(value))
+        else {
+            self.set_stepped_value(name, format_date(today()));
+            return true;
+        };
+        self.set_stepped_value(name, format_date(step_date(current, segment, amount)));
+        true
+    }
+
+    /// `step_field` on the selected field, if it is a number or date field.
+    pub fn step_selected(&mut self, steps: i64) -> bool {
+        let Some(Interactable::TextField { name, .. }) = self
+            .hitboxes
+            .get(self.selected)
+            .map(|hitbox| hitbox.interactable.clone())
+        else {
+            return false;
+        };
+        self.step_field(&name, steps)
+    }
+
+    /// Steps the field being edited. Date fields step the segment under the
+    /// cursor.
+    pub fn step_editing(&mut self, steps: i64) -> bool {
+        let Some(editing) = &self.editing else {
+            return false;
+        };
+        let name = editing.name.clone();
+        if self.field_kind(&name) != Some(&FieldKind::Date) {
+            return self.step_field(&name, steps);
+        }
+        let segment = match editing.cursor {
+            0..=4 => DateSegment::Year,
+            5..=7 => DateSegment::Month,
+            _ => DateSegment::Day,
+        };
+        self.step_date(&name, segment, steps.signum() as i32)
+    }
+
+    fn set_stepped_value(&mut self, name: &str, value: String) {
+        let len = value.chars().count();
+        let number = matches!(self.field_kind(name), Some(FieldKind::Number { .. }));
+        self.set_field_value(name, value);
+        if let Some(editing) = self.editing.as_mut().filter(|editing| editing.name == name) {
+            editing.cursor = if number { len } else { editing.cursor.min(len) };
+        }
+    }
+
+    fn field_kind(&self, name: &str) -> Option<&FieldKind> {
+        self.document()?
+            .lines
+            .iter()
+            .flat_map(|line| &line.elements)
+            .find_map(|element| match element {
+                Element::Field(field) if field.name == name => Some(&field.kind),
+                _ => None,
+            })
+    }
+
+    pub fn toggle_reveal(&mut self, name: &str) -> bool {
+        let revealed = !self.revealed_fields.remove(name);
+        if revealed {
+            self.revealed_fields.insert(name.to_string());
+        }
+        self.mark_dirty();
+        revealed
+    }
+
+    pub fn is_revealed(&self, name: &str) -> bool {
+        self.revealed_fields.contains(name)
+    }
+
+    pub fn set_checkbox(&mut self, name: &str, checked: bool) {
+        self.checkbox_states.insert(name.to_string(), checked);
+        self.form_changed(name);
+    }
+
+    pub fn toggle_checkbox(&mut self, name: &str) -> bool {
+        let checked = !self.checkbox(name);
+        self.set_checkbox(name, checked);
+        checked
+    }
+
+    pub fn checkbox(&self, name: &str) -> bool {
+        self.checkbox_states.get(name).copied().unwrap_or(false)
+    }
+
+    /// Checks or unchecks every checkbox whose name starts with `prefix`,
+    /// returning how many there were.
+    pub fn set_group(&mut self, prefix: &str, checked: bool) -> usize {
+        let names = self.group_names(prefix);
+        for name in &names {
+            self.set_checkbox(name, checked);
+        }
+        names.len()
+    }
+
+    /// The values of the checked boxes whose names start with `prefix`, in
+    /// page order.
+    pub fn group_values(&self, prefix: &str) -> Vec<&str> {
+        self.group_names(prefix)
+            .iter()
+            .filter(|name| self.checkbox(name))
+            .map(|name| {
+                self.checkbox_values
+                    .get(name.as_str())
+                    .filter(|value| !value.is_empty())
+                    .map_or("1", String::as_str)
+            })
+            .collect()
+    }
+
+    fn group_names(&self, prefix: &str) -> Vec<String> {
+        let order: Vec<&str> = self
+            .document()
+            .into_iter()
+            .flat_map(|doc| &doc.lines)
+            .flat_map(|line| &line.elements)
+            .filter_map(|element| match element {
+                Element::Field(field) => Some(field.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut names: Vec<String> = self
+            .checkbox_states
+            .keys()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect();
+        names.sort_by_cached_key(|name| {
+            let position = order.iter().position(|field| field == name);
+            (position.unwrap_or(usize::MAX), name.clone())
+        });
+        names
+    }
+
+    pub fn set_radio(&mut self, name: &str, value: &str) {
+        self.radio_states
+            .insert(name.to_string(), value.to_string());
+        self.form_changed(name);
+    }
+
+    pub fn radio(&self, name: &str) -> Option<&str> {
+        self.radio_states
+            .get(name)
+            .map(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn clear_radio(&mut self, name: &str) {
+        if let Some(value) = self.radio_states.get_mut(name) {
+            value.clear();
+            self.form_changed(name);
+        }
+    }
+
+    fn form_changed(&mut self, name: &str) {
+        self.form_touched = true;
+        if let Some(events) = &mut self.events {
+            events.push(match self.checkbox_states.get(name) {
+                Some(&checked) => PageEvent::CheckboxToggled {
+                    name: name.to_string(),
+                    checked,
+                },
+                None => PageEvent::FieldChanged {
+                    name: name.to_string(),
+                    value: self
+                        .field_values
+                        .get(name)
+                        .or_else(|| self.radio_states.get(name))
+                        .cloned()
+                        .unwrap_or_default(),
+                },
+            });
+        }
+        for state in self.partials.values_mut() {
+            let watches = state
+                .info
+                .fields
+                .iter()
+                .any(|spec| spec == "*" || spec == name);
+            if !watches {
+                continue;
+            }
+            let debounce = self.partial_limits.field_debounce;
+            if debounce.is_zero() {
+                mark_partial_due(state, &mut self.events);
+            } else {
+                state.debounce = Some(debounce);
+            }
+        }
+        self.mark_dirty();
+    }
+
+    /// Counts down field debounces, making partials due once theirs runs
+    /// out. Returns whether any are still waiting.
+    fn tick_partial_debounce(&mut self, dt: Duration) -> bool {
+        let mut waiting = false;
+        for state in self.partials.values_mut() {
+            match state.debounce {
+                Some(left) if left > dt => {
+                    state.debounce = Some(left - dt);
+                    waiting = true;
+                }
+                Some(_) => {
+                    state.debounce = None;
+                    mark_partial_due(state, &mut self.events);
+                }
+                None => {}
+            }
+        }
+        waiting
+    }
+
+    /// Starts or stops queueing `PageEvent`s. Off by default so a browser
+    /// nobody polls doesn't grow an unbounded queue.
+    pub fn set_record_events(&mut self, enabled: bool) {
+        self.events = enabled.then(|| self.events.take().unwrap_or_default());
+    }
+
+    /// Takes every event queued since the last call, oldest first.
+    pub fn poll_events(&mut self) -> Vec<PageEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub fn radio_default(&self) -> RadioDefault {
+        self.radio_default
+    }
+
+    pub fn set_radio_default(&mut self, policy: RadioDefault) {
+        self.radio_default = policy;
+    }
+
+    pub fn unchecked_checkbox(&self) -> UncheckedCheckbox {
+        self.unchecked_checkbox
+    }
+
+    pub fn set_unchecked_checkbox(&mut self, policy: UncheckedCheckbox) {
+        self.unchecked_checkbox = policy;
+    }
+
+    pub fn begin_editing(&mut self, name: &str) {
+        let value = self.field_values.entry(name.to_string()).or_default();
+        self.editing = Some(FieldCursor {
+            name: name.to_string(),
+            cursor: value.chars().count(),
+            original: value.clone(),
+        });
+        self.mark_dirty();
+    }
+
+    pub fn stop_editing(&mut self) -> Option<TextField> {
+        let masked = self.editing_masked();
+        let editing = self.editing.take()?;
+        self.mark_dirty();
+        Some(TextField {
+            value: self
+                .field_values
+                .get(&editing.name)
+                .cloned()
+                .unwrap_or_default(),
+            name: editing.name,
+            masked,
+        })
+    }
+
+    /// Stops editing and, with `set_submit_on_enter`, follows the page's
+    /// only submit link that sends the field. Otherwise reports the field
+    /// as submitted.
+    pub fn submit_editing(&mut self) -> Option<Interaction> {
+        let field = self.stop_editing()?;
+        if self.submit_on_enter
+            && let Some(idx) = self.submit_link_for(&field.name)
+        {
+            self.focus(idx);
+            return self.interact();
+        }
+        Some(Interaction::Submitted(field))
+    }
+
+    fn submit_link_for(&self, name: &str) -> Option<usize> {
+        let mut links = self.hitboxes.iter().enumerate().filter(|(_, hitbox)| {
+            matches!(
+                &hitbox.interactable,
+                Interactable::Link { url, fields, .. }
+                    if !url.starts_with("p:") && fields.iter().any(|spec| spec == "*" || spec == name)
+            )
+        });
+        let (idx, first) = links.next()?;
+        links
+            .all(|(_, hitbox)| hitbox.interactable_idx == first.interactable_idx)
+            .then_some(idx)
+    }
+
+    pub fn set_submit_on_enter(&mut self, enabled: bool) {
+        self.submit_on_enter = enabled;
+    }
+
+    pub fn submits_on_enter(&self) -> bool {
+        self.submit_on_enter
+    }
+
+    pub fn cancel_editing(&mut self) -> bool {
+        let Some(editing) = self.editing.take() else {
+            return false;
+        };
+        if self.field_values.get(&editing.name) != Some(&editing.original) {
+            self.field_values
+                .insert(editing.name.clone(), editing.original);
+            self.form_changed(&editing.name);
+        } else {
+            self.mark_dirty();
+        }
+        true
+    }
+
+    pub fn editing(&self) -> Option<&FieldCursor> {
+        self.editing.as_ref()
+    }
+
+    pub(crate) fn editing_masked(&self) -> bool {
+        let Some(editing) = &self.editing else {
+            return false;
+        };
+        self.hitboxes.iter().any(|hb| {
+            matches!(&hb.interactable, Interactable::TextField { name, masked: true, .. } if *name == editing.name)
+        })
+    }
+
+    fn edit_field(&mut self, f: impl FnOnce(&mut Vec<char>, &mut usize)) {
+        let Some(editing) = self.editing.as_mut() else {
+            return;
+        };
+        let value = self.field_values.entry(editing.name.clone()).or_default();
+        let mut chars: Vec<char> = value.chars().collect();
+        editing.cursor = editing.cursor.min(chars.len());
+        f(&mut chars, &mut editing.cursor);
+        let edited: String = chars.into_iter().collect();
+        if *value == edited {
+            self.mark_dirty();
+            return;
+        }
+        *value = edited;
+        let name = editing.name.clone();
+        self.form_changed(&name);
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        self.edit_field(|chars, cursor| {
+            chars.insert(*cursor, c);
+            *cursor += 1;
+        });
+    }
+
+    pub fn input_str(&mut self, text: &str) {
+        self.edit_field(|chars, cursor| {
+            let inserted: Vec<char> = text.chars().filter(|c| !c.is_control()).collect();
+            let count = inserted.len();
+            chars.splice(*cursor..*cursor, inserted);
+            *cursor += count;
+        });
+    }
+
+    pub fn input_backspace(&mut self) {
+        self.edit_field(|chars, cursor| {
+            if *cursor > 0 {
+                *cursor -= 1;
+                chars.remove(*cursor);
+            }
+        });
+    }
+
+    pub fn input_delete(&mut self) {
+        self.edit_field(|chars, cursor| {
+            if *cursor < chars.len() {
+                chars.remove(*cursor);
+            }
+        });
+    }
+
+    pub fn cursor_left(&mut self) {
+        self.edit_field(|_, cursor| *cursor = cursor.saturating_sub(1));
+    }
+
+    pub fn cursor_right(&mut self) {
+        self.edit_field(|chars, cursor| *cursor = (*cursor + 1).min(chars.len()));
+    }
+
+    pub fn cursor_home(&mut self) {
+        self.edit_field(|_, cursor| *cursor = 0);
+    }
+
+    pub fn cursor_end(&mut self) {
+        self.edit_field(|chars, cursor| *cursor = chars.len());
+    }
+
+    pub fn cursor_word_left(&mut self) {
+        self.edit_field(|chars, cursor| {
+            while *cursor > 0 && chars[*cursor - 1].is_whitespace() {
+                *cursor -= 1;
+            }
+            while *cursor > 0 && !chars[*cursor - 1].is_whitespace() {
+                *cursor -= 1;
+            }
+        });
+    }
+
+    pub fn cursor_word_right(&mut self) {
+        self.edit_field(|chars, cursor| {
+            while *cursor < chars.len() && !chars[*cursor].is_whitespace() {
+                *cursor += 1;
+            }
+            while *cursor < chars.len() && chars[*cursor].is_whitespace() {
+                *cursor += 1;
+            }
+        });
+    }
+
+    pub fn selected(&self) -> Option<SelectedElement> {
+        let hitbox = self.hitboxes.get(self.selected)?;
+        Some(match &hitbox.interactable {
+            Interactable::Link { url, fields, .. } => SelectedElement::Link {
+                url: url.clone(),
+                fields: fields.clone(),
+            },
+            Interactable::TextField { name, masked, .. } => SelectedElement::TextField {
+                name: name.clone(),
+                value: self.field_values.get(name).cloned().unwrap_or_default(),
+                masked: *masked,
+            },
+            Interactable::Checkbox { name, value, .. } => SelectedElement::Checkbox {
+                name: name.clone(),
+                value: value.clone(),
+                checked: self.checkbox_states.get(name).copied().unwrap_or(false),
+            },
+            Interactable::Radio { name, value, .. } => SelectedElement::Radio {
+                name: name.clone(),
+                value: value.clone(),
+                checked: self.radio_states.get(name) == Some(value),
+            },
+        })
+    }
+
+    pub fn selected_link(&self) -> Option<&str> {
+        let hitbox = self.hitboxes.get(self.selected)?;
+        match &hitbox.interactable {
+            Interactable::Link { url, .. } => Some(url),
+            _ => None,
+        }
+    }
+
+    pub fn selected_link_fields(&self) -> Option<Vec<(&str, String)>> {
+        let hitbox = self.hitboxes.get(self.selected)?;
+        match &hitbox.interactable {
+            Interactable::Link { fields, .. } if !fields.is_empty() => {
+                let mut result = Vec::new();
+                for spec in fields {
+                    if let Some((key, value)) = spec.split_once('=') {
+                        result.push((key, value.to_string()));
+                    } else if spec != "*"
+                        && let Some(value) = self.submitted_value(spec)
+                    {
+                        result.push((spec.as_str(), value));
+                    }
+                }
+                Some(result)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn partial_limits(&self) -> PartialLimits {
+        self.partial_limits
+    }
+
+    pub fn set_partial_limits(&mut self, limits: PartialLimits) {
+        self.partial_limits = limits;
+    }
+
+    /// Due partials, most overdue and then on-screen first, cut to what
+    /// `PartialLimits` allows to load right now.
+    pub fn partials_needing_update(&self, now_secs: u64) -> Vec<PartialInfo> {
+        let mut due: Vec<_> = self
+            .partials
+            .values()
+            .filter_map(|state| {
+                let due_at = self.partial_due_at(state)?;
+                let order = (!self.partial_visible(state), state.lines.first().copied());
+                (due_at <= now_secs).then_some((due_at, order, state))
+            })
+            .collect();
+        due.sort_by_key(|&(due_at, order, state)| (due_at, order, &state.info.id));
+        due.into_iter()
+            .take(self.partial_budget(now_secs))
+            .map(|(_, _, state)| state.info.clone())
+            .collect()
+    }
+
+    pub fn next_partial_refresh(&self, now_secs: u64) -> Option<u64> {
+        let next = self
+            .partials
+            .values()
+            .filter_map(|state| self.partial_due_at(state))
+            .min()?
+            .saturating_sub(now_secs);
+        if self.partial_budget(now_secs) > 0 {
+            return Some(next);
+        }
+        let freed = self
+            .partial_loads
+            .iter()
+            .min()
+            .map_or(0, |oldest| oldest + 60);
+        Some(next.max(freed.saturating_sub(now_secs)))
+    }
+
+    /// When `state` should next load, or `None` when it shouldn't.
+    fn partial_due_at(&self, state: &PartialState) -> Option<u64> {
+        let limits = &self.partial_limits;
+        let updated = match (state.last_updated_secs, state.info.refresh) {
+            (None, _) => return Some(0),
+            (Some(_), None) => return None,
+            (Some(updated), Some(_)) => updated,
+        };
+        if limits.pause_offscreen && !self.partial_visible(state) {
+            return None;
+        }
+        let interval = state.info.refresh?.max(limits.min_interval_secs) as u64;
+        Some(updated + interval + partial_jitter(&state.info.id, updated, limits.jitter_secs))
+    }
+
+    fn partial_visible(&self, state: &PartialState) -> bool {
+        if self.line_rows.is_empty() || state.lines.is_empty() {
+            return true;
+        }
+        let top = self.scroll as usize;
+        let bottom = top + self.height as usize;
+        state.lines.iter().any(|&line| {
+            let start = self.line_rows.get(line).copied().unwrap_or(usize::MAX);
+            let end = self
+                .line_rows
+                .get(line + 1)
+                .copied()
+                .unwrap_or(self.content_height as usize)
+                .max(start + 1);
+            start < bottom && end > top
+        })
+    }
+
+    fn partial_budget(&self, now_secs: u64) -> usize {
+        let Some(max) = self.partial_limits.max_per_minute else {
+            return usize::MAX;
+        };
+        let recent = self
+            .partial_loads
+            .iter()
+            .filter(|&&at| at + 60 > now_secs)
+            .count();
+        (max as usize).saturating_sub(recent)
+    }
+
+    fn record_partial_load(&mut self, now_secs: u64) {
+        if self.partial_limits.max_per_minute.is_some() {
+            self.partial_loads.retain(|&at| at + 60 > now_secs);
+            self.partial_loads.push(now_secs);
+        }
+    }
+
+    pub async fn refresh_partials<L: PageLoader>(
+        &mut self,
+        loader: &L,
+        now_secs: u64,
+    ) -> Vec<(PartialInfo, L::Error)> {
+        let mut errors = Vec::new();
+        for partial in self.partials_needing_update(now_secs) {
+            let link = self.partial_request(&partial);
+            match loader.load(&link).await {
+                Ok(content) => self.set_partial_content(&partial, content, now_secs),
+                Err(err) => {
+                    if let Some(state) = self.partials.get_mut(&partial.id) {
+                        state.last_updated_secs = Some(now_secs);
+                    }
+                    self.record_partial_load(now_secs);
+                    errors.push((partial, err));
+                }
+            }
+        }
+        errors
+    }
+
+    pub fn set_partial_content(&mut self, partial: &PartialInfo, content: String, now_secs: u64) {
+        if let Some(state) = self.partials.get_mut(&partial.id) {
+            state.content = Some(content);
+            state.last_updated_secs = Some(now_secs);
+            self.record_partial_load(now_secs);
+            self.rebuild();
+        }
+    }
+
+    /// Content shown for partials loading `url` until a load replaces it,
+    /// so pages render in tests, previews and offline without a loader.
+    /// Seeded partials are still due for loading.
+    pub fn seed_partial(&mut self, url: &str, content: &str) {
+        self.partial_seeds
+            .insert(url.to_string(), content.to_string());
+        let mut seeded = false;
+        for state in self.partials.values_mut() {
+            if state.info.url == url && state.last_updated_secs.is_none() {
+                state.content = Some(content.to_string());
+                seeded = true;
+            }
+        }
+        if seeded {
+            self.rebuild();
+        }
+    }
+
+    /// The content currently shown for partials loading `url`.
+    pub fn partial_content(&self, url: &str) -> Option<&str> {
+        self.partials
+            .values()
+            .find(|state| state.info.url == url)
+            .and_then(|state| state.content.as_deref())
+    }
+
+    pub fn clear_partial_seeds(&mut self) {
+        self.partial_seeds.clear();
+    }
+
+    pub fn partial_form_data(&self, partial: &PartialInfo) -> HashMap<String, String> {
+        self.collect_form_data(&partial.fields)
+    }
+
+    pub fn partial_request(&self, partial: &PartialInfo) -> Link {
+        Link {
+            url: partial.url.clone(),
+            fields: partial.fields.clone(),
+            form_data: self.partial_form_data(partial),
+            headers: HashMap::new(),
+        }
+    }
+
+    pub fn has_partials(&self) -> bool {
+        !self.partials.is_empty()
+    }
+}
+
+impl<R: Renderer> IncludeResolver for Browser<R> {
+    fn include(&self, url: &str) -> Option<String> {
+        self.cached_page(url).map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::micronaut::ast::Element;
+    use crate::micronaut::loader::block_on;
+    use crate::micronaut::parser::parse;
+    use crate::micronaut::render::RenderOutput;
+
+    struct NullRenderer;
+
+    struct MapLoader(HashMap<&'static str, &'static str>);
+
+    impl PageLoader for MapLoader {
+        type Error = String;
+
+        async fn load(&self, link: &Link) -> Result<String, String> {
+            if let Some(name) = link.form_data.get("field_name") {
+                return Ok(format!("Hello {}", name));
+            }
+            self.0
+                .get(link.url.as_str())
+                .map(|content| content.to_string())
+                .ok_or_else(|| format!("not found: {}", link.url))
+        }
+    }
+
+    impl Renderer for NullRenderer {
+        type Output = ();
+
+        fn render(
+            &self,
+            doc: &Document,
+            _width: u16,
+            _scroll: u16,
+            _form_state: &FormState,
+            partial_contents: &HashMap<String, String>,
+            _view: &ViewState,
+        ) -> RenderOutput<()> {
+            let mut hitboxes = Vec::new();
+            let mut interactable_idx = 0usize;
+            let mut width = 0;
+            for (line_idx, line) in doc.lines.iter().enumerate() {
+                let mut col = 0;
+                for element in &line.elements {
+                    match element {
+                        Element::Link(link) => {
+                            let len = link.label.len();
+                            hitboxes.push(Hitbox {
+                                line: line_idx,
+                                col_start: col,
+                                col_end: col + len,
+                                interactable: Interactable::Link {
+                                    url: link.url.clone(),
+                                    fields: link.fields.clone(),
+                                    required: link.required.clone(),
+                                },
+                                interactable_idx,
+                                tab_index: None,
+                            });
+                            interactable_idx += 1;
+                            col += len;
+                        }
+                        Element::Field(field) => {
+                            let len = 24;
+                            let interactable = match &field.kind {
+                                FieldKind::Text | FieldKind::Number { .. } | FieldKind::Date => {
+                                    Interactable::TextField {
+                                        name: field.name.clone(),
+                                        masked: field.masked,
+                                        default: field.default.clone(),
+                                    }
+                                }
+                                FieldKind::Checkbox { value, checked } => Interactable::Checkbox {
+                                    name: field.name.clone(),
+                                    value: value.clone(),
+                                    checked: *checked,
+                                },
+                                FieldKind::Radio { value, checked } => Interactable::Radio {
+                                    name: field.name.clone(),
+                                    value: value.clone(),
+                                    checked: *checked,
+                                },
+                            };
+                            hitboxes.push(Hitbox {
+                                line: line_idx,
+                                col_start: col,
+                                col_end: col + len,
+                                interactable,
+                                interactable_idx,
+                                tab_index: field.tab_index,
+                            });
+                            interactable_idx += 1;
+                            col += len;
+                        }
+                        Element::Text(t) => {
+                            col += t.text.len();
+                        }
+                        Element::Progress(progress) => {
+                            col += progress.label.len();
+                        }
+                        Element::Partial(partial) => {
+                            let Some(content) = partial_contents.get(&compute_partial_id(partial))
+                            else {
+                                continue;
+                            };
+                            for partial_line in parse(content).lines {
+                                for element in partial_line.elements {
+                                    if let Element::Link(link) = element {
+                                        let len = link.label.len();
+                                        hitboxes.push(Hitbox {
+                                            line: line_idx,
+                                            col_start: col,
+                                            col_end: col + len,
+                                            interactable: Interactable::Link {
+                                                url: link.url,
+                                                fields: link.fields,
+                                                required: link.required,
+                                            },
+                                            interactable_idx,
+                                            tab_index: None,
+                                        });
+                                        interactable_idx += 1;
+                                        col += len;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                width = width.max(col);
+            }
+            RenderOutput {
+                content: (),
+                hitboxes,
+                height: doc.lines.len() as u16,
+                width: width as u16,
+                line_rows: (0..doc.lines.len()).collect(),
+            }
+        }
+    }
+
+    fn form_state(browser: &mut Browser<NullRenderer>) -> FormState {
+        browser.render();
+        FormState {
+            fields: browser.field_values.clone(),
+            checkboxes: browser.checkbox_states.clone(),
+            radios: browser.radio_states.clone(),
+            revealed: browser.revealed_fields.clone(),
+        }
+    }
+
+    #[test]
+    fn initial_state() {
+        let browser = Browser::new(NullRenderer);
+        assert!(browser.content.is_none());
+        assert!(browser.url().is_none());
+        assert!(!browser.can_go_back());
+        assert!(!browser.can_go_forward());
+    }
+
+    #[test]
+    fn set_content_and_back() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/page1", "Page 1");
+        assert_eq!(browser.url(), Some("/page1"));
+
+        browser.set_content("/page2", "Page 2");
+        assert_eq!(browser.url(), Some("/page2"));
+        assert!(browser.can_go_back());
+        assert!(!browser.can_go_forward());
+
+        browser.back();
+        assert_eq!(browser.url(), Some("/page1"));
+        assert!(browser.can_go_forward());
+
+        browser.forward();
+        assert_eq!(browser.url(), Some("/page2"));
+        assert!(!browser.can_go_forward());
+    }
+
+    #[test]
+    fn scroll() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.resize(80, 10);
+        browser.set_content("/test", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no");
+
+        browser.scroll_by(5);
+        assert_eq!(browser.scroll(), 5);
+
+        browser.scroll_by(-3);
+        assert_eq!(browser.scroll(), 2);
+
+        browser.scroll_to(0);
+        assert_eq!(browser.scroll(), 0);
+    }
+
+    #[test]
+    fn click_link() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`[Click Me`http://target]");
+
+        let interaction = browser.click(3, 0);
+        assert!(interaction.is_some());
+        if let Some(Interaction::Link(link)) = interaction {
+            assert_eq!(link.url, "http://target");
+        } else {
+            panic!("Expected Link interaction");
+        }
+    }
+
+    #[test]
+    fn checkbox_toggle() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<?|agree|yes`I agree>");
+
+        assert!(
+            !form_state(&mut browser)
+                .checkboxes
+                .get("agree")
+                .copied()
+                .unwrap_or(false)
+        );
+        browser.interact();
+        assert!(
+            form_state(&mut browser)
+                .checkboxes
+                .get("agree")
+                .copied()
+                .unwrap_or(false)
+        );
+    }
+
+    #[test]
+    fn text_field_returns_edit_interaction() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<|name`>");
+
+        let interaction = browser.interact();
+        assert!(interaction.is_some());
+        if let Some(Interaction::EditField(field)) = interaction {
+            assert_eq!(field.name, "name");
+            assert_eq!(field.value, "");
+            assert!(!field.masked);
+        } else {
+            panic!("Expected EditField interaction");
+        }
+    }
+
+    #[test]
+    fn text_field_with_default() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<|name`John>");
+
+        assert_eq!(
+            form_state(&mut browser).fields.get("name"),
+            Some(&"John".to_string())
+        );
+
+        let interaction = browser.interact();
+        if let Some(Interaction::EditField(field)) = interaction {
+            assert_eq!(field.value, "John");
+        } else {
+            panic!("Expected EditField interaction");
+        }
+    }
+
+    #[test]
+    fn masked_field() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<!|password`>");
+
+        let interaction = browser.interact();
+        if let Some(Interaction::EditField(field)) = interaction {
+            assert_eq!(field.name, "password");
+            assert!(field.masked);
+        } else {
+            panic!("Expected EditField interaction");
+        }
+    }
+
+    #[test]
+    fn set_field_value() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<|name`>\n`[Submit`/send`name]");
+
+        browser.set_field_value("name", "Alice".to_string());
+
+        browser.select_next();
+        let interaction = browser.interact();
+        if let Some(Interaction::Link(link)) = interaction {
+            assert_eq!(link.form_data.get("field_name"), Some(&"Alice".to_string()));
+        } else {
+            panic!("Expected Link interaction");
+        }
+    }
+
+    #[test]
+    fn form_data_collection() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/test",
+            "`<|user`>\n`<|msg`>\n`[Submit`/send`user|msg|action=go]",
+        );
+
+        browser.set_field_value("user", "A".to_string());
+        browser.set_field_value("msg", "B".to_string());
+
+        browser.select_next();
+        browser.select_next();
+        let interaction = browser.interact();
+
+        if let Some(Interaction::Link(link)) = interaction {
+            assert_eq!(link.url, "/send");
+            assert_eq!(link.form_data.get("field_user"), Some(&"A".to_string()));
+            assert_eq!(link.form_data.get("field_msg"), Some(&"B".to_string()));
+            assert_eq!(link.form_data.get("var_action"), Some(&"go".to_string()));
+        } else {
+            panic!("Expected Link interaction");
+        }
+    }
+
+    #[test]
+    fn select_next_prev_cycles() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`[A`/a]\n`[B`/b]\n`[C`/c]");
+
+        assert_eq!(browser.selected_link(), Some("/a"));
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/b"));
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/c"));
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/a"));
+
+        browser.select_prev();
+        assert_eq!(browser.selected_link(), Some("/c"));
+    }
+
+    #[test]
+    fn radio_button_selection() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/test",
+            "`<^|color|red`Red>\n`<^|color|blue`Blue>\n`<^|color|green`Green>",
+        );
+
+        assert_eq!(
+            form_state(&mut browser).radios.get("color"),
+            Some(&"red".to_string())
+        );
+
+        browser.select_next();
+        browser.interact();
+        assert_eq!(
+            form_state(&mut browser).radios.get("color"),
+            Some(&"blue".to_string())
+        );
+
+        browser.select_next();
+        browser.interact();
+        assert_eq!(
+            form_state(&mut browser).radios.get("color"),
+            Some(&"green".to_string())
+        );
+    }
+
+    #[test]
+    fn update_content_preserves_view_and_form_state() {
+        let chat = |messages: &[&str]| {
+            let mut lines = vec![">Chat".to_string()];
+            lines.extend(messages.iter().map(|m| m.to_string()));
+            lines.extend((0..30).map(|i| format!("old {}", i)));
+            lines.push("`<msg`>".to_string());
+            lines.push("`[Send`/send`msg]".to_string());
+            lines.join("\n")
+        };
+
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/chat", &chat(&[]));
+        browser.set_field_value("msg", "draft".to_string());
+        browser.focus_link_by_url("/send");
+        browser.scroll_to(5);
+
+        assert!(!browser.update_content("/chat", &chat(&[])));
+
+        assert!(browser.update_content("/chat", &chat(&["new 1", "new 2"])));
+        assert_eq!(browser.scroll(), 7);
+        assert_eq!(browser.selected_link(), Some("/send"));
+        assert_eq!(browser.field_value("msg"), Some("draft"));
+        assert!(!browser.can_go_back());
+
+        assert!(browser.update_content("/other", "Other"));
+        assert!(browser.can_go_back());
+    }
+
+    #[test]
+    fn set_document_keeps_parsed_document() {
+        let mut doc = Document::new();
+        doc.push(Line::heading(1).text("Built"));
+        doc.push(Line::normal().link(LinkElement::new("/next").label("Next")));
+
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_document("/built", doc.clone());
+        assert_eq!(browser.content.as_deref(), Some(">Built\n`[Next`/next]"));
+        assert_eq!(browser.document(), Some(&doc));
+        assert_eq!(browser.title().as_deref(), Some("Built"));
+        assert_eq!(browser.selected_link(), Some("/next"));
+
+        browser.content = Some("changed".to_string());
+        assert_eq!(browser.document(), None);
+        browser.resize(40, 10);
+        assert_eq!(browser.document(), Some(&parse("changed")));
+    }
+
+    #[test]
+    fn set_document_matches_set_content() {
+        let text = "see https://example.com/x now\n`<|name#2`>";
+        for tab_indices in [false, true] {
+            let mut from_text = Browser::new(NullRenderer);
+            from_text.set_linkify(true);
+            from_text.set_tab_indices(tab_indices);
+            from_text.set_content("/", text);
+
+            let mut from_doc = Browser::new(NullRenderer);
+            from_doc.set_linkify(true);
+            from_doc.set_tab_indices(tab_indices);
+            from_doc.set_document("/", parse(text));
+
+            assert_eq!(from_doc.links().len(), 1);
+            assert!(from_doc.document().is_some());
+            assert_eq!(from_doc.document(), from_text.document());
+        }
+    }
+
+    #[test]
+    fn links_and_select_link_matching() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/test",
+            "`[A`/a]\n`<name`x>\n`[B`/docs/b`name]\ntext `[C`/docs/c]",
+        );
+
+        let links = browser.links();
+        let urls: Vec<_> = links.iter().map(|link| link.url.as_str()).collect();
+        assert_eq!(urls, ["/a", "/docs/b", "/docs/c"]);
+        assert_eq!(links[1].fields, ["name"]);
+        assert_eq!(links[2].row, 3);
+
+        assert!(browser.select_link_matching(|url| url.starts_with("/docs")));
+        assert_eq!(browser.selected_link(), Some("/docs/b"));
+        assert!(!browser.select_link_matching(|url| url == "/missing"));
+        assert_eq!(browser.selected_link(), Some("/docs/b"));
+    }
+
+    #[test]
+    fn tab_order_honors_tab_index_and_filter() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_tab_indices(true);
+        browser.set_content(
+            "/test",
+            "`[A`/a]\n`<last#3`>\n`[B`/b]\n`<first#1`>\n`<middle#2`>",
+        );
+        let mut visited = Vec::new();
+        for _ in 0..5 {
+            browser.select_next();
+            visited.push(match browser.selected() {
+                Some(SelectedElement::Link { url, .. }) => url,
+                Some(SelectedElement::TextField { name, .. }) => name,
+                other => panic!("unexpected {:?}", other),
+            });
+        }
+        assert_eq!(visited, ["/b", "first", "middle", "last", "/a"]);
+
+        browser.set_tab_filter(TabFilter::Links);
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/b"));
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/a"));
+
+        browser.set_tab_filter(TabFilter::Fields);
+        browser.select_prev();
+        assert!(matches!(
+            browser.selected(),
+            Some(SelectedElement::TextField { name, .. }) if name == "last"
+        ));
+    }
+
+    #[test]
+    fn tab_order_skips_zero_width() {
+        let mut doc = Document::new();
+        doc.push(Line::normal().link(LinkElement::new("/a").label("A")));
+        doc.push(Line::normal().link(LinkElement::new("/hidden").label("")));
+        doc.push(Line::normal().link(LinkElement::new("/b").label("B")));
+
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_document("/test", doc);
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/b"));
+    }
+
+    #[test]
+    fn toggle_reveal_marks_field() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<!pass`>");
+
+        assert!(browser.toggle_reveal("pass"));
+        assert!(browser.is_revealed("pass"));
+        assert!(form_state(&mut browser).revealed.contains("pass"));
+
+        assert!(!browser.toggle_reveal("pass"));
+        assert!(!browser.is_revealed("pass"));
+
+        browser.toggle_reveal("pass");
+        browser.set_content("/next", "`<!pass`>");
+        assert!(!browser.is_revealed("pass"));
+    }
+
+    #[test]
+    fn prefetch_caches_visible_links() {
+        let loader = MapLoader(HashMap::from([
+            ("/a.mu", "Page A"),
+            ("/b.mu", "Page B"),
+            ("/c.mu", "Page C"),
+            ("/file/big.zip", "zip"),
+        ]));
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/",
+            "`[Zip`/file/big.zip]\n`[A`/a.mu]\n`[B`/b.mu]\n`[C`/c.mu]\n`[Form`/a.mu`name]",
+        );
+
+        let pages = block_on(browser.prefetch_idle(&loader));
+        assert_eq!(browser.cache_prefetched(pages), 0);
+
+        browser.set_prefetch(Some(PrefetchLimits {
+            max_concurrent: 2,
+            max_bytes: 12,
+        }));
+        let pages = block_on(browser.prefetch_idle(&loader));
+        assert_eq!(browser.cache_prefetched(pages), 2);
+        assert_eq!(browser.cached_page("/file/big.zip"), None);
+        assert_eq!(browser.cached_page("/a.mu"), Some("Page A"));
+        assert_eq!(browser.cached_page("/b.mu"), Some("Page B"));
+
+        let empty = MapLoader(HashMap::new());
+        block_on(browser.navigate(&empty, "/a.mu")).unwrap();
+        assert_eq!(browser.content.as_deref(), Some("Page A"));
+        assert!(browser.back());
+
+        let pages = block_on(browser.prefetch_idle(&loader));
+        assert_eq!(browser.cache_prefetched(pages), 1);
+        assert_eq!(browser.cached_page("/a.mu"), Some("Page A"));
+        assert_eq!(browser.cached_page("/b.mu"), None);
+        assert_eq!(browser.cached_page("/c.mu"), Some("Page C"));
+    }
+
+    #[test]
+    fn prefetch_honors_cache_header() {
+        let loader = MapLoader(HashMap::from([
+            ("/live.mu", "#!c=0\nLive"),
+            ("/news.mu", "#!c=60\nNews"),
+        ]));
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_prefetch(Some(PrefetchLimits::default()));
+        browser.set_content("/", "`[Live`/live.mu]\n`[News`/news.mu]");
+
+        let pages = block_on(browser.prefetch_idle(&loader));
+        assert_eq!(browser.cache_prefetched(pages), 1);
+        assert_eq!(browser.cached_page("/live.mu"), None);
+        assert_eq!(browser.cached_page("/news.mu"), Some("#!c=60\nNews"));
+
+        browser
+            .page_cache
+            .pages
+            .get_mut("/news.mu")
+            .unwrap()
+            .expires = Some(SystemTime::UNIX_EPOCH);
+        assert_eq!(browser.cached_page("/news.mu"), None);
+        let empty = MapLoader(HashMap::new());
+        assert!(block_on(browser.navigate(&empty, "/news.mu")).is_err());
+        assert!(browser.page_cache.pages.is_empty());
+    }
+
+    #[test]
+    fn navigate_interceptor_rewrites_and_cancels() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "`[Local`:/page/a.mu]\n`[External`http://example.com]");
+        browser.on_navigate(|link| {
+            if link.url.starts_with("http") {
+                return false;
+            }
+            link.url.push_str("?kiosk");
+            link.form_data
+                .insert("var_token".to_string(), "secret".to_string());
+            link.headers
+                .insert("Authorization".to_string(), "Bearer t".to_string());
+            true
+        });
+        browser.set_prefetch(Some(PrefetchLimits::default()));
+        browser.cache_prefetched(vec![(":/page/a.mu".to_string(), "cached".to_string())]);
+
+        let Some(Interaction::Link(link)) = browser.interact() else {
+            panic!("Expected Link interaction");
+        };
+        let pending = browser.begin_follow(&link).unwrap();
+        assert_eq!(pending.link.url, ":/page/a.mu?kiosk");
+        assert_eq!(pending.link.form_data["var_token"], "secret");
+        assert_eq!(pending.link.headers["Authorization"], "Bearer t");
+        browser
+            .finish_load(pending, Ok::<_, String>("A".to_string()))
+            .unwrap();
+        assert_eq!(browser.url(), Some(":/page/a.mu"));
+
+        let loader = MapLoader(HashMap::from([(":/page/a.mu?kiosk", "A again")]));
+        block_on(browser.reload(&loader)).unwrap();
+        assert_eq!(browser.content.as_deref(), Some("A again"));
+
+        block_on(browser.navigate(&loader, "http://example.com")).unwrap();
+        assert_eq!(browser.url(), Some(":/page/a.mu"));
+        assert!(!browser.is_loading());
+
+        browser.clear_on_navigate();
+        assert!(
+            browser
+                .begin_follow(&Link::new("http://example.com"))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn toggles_report_interaction() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<?|news|1`News>\n`<^|color|red`Red>");
+
+        assert!(matches!(
+            browser.interact(),
+            Some(Interaction::Toggled { name, value: None, checked: true }) if name == "news"
+        ));
+        assert!(matches!(
+            browser.interact(),
+            Some(Interaction::Toggled { checked: false, .. })
+        ));
+
+        browser.select_next();
+        assert!(matches!(
+            browser.interact(),
+            Some(Interaction::Toggled { name, value: Some(value), checked: true })
+                if name == "color" && value == "red"
+        ));
+    }
+
+    #[test]
+    fn checkbox_submits_declared_value() {
+        let page = "`<?|news|weekly`News>`<?|terms|`Terms>`<?|spam|yes`Spam>";
+        let fields = ["*".to_string()];
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", page);
+        browser.set_checkbox("news", true);
+        browser.set_checkbox("terms", true);
+        let data = browser.collect_form_data(&fields);
+        assert_eq!(data["field_news"], "weekly");
+        assert_eq!(data["field_terms"], "1");
+        assert!(!data.contains_key("field_spam"));
+
+        browser.set_unchecked_checkbox(UncheckedCheckbox::Empty);
+        assert_eq!(browser.collect_form_data(&fields)["field_spam"], "");
+    }
+
+    #[test]
+    fn link_field_preview_matches_submission() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/test",
+            "`[Go`/go`news|spam|k=v]\n`<?|news|weekly`News>`<?|spam|yes`Spam>",
+        );
+        browser.set_checkbox("news", true);
+        assert_eq!(
+            browser.selected_link_fields(),
+            Some(vec![("news", "weekly".to_string()), ("k", "v".to_string())])
+        );
+
+        browser.set_unchecked_checkbox(UncheckedCheckbox::Empty);
+        assert_eq!(
+            browser.selected_link_fields(),
+            Some(vec![
+                ("news", "weekly".to_string()),
+                ("spam", String::new()),
+                ("k", "v".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn radio_default_policies() {
+        let page = "`<^|size|s`S>\n`<^|size|m|*`M>\n`<^|flavor|a`A>\n`<^|flavor|b`B>";
+
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", page);
+        assert_eq!(browser.radio("size"), Some("m"));
+        assert_eq!(browser.radio("flavor"), Some("a"));
+
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_radio_default(RadioDefault::PageDeclared);
+        browser.set_content("/test", page);
+        assert_eq!(browser.radio("size"), Some("m"));
+        assert_eq!(browser.radio("flavor"), None);
+
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_radio_default(RadioDefault::None);
+        browser.set_content("/test", page);
+        assert_eq!(browser.radio("size"), None);
+        assert_eq!(browser.radio("flavor"), None);
+        assert!(browser.collect_form_data(&["*".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn clear_radio_group() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/test",
+            "`<^|color|red`Red>\n`<^|color|blue`Blue>\n`[Go`/go`!color]",
+        );
+        assert_eq!(browser.radio("color"), Some("red"));
+
+        browser.clear_radio("color");
+        assert_eq!(browser.radio("color"), None);
+
+        browser.focus_link_by_url("/go");
+        assert!(matches!(
+            browser.interact(),
+            Some(Interaction::SubmitBlocked { missing }) if missing == ["color"]
+        ));
+    }
+
+    #[test]
+    fn checkbox_uses_page_declared_state() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "`<?|news|1|*`News>\n`<?|spam|1`Spam>");
+        assert!(browser.checkbox("news"));
+        assert!(!browser.checkbox("spam"));
+    }
+
+    #[test]
+    fn redraw_requested_on_changes() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut browser = Browser::new(NullRenderer);
+        browser.on_redraw({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        assert!(!browser.needs_redraw());
+
+        browser.set_content("/test", &tall_page("Page"));
+        assert!(browser.needs_redraw());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        browser.scroll_by(1);
+        browser.scroll_by(1);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        browser.render();
+        assert!(!browser.needs_redraw());
+
+        browser.set_field_value("name", "x".to_string());
+        assert!(browser.needs_redraw());
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn smooth_scroll_interpolates_on_tick() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", &tall_page("Page"));
+        browser.set_smooth_scroll(true);
+
+        browser.scroll_by(3);
+        browser.scroll_by(3);
+        assert_eq!(browser.scroll(), 0);
+        assert!(browser.is_scrolling());
+
+        assert!(browser.tick(Duration::from_millis(50)));
+        let mid = browser.scroll();
+        assert!(mid > 0 && mid < 6);
+        assert_eq!(browser.scroll_offset().round() as u16, mid);
+
+        while browser.tick(Duration::from_millis(50)) {}
+        assert_eq!(browser.scroll(), 6);
+        assert!(!browser.is_scrolling());
+    }
+
+    #[test]
+    fn smooth_scroll_yields_to_direct_scroll() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", &tall_page("Page"));
+        browser.set_smooth_scroll(true);
+
+        browser.scroll_by(10);
+        browser.scroll_to(2);
+        assert!(!browser.tick(Duration::from_millis(50)));
+        assert_eq!(browser.scroll(), 2);
+
+        browser.scroll_by(10);
+        browser.set_smooth_scroll(false);
+        assert_eq!(browser.scroll(), 12);
+    }
+
+    #[test]
+    fn resize_triggers_rebuild() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/test", "Hello world");
+        browser.render();
+
+        browser.resize(40, 20);
+        assert!(browser.render().is_some());
+    }
+
+    #[test]
+    fn navigation_clears_form_state() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/page1", "`<|name`>");
+
+        browser.set_field_value("name", "X".to_string());
+        assert_eq!(
+            form_state(&mut browser).fields.get("name"),
+            Some(&"X".to_string())
+        );
+
+        browser.set_content("/page2", "`<|name`>");
+        assert_eq!(
+            form_state(&mut browser).fields.get("name"),
+            Some(&"".to_string())
+        );
+    }
+
+    #[test]
+    fn back_preserves_scroll_position() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.resize(80, 10);
+        browser.set_content("/page1", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no");
+        browser.scroll_to(5);
+
+        browser.set_content("/page2", "Page 2");
+        assert_eq!(browser.scroll(), 0);
+
+        browser.back();
+        assert_eq!(browser.scroll(), 5);
+    }
+
+    #[test]
+    fn directory_navigation_simulation() {
+        let mut browser = Browser::new(NullRenderer);
+
+        let index = r#">Welcome
+`[Documents`/docs]
+`[Settings`/settings]
+`[About`/about]"#;
+
+        let docs = r#">Documents
+`[Back`/]
+-
+`[Report.pdf`/docs/report]
+`[Notes.txt`/docs/notes]"#;
+
+        let report = r#">Report
+`[Back to Documents`/docs]
+-
+This is the report content."#;
+
+        browser.set_content("/", index);
+        assert_eq!(browser.url(), Some("/"));
+        assert_eq!(browser.selected_link(), Some("/docs"));
+
+        if let Some(Interaction::Link(link)) = browser.interact() {
+            assert_eq!(link.url, "/docs");
+            browser.set_content("/docs", docs);
+        }
+        assert_eq!(browser.url(), Some("/docs"));
+
+        browser.select_next();
+        assert_eq!(browser.selected_link(), Some("/docs/report"));
+
+        if let Some(Interaction::Link(link)) = browser.interact() {
+            browser.set_content(&link.url, report);
+        }
+        assert_eq!(browser.url(), Some("/docs/report"));
+
+        assert!(browser.can_go_back());
+        browser.back();
+        assert_eq!(browser.url(), Some("/docs"));
+
+        browser.back();
+        assert_eq!(browser.url(), Some("/"));
+
+        browser.forward();
+        assert_eq!(browser.url(), Some("/docs"));
+    }
+
+    #[test]
+    fn login_form_simulation() {
+        let mut browser = Browser::new(NullRenderer);
+
+        let login_page = r#">Login
+`<|username`>
+`<!|password`>
+`[Login`/auth`username|password]"#;
+
+        browser.set_content("/login", login_page);
+
+        browser.set_field_value("username", "alice".to_string());
+        browser.set_field_value("password", "secret123".to_string());
+
+        browser.select_next();
+        browser.select_next();
+
+        if let Some(Interaction::Link(link)) = browser.interact() {
+            assert_eq!(link.url, "/auth");
+            assert_eq!(
+                link.form_data.get("field_username"),
+                Some(&"alice".to_string())
+            );
+            assert_eq!(
+                link.form_data.get("field_password"),
+                Some(&"secret123".to_string())
+            );
+        } else {
+            panic!("Expected Link interaction");
+        }
+    }
+
+    #[test]
+    fn search_with_wildcard_fields() {
+        let mut browser = Browser::new(NullRenderer);
+
+        let search_page = r#"`<|query`>
+`<?|exact|1`Exact match>
+`[Search`/search`*]"#;
+
+        browser.set_content("/search", search_page);
+
+        browser.set_field_value("query", "rust".to_string());
+
+        browser.select_next();
+        browser.interact(); // toggle checkbox
+
+        browser.select_next();
+        if let Some(Interaction::Link(link)) = browser.interact() {
+            assert_eq!(link.url, "/search");
+            assert_eq!(link.form_data.get("field_query"), Some(&"rust".to_string()));
+            assert_eq!(link.form_data.get("field_exact"), Some(&"1".to_string()));
+        } else {
+            panic!("Expected Link interaction");
+        }
+    }
+
+    #[test]
+    fn empty_content_handling() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/empty", "");
+
+        assert!(browser.content.is_some());
+        assert!(browser.render().is_some());
+        assert!(browser.selected_link().is_none());
+
+        browser.select_next();
+        browser.select_prev();
+        assert!(browser.interact().is_none());
+    }
+
+    #[test]
+    fn selection_tracks_drag_and_clears_on_navigation() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.resize(80, 10);
+        browser.set_content("/test", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no");
+        browser.scroll_to(2);
+
+        browser.start_selection(4, 1);
+        browser.extend_selection(2, 3);
+        assert_eq!(
+            browser.selection(),
+            Some(TextSelection::new((3, 4), (5, 2)))
+        );
+        assert_eq!(browser.view_state().selection, browser.selection());
+
+        browser.set_content("/other", "x");
+        assert!(browser.selection().is_none());
+    }
+
+    #[test]
+    fn navigate_loads_content() {
+        let loader = MapLoader(HashMap::from([("/", "`[Docs`/docs]"), ("/docs", "Docs")]));
+        let mut browser = Browser::new(NullRenderer);
+
+        block_on(browser.navigate(&loader, "/")).unwrap();
+        assert_eq!(browser.url(), Some("/"));
+        assert!(!browser.is_loading());
+
+        let Some(Interaction::Link(link)) = browser.interact() else {
+            panic!("Expected Link interaction");
+        };
+        block_on(browser.follow(&loader, &link)).unwrap();
+        assert_eq!(browser.url(), Some("/docs"));
+        assert!(browser.can_go_back());
+    }
+
+    #[test]
+    fn loading_is_visible_between_begin_and_finish() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "Home");
+
+        let pending = browser.begin_follow(&Link::new("/docs")).unwrap();
+        assert_eq!(browser.loading(), Some("/docs"));
+        assert_eq!(browser.url(), Some("/"));
+        browser
+            .finish_load(pending, Ok::<_, String>("Docs".to_string()))
+            .unwrap();
+        assert!(!browser.is_loading());
+        assert_eq!(browser.url(), Some("/docs"));
+
+        let pending = browser.begin_reload().unwrap();
+        assert_eq!(browser.loading(), Some("/docs"));
+        let err = browser.finish_load(pending, Err("offline")).unwrap_err();
+        assert_eq!(err, "offline");
+        assert!(!browser.is_loading());
+        assert_eq!(browser.content.as_deref(), Some("Docs"));
+    }
+
+    #[test]
+    fn navigate_failure_keeps_current_page() {
+        let loader = MapLoader(HashMap::from([("/", "Home")]));
+        let mut browser = Browser::new(NullRenderer);
+        block_on(browser.navigate(&loader, "/")).unwrap();
+
+        let err = block_on(browser.navigate(&loader, "/missing")).unwrap_err();
+        assert_eq!(err, "not found: /missing");
+        assert_eq!(browser.url(), Some("/"));
+        assert!(!browser.is_loading());
+        assert!(!browser.can_go_back());
+    }
+
+    #[test]
+    fn follow_passes_form_data_to_loader() {
+        let loader = MapLoader(HashMap::new());
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", "`<|name`>\n`[Go`/greet`name]");
+        browser.set_field_value("name", "Ada".to_string());
+        browser.select_next();
+
+        let Some(Interaction::Link(link)) = browser.interact() else {
+            panic!("Expected Link interaction");
+        };
+        block_on(browser.follow(&loader, &link)).unwrap();
+        assert_eq!(browser.content.as_deref(), Some("Hello Ada"));
+    }
+
+    #[test]
+    fn refresh_partials_loads_due_partials() {
+        let loader = MapLoader(HashMap::from([
+            ("/clock", "`[Now`/now]"),
+            ("/static", "`[Home`/]"),
+        ]));
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "`{/clock`10}\n`{/static}");
+        browser.render();
+        assert!(browser.hitboxes.is_empty());
+        assert_eq!(browser.next_partial_refresh(0), Some(0));
+
+        let errors = block_on(browser.refresh_partials(&loader, 100));
+        assert!(errors.is_empty());
+        assert_eq!(browser.hitboxes.len(), 2);
+        assert_eq!(browser.next_partial_refresh(104), Some(6));
+        assert!(browser.partials_needing_update(105).is_empty());
+
+        let due = browser.partials_needing_update(110);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].url, "/clock");
+    }
+
+    #[test]
+    fn partial_limits_throttle_refreshes() {
+        let loader = MapLoader(HashMap::new());
+        let mut page = "`{/top`1}\n`{/a`1}\n`{/b`1}\n".to_string();
+        page.push_str(&"filler\n".repeat(50));
+        page.push_str("`{/bottom`1}");
+        let mut browser = Browser::new(NullRenderer);
+        browser.resize(80, 10);
+        browser.set_partial_limits(PartialLimits {
+            max_per_minute: Some(3),
+            min_interval_secs: 30,
+            jitter_secs: 5,
+            pause_offscreen: true,
+            ..Default::default()
+        });
+        browser.set_content("/", &page);
+        browser.render();
+
+        assert_eq!(browser.partials_needing_update(0).len(), 3);
+        block_on(browser.refresh_partials(&loader, 0));
+        assert!(browser.partials_needing_update(1).is_empty());
+        assert_eq!(browser.next_partial_refresh(1), Some(59));
+
+        let due = browser.partials_needing_update(60);
+        assert_eq!(due.len(), 3);
+        assert_eq!(due[0].url, "/bottom");
+        block_on(browser.refresh_partials(&loader, 60));
+        assert_eq!(browser.next_partial_refresh(60), Some(60));
+        let jitter: HashSet<u64> = (0..20).map(|t| partial_jitter("id", t, 5)).collect();
+        assert!(jitter.len() > 1 && jitter.iter().all(|&j| j <= 5));
+
+        browser.scroll_to(browser.viewport().max_scroll);
+        let due = browser.partials_needing_update(200);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].url, "/bottom");
+    }
+
+    #[test]
+    fn field_changes_refresh_partials_after_debounce() {
+        let loader = MapLoader(HashMap::new());
+        let mut browser = Browser::new(NullRenderer);
+        assert_eq!(
+            browser.partial_limits().field_debounce,
+            Duration::from_millis(300)
+        );
+        browser.set_content("/", "`<|q`>\n`{/results``q}");
+        block_on(browser.refresh_partials(&loader, 0));
+
+        browser.begin_editing("q");
+        browser.input_str("ru");
+        assert!(browser.tick(Duration::from_millis(200)));
+        browser.input_char('s');
+        assert!(browser.tick(Duration::from_millis(200)));
+        assert!(browser.partials_needing_update(1).is_empty());
+        assert!(!browser.tick(Duration::from_millis(100)));
+        let due = browser.partials_needing_update(1);
+        assert_eq!(due.len(), 1);
+        assert_eq!(browser.partial_request(&due[0]).form_data["field_q"], "rus");
+    }
+
+    #[test]
+    fn seeded_partials_render_before_loading() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.seed_partial("/news", "`[Story`/story]");
+        browser.set_content("/", "`{/news`60}\n`{/weather}");
+        browser.render();
+        assert_eq!(browser.hitboxes.len(), 1);
+        assert_eq!(browser.partials_needing_update(0).len(), 2);
+
+        browser.seed_partial("/weather", "`[Sunny`/sun]");
+        assert_eq!(browser.hitboxes.len(), 2);
+
+        let loader = MapLoader(HashMap::from([("/news", "nothing new")]));
+        let errors = block_on(browser.refresh_partials(&loader, 0));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(browser.hitboxes.len(), 1);
+        assert!(
+            matches!(&browser.hitboxes[0].interactable, Interactable::Link { url, .. } if url == "/sun")
+        );
+    }
+
+    #[test]
+    fn field_changes_refresh_dependent_partials() {
+        let loader = MapLoader(HashMap::new());
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_partial_limits(PartialLimits {
+            field_debounce: Duration::ZERO,
+            ..Default::default()
+        });
+        browser.set_content("/", "`<name`>\n`{/search``name}\n`{/other`60`x}");
+        block_on(browser.refresh_partials(&loader, 0));
+        assert!(browser.partials_needing_update(1).is_empty());
+
+        browser.begin_editing("name");
+        browser.cursor_left();
+        assert!(browser.partials_needing_update(1).is_empty());
+        browser.input_char('A');
+        let due = browser.partials_needing_update(1);
+        assert_eq!(due.len(), 1);
+        assert_eq!(
+            browser.partial_request(&due[0]).form_data["field_name"],
+            "A"
+        );
+
+        block_on(browser.refresh_partials(&loader, 1));
+        let contents: Vec<_> = browser.partial_contents().into_values().collect();
+        assert!(contents.contains(&"Hello A".to_string()));
+    }
+
+    #[test]
+    fn refresh_partials_reports_failures() {
+        let loader = MapLoader(HashMap::new());
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "`{/missing`30}");
+
+        let errors = block_on(browser.refresh_partials(&loader, 0));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0.url, "/missing");
+        assert_eq!(errors[0].1, "not found: /missing");
+        assert_eq!(browser.next_partial_refresh(0), Some(30));
+    }
+
+    fn tall_page(title: &str) -> String {
+        std::iter::once(title.to_string())
+            .chain((0..50).map(|i| format!("line {}", i)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn history_restores_lazily() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/a", &tall_page("A"));
+        browser.scroll_to(5);
+        browser.set_content("/b", &tall_page("B"));
+        browser.scroll_to(7);
+        browser.set_content("/c", &tall_page("C"));
+        browser.back();
+
+        let saved = browser.save_history(10);
+        assert_eq!(saved.back.len(), 1);
+        assert_eq!(saved.current.as_ref().unwrap().url, "/b");
+        assert_eq!(saved.forward[0].url, "/c");
+
+        let mut restored = Browser::new(NullRenderer);
+        restored.restore_history(saved.clone());
+        assert_eq!(restored.url(), Some("/b"));
+        assert_eq!(restored.needs_content(), Some("/b"));
+        assert_eq!(restored.save_history(10), saved);
+
+        restored.provide_content(&tall_page("B"));
+        assert_eq!(restored.needs_content(), None);
+        assert_eq!(restored.scroll(), 7);
+
+        assert!(restored.back());
+        assert_eq!(restored.needs_content(), Some("/a"));
+        restored.provide_content(&tall_page("A changed"));
+        assert_eq!(restored.scroll(), 0);
+
+        assert!(restored.forward());
+        assert_eq!(restored.needs_content(), None);
+        assert_eq!(restored.scroll(), 7);
     }
 
-    pub fn set_partial_content(&mut self, partial: &PartialInfo, content: String, now_secs: u64) {
-        if let Some(state) = self.partials.get_mut(&partial.id) {
-            state.content = Some(content);
-            state.last_updated_secs = Some(now_secs);
-            self.render_dirty = true;
+    #[test]
+    fn save_history_caps_entries() {
+        let mut browser = Browser::new(NullRenderer);
+        for i in 0..5 {
+            browser.set_content(&format!("/{}", i), "page");
         }
-    }
+        browser.back();
 
-    pub fn partial_form_data(&self, partial: &PartialInfo) -> HashMap<String, String> {
-        self.collect_form_data(&partial.fields)
+        let saved = browser.save_history(2);
+        let back: Vec<_> = saved.back.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(back, ["/1", "/2"]);
+        assert_eq!(saved.current.unwrap().url, "/3");
+        assert_eq!(saved.forward.len(), 1);
     }
 
-    pub fn has_partials(&self) -> bool {
-        !self.partials.is_empty()
+    #[test]
+    fn history_limits_prune_least_recently_used() {
+        let mut browser = Browser::new(NullRenderer);
+        for i in 0..5 {
+            browser.set_content(&format!("/{}", i), "page");
+        }
+        browser.back();
+        browser.back();
+        assert_eq!(browser.history_len(), 4);
+
+        browser.set_history_limits(HistoryLimits {
+            max_entries: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(browser.history_len(), 2);
+        assert!(!browser.can_go_back());
+        assert!(browser.forward());
+        assert_eq!(browser.url(), Some("/3"));
+
+        browser.set_content("/5", "page");
+        browser.set_content("/6", "page");
+        assert_eq!(browser.history_len(), 2);
+        assert!(browser.back());
+        assert_eq!(browser.url(), Some("/5"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::micronaut::ast::{Element, FieldKind};
+    #[test]
+    fn history_byte_budget_drops_oldest_content() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_history_limits(HistoryLimits {
+            max_bytes: Some(8),
+            ..Default::default()
+        });
+        for url in ["/a", "/b", "/c", "/d"] {
+            browser.set_content(url, "page");
+        }
+        assert_eq!(browser.history_len(), 3);
+        assert_eq!(browser.history_bytes(), 8);
 
-    struct NullRenderer;
+        browser.back();
+        browser.back();
+        assert_eq!(browser.needs_content(), None);
+        browser.back();
+        assert_eq!(browser.url(), Some("/a"));
+        assert_eq!(browser.needs_content(), Some("/a"));
+    }
 
-    impl Renderer for NullRenderer {
-        type Output = ();
+    #[test]
+    fn history_can_store_hashes_only() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_history_limits(HistoryLimits {
+            store_content: false,
+            ..Default::default()
+        });
+        browser.set_content("/a", &tall_page("A"));
+        browser.scroll_to(5);
+        browser.set_content("/b", "B");
+        assert_eq!(browser.history_bytes(), 0);
 
-        fn render(
-            &self,
-            doc: &Document,
-            _width: u16,
-            _scroll: u16,
-            _form_state: &FormState,
-            _partial_contents: &HashMap<String, String>,
-            _selected: Option<usize>,
-        ) -> RenderOutput<()> {
-            let mut hitboxes = Vec::new();
-            let mut interactable_idx = 0usize;
-            for (line_idx, line) in doc.lines.iter().enumerate() {
-                let mut col = 0;
-                for element in &line.elements {
-                    match element {
-                        Element::Link(link) => {
-                            let len = link.label.len();
-                            hitboxes.push(Hitbox {
-                                line: line_idx,
-                                col_start: col,
-                                col_end: col + len,
-                                interactable: Interactable::Link {
-                                    url: link.url.clone(),
-                                    fields: link.fields.clone(),
-                                },
-                                interactable_idx,
-                            });
-                            interactable_idx += 1;
-                            col += len;
-                        }
-                        Element::Field(field) => {
-                            let len = 24;
-                            let interactable = match &field.kind {
-                                FieldKind::Text => Interactable::TextField {
-                                    name: field.name.clone(),
-                                    masked: field.masked,
-                                    default: field.default.clone(),
-                                },
-                                FieldKind::Checkbox { .. } => Interactable::Checkbox {
-                                    name: field.name.clone(),
-                                },
-                                FieldKind::Radio { value, .. } => Interactable::Radio {
-                                    name: field.name.clone(),
-                                    value: value.clone(),
-                                },
-                            };
-                            hitboxes.push(Hitbox {
-                                line: line_idx,
-                                col_start: col,
-                                col_end: col + len,
-                                interactable,
-                                interactable_idx,
-                            });
-                            interactable_idx += 1;
-                            col += len;
-                        }
-                        Element::Text(t) => {
-                            col += t.text.len();
-                        }
-                        Element::Partial(_) => {}
-                    }
-                }
-            }
-            RenderOutput {
-                content: (),
-                hitboxes,
-                height: doc.lines.len() as u16,
-            }
-        }
+        browser.back();
+        assert_eq!(browser.needs_content(), Some("/a"));
+        browser.provide_content(&tall_page("A"));
+        assert_eq!(browser.scroll(), 5);
     }
 
-    fn form_state(browser: &mut Browser<NullRenderer>) -> FormState {
-        browser.render();
-        FormState {
-            fields: browser.field_values.clone(),
-            checkboxes: browser.checkbox_states.clone(),
-            radios: browser.radio_states.clone(),
-        }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_history_serde_roundtrip() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/a", "A");
+        browser.set_content("/b", "B");
+
+        let saved = browser.save_history(10);
+        let json = serde_json::to_string(&saved).unwrap();
+        let restored: SavedHistory = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, saved);
     }
 
     #[test]
-    fn initial_state() {
-        let browser = Browser::new(NullRenderer);
-        assert!(browser.content.is_none());
-        assert!(browser.url().is_none());
+    fn reload_preserves_view_and_form_state() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/form",
+            &tall_page("`<|name`>`<?|agree|yes`Agree>`<|old`>`[Go`/go]"),
+        );
+        browser.set_field_value("name", "Ada".to_string());
+        browser.set_field_value("old", "gone".to_string());
+        browser.select_next();
+        browser.interact();
+        browser.scroll_to(10);
+
+        browser.reload_content(&tall_page("`<|name`>`<?|agree|yes`Agree>`[Go`/go]"));
+        let state = form_state(&mut browser);
+        assert_eq!(state.fields.get("name").map(String::as_str), Some("Ada"));
+        assert!(!state.fields.contains_key("old"));
+        assert_eq!(state.checkboxes.get("agree"), Some(&true));
+        assert_eq!(browser.scroll(), 10);
+        assert_eq!(browser.selected, 1);
         assert!(!browser.can_go_back());
-        assert!(!browser.can_go_forward());
     }
 
     #[test]
-    fn set_content_and_back() {
+    fn reload_refetches_current_url() {
+        let loader = MapLoader(HashMap::from([("/", "`<|q`>")]));
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/page1", "Page 1");
-        assert_eq!(browser.url(), Some("/page1"));
+        block_on(browser.reload(&loader)).unwrap();
+        assert_eq!(browser.url(), None);
 
-        browser.set_content("/page2", "Page 2");
-        assert_eq!(browser.url(), Some("/page2"));
-        assert!(browser.can_go_back());
-        assert!(!browser.can_go_forward());
+        block_on(browser.navigate(&loader, "/")).unwrap();
+        browser.set_field_value("q", "kept".to_string());
+        block_on(browser.reload(&loader)).unwrap();
+        assert_eq!(
+            form_state(&mut browser).fields.get("q").map(String::as_str),
+            Some("kept")
+        );
+        assert!(!browser.can_go_back());
+    }
 
-        browser.back();
-        assert_eq!(browser.url(), Some("/page1"));
-        assert!(browser.can_go_forward());
+    #[test]
+    fn field_editing_at_cursor() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", "`<|query`hello world>");
+        browser.input_char('x');
+        assert_eq!(browser.field_values["query"], "hello world");
+
+        browser.begin_editing("query");
+        assert_eq!(browser.editing().unwrap().cursor, 11);
+        browser.cursor_word_left();
+        assert_eq!(browser.editing().unwrap().cursor, 6);
+        browser.input_char('a');
+        browser.input_char(' ');
+        browser.cursor_home();
+        browser.input_delete();
+        browser.cursor_word_right();
+        browser.input_backspace();
+        assert_eq!(browser.field_values["query"], "elloa world");
+        assert_eq!(browser.editing().unwrap().cursor, 4);
+
+        browser.cursor_end();
+        browser.cursor_right();
+        browser.input_char('!');
+        browser.cursor_left();
+        browser.cursor_left();
+        assert_eq!(browser.editing().unwrap().cursor, 10);
+
+        let field = browser.stop_editing().unwrap();
+        assert_eq!(field.name, "query");
+        assert_eq!(field.value, "elloa world!");
+        assert!(browser.editing().is_none());
+    }
 
-        browser.forward();
-        assert_eq!(browser.url(), Some("/page2"));
+    #[test]
+    fn history_items_and_jumps() {
+        let mut browser = Browser::new(NullRenderer);
+        assert!(browser.history().is_empty());
+        browser.set_content("/a", ">Page A");
+        browser.set_content("/b", "no heading");
+        browser.set_content("/c", ">Page C");
+        browser.set_content("/d", ">Page D");
+        assert!(browser.back());
+
+        let history = browser.history();
+        let summary: Vec<(&str, Option<&str>, isize)> = history
+            .iter()
+            .map(|item| (item.url.as_str(), item.title.as_deref(), item.offset))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/a", Some("Page A"), -2),
+                ("/b", None, -1),
+                ("/c", Some("Page C"), 0),
+                ("/d", Some("Page D"), 1),
+            ]
+        );
+        assert!(history.iter().all(|item| item.visited.is_some()));
+
+        assert!(!browser.go(-3));
+        assert!(!browser.go(0));
+        assert!(browser.go(-2));
+        assert_eq!(browser.url(), Some("/a"));
+        assert_eq!(browser.content.as_deref(), Some(">Page A"));
+        assert!(browser.go(3));
+        assert_eq!(browser.url(), Some("/d"));
         assert!(!browser.can_go_forward());
+        assert!(browser.back());
+        assert_eq!(browser.url(), Some("/c"));
     }
 
     #[test]
-    fn scroll() {
+    fn cancel_editing_restores_value() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", "`<|query`hello>");
+        assert!(!browser.cancel_editing());
+
+        browser.begin_editing("query");
+        browser.input_str(" there");
+        assert_eq!(browser.field_value("query"), Some("hello there"));
+        assert!(browser.cancel_editing());
+        assert_eq!(browser.field_value("query"), Some("hello"));
+        assert!(browser.editing().is_none());
+    }
+
+    #[test]
+    fn visible_hitboxes_are_viewport_relative() {
         let mut browser = Browser::new(NullRenderer);
+        let page: Vec<String> = (0..40).map(|i| format!("`[L{}`/{}]", i, i)).collect();
+        browser.set_content("/links", &page.join("\n"));
         browser.resize(80, 10);
-        browser.set_content("/test", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no");
+        browser.scroll_to(5);
 
-        browser.scroll_by(5);
-        assert_eq!(browser.scroll(), 5);
+        assert_eq!(browser.hitboxes().len(), 40);
+        let visible = browser.visible_hitboxes();
+        assert_eq!(visible.len(), 10);
+        assert_eq!(visible[0].line, 0);
+        assert!(matches!(&visible[0].interactable, Interactable::Link { url, .. } if url == "/5"));
+        assert_eq!(visible[9].interactable_idx, 14);
+    }
 
-        browser.scroll_by(-3);
-        assert_eq!(browser.scroll(), 2);
+    #[test]
+    fn linkify_makes_bare_urls_interactable() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "mirror at https://example.com/page");
+        assert!(browser.hitboxes().is_empty());
+
+        browser.set_linkify(true);
+        assert!(browser.linkifies());
+        assert!(matches!(
+            &browser.hitboxes()[0].interactable,
+            Interactable::Link { url, .. } if url == "https://example.com/page"
+        ));
+        assert_eq!(browser.hitboxes()[0].col_start, 10);
+
+        browser.set_content("/lit", "`=\nsee https://x.example/a\n`=");
+        assert!(browser.hitboxes().is_empty());
+    }
 
-        browser.scroll_to(0);
-        assert_eq!(browser.scroll(), 0);
+    #[test]
+    fn copy_selected_url_resolves_local_links() {
+        let hash = "0123456789abcdef0123456789abcdef";
+        let page = "`[Home`:/page/index.mu] `[Ext`https://example.com]";
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(&format!("nomadnetwork://{}:/page/news.mu", hash), page);
+        assert_eq!(
+            browser.copy_selected_url(),
+            Some(format!("nomadnetwork://{}:/page/index.mu", hash))
+        );
+        browser.select_next();
+        assert_eq!(
+            browser.copy_selected_url().as_deref(),
+            Some("https://example.com")
+        );
+
+        browser.set_content("/local.mu", page);
+        assert_eq!(
+            browser.copy_selected_url().as_deref(),
+            Some(":/page/index.mu")
+        );
+        browser.set_content("/empty.mu", "no links");
+        assert_eq!(browser.copy_selected_url(), None);
     }
 
     #[test]
-    fn click_link() {
+    fn speech_reads_current_form_values() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`[Click Me`http://target]");
+        browser.set_content("/form", "`<|user`guest>\n`<?|news|1`News>");
+        browser.set_field_value("user", "ada".to_string());
+        browser.set_checkbox("news", true);
+        assert_eq!(
+            browser.speech(),
+            ["text field 'user', value 'ada'", "checkbox 'News', checked"]
+        );
+    }
 
-        let interaction = browser.click(3, 0);
-        assert!(interaction.is_some());
-        if let Some(Interaction::Link(link)) = interaction {
-            assert_eq!(link.url, "http://target");
-        } else {
-            panic!("Expected Link interaction");
-        }
+    #[test]
+    fn enter_submits_the_only_link_for_a_field() {
+        let page = "`<|query`>`[Search`/search`query]\n`<|name`>`[Save`/save`name]`[Preview`/preview`name]";
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", page);
+        browser.begin_editing("query");
+        assert!(matches!(
+            browser.submit_editing(),
+            Some(Interaction::Submitted(field)) if field.name == "query"
+        ));
+
+        browser.set_submit_on_enter(true);
+        browser.begin_editing("query");
+        browser.input_str("rust");
+        let Some(Interaction::Link(link)) = browser.submit_editing() else {
+            panic!("expected link");
+        };
+        assert_eq!(link.url, "/search");
+        assert_eq!(link.form_data["field_query"], "rust");
+
+        browser.begin_editing("name");
+        assert!(matches!(
+            browser.submit_editing(),
+            Some(Interaction::Submitted(_))
+        ));
     }
 
     #[test]
-    fn checkbox_toggle() {
+    fn user_and_page_styles_restyle_documents() {
+        let bg = |doc: Option<&Document>| match &doc.unwrap().lines[0].elements[0] {
+            Element::Text(text) => text.style.bg,
+            _ => unreachable!(),
+        };
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`<?|agree|yes`I agree>");
+        browser.set_content("/garish/index.mu", "`Bf00loud");
+        assert!(bg(browser.document()).is_some());
+
+        browser.set_user_style(Some(UserStyle {
+            no_backgrounds: true,
+            ..Default::default()
+        }));
+        assert_eq!(bg(browser.document()), None);
+
+        browser.set_page_style("/garish/", Some(UserStyle::default()));
+        assert!(bg(browser.document()).is_some());
+        browser.set_content("/other.mu", "`B0f0calm");
+        assert_eq!(bg(browser.document()), None);
+    }
 
-        assert!(
-            !form_state(&mut browser)
-                .checkboxes
-                .get("agree")
-                .copied()
-                .unwrap_or(false)
-        );
-        browser.interact();
-        assert!(
-            form_state(&mut browser)
-                .checkboxes
-                .get("agree")
-                .copied()
-                .unwrap_or(false)
+    #[test]
+    fn width_blocks_follow_resize() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.resize(30, 10);
+        browser.set_content(
+            "/",
+            "#@if width=narrow\n`[Menu`/menu.mu]\n#@else\nFull\n#@end",
         );
+        assert_eq!(browser.document().unwrap().to_string(), "`[Menu`/menu.mu]");
+        browser.resize(80, 10);
+        assert_eq!(browser.document().unwrap().to_string(), "Full");
+        browser.resize(90, 10);
+        assert_eq!(browser.document().unwrap().to_string(), "Full");
     }
 
     #[test]
-    fn text_field_returns_edit_interaction() {
+    fn line_ranges_map_lines_and_rows() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`<|name`>");
-
-        let interaction = browser.interact();
-        assert!(interaction.is_some());
-        if let Some(Interaction::EditField(field)) = interaction {
-            assert_eq!(field.name, "name");
-            assert_eq!(field.value, "");
-            assert!(!field.masked);
-        } else {
-            panic!("Expected EditField interaction");
-        }
+        browser.resize(40, 2);
+        browser.set_content("/", "#!title=Lines\none\n# note\ntwo\nthree\nfour");
+        // The metadata line isn't a document line, so "one" is line 0
+        assert_eq!(browser.document().unwrap().lines.len(), 5);
+        assert_eq!(browser.document_line_range(0), Some(0..1));
+        assert_eq!(browser.document_line_range(1), Some(1..2));
+        assert_eq!(browser.document_line_at_row(3), Some(3));
+        assert_eq!(browser.document_line_at_row(9), None);
+        assert!(browser.scroll_to_line(3));
+        assert_eq!(browser.scroll(), 3);
+        assert!(!browser.scroll_to_line(7));
     }
 
     #[test]
-    fn text_field_with_default() {
+    fn checkbox_groups_toggle_together() {
+        let mut doc = Document::new();
+        let names =
+            doc.push_checkbox_group("file_", &[("c.txt", "C"), ("a.txt", "A"), ("b.txt", "B")]);
+        doc.push(Line::normal().field(crate::Field::checkbox("notify", "yes")));
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`<|name`John>");
+        browser.set_document("/files.mu", doc);
 
-        assert_eq!(
-            form_state(&mut browser).fields.get("name"),
-            Some(&"John".to_string())
-        );
+        assert_eq!(browser.set_group("file_", true), 3);
+        assert_eq!(browser.group_values("file_"), ["c.txt", "a.txt", "b.txt"]);
+        assert!(!browser.checkbox("notify"));
 
-        let interaction = browser.interact();
-        if let Some(Interaction::EditField(field)) = interaction {
-            assert_eq!(field.value, "John");
-        } else {
-            panic!("Expected EditField interaction");
-        }
+        browser.set_checkbox(&names[1], false);
+        assert_eq!(browser.group_values("file_"), ["c.txt", "b.txt"]);
+        browser.set_group("file_", false);
+        assert!(browser.group_values("file_").is_empty());
     }
 
     #[test]
-    fn masked_field() {
+    fn number_fields_step_within_range() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`<!|password`>");
+        browser.set_content("/", "`<+|volume|0|20|5`15> `<|name`x>");
+        assert!(browser.step_field("volume", 1));
+        assert_eq!(browser.field_value("volume"), Some("20"));
+        browser.step_field("volume", 1);
+        assert_eq!(browser.field_value("volume"), Some("20"));
+        browser.step_field("volume", -10);
+        assert_eq!(browser.field_value("volume"), Some("0"));
+        browser.set_field_value("volume", "loud".to_string());
+        browser.step_field("volume", 1);
+        assert_eq!(browser.field_value("volume"), Some("0"));
+        assert!(!browser.step_field("name", 1));
+
+        assert!(browser.step_selected(2));
+        assert_eq!(browser.field_value("volume"), Some("10"));
+    }
 
-        let interaction = browser.interact();
-        if let Some(Interaction::EditField(field)) = interaction {
-            assert_eq!(field.name, "password");
-            assert!(field.masked);
-        } else {
-            panic!("Expected EditField interaction");
+    #[test]
+    fn date_fields_step_segments_and_submit_iso() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "`<@|due`2024-1-31>\n`[Save`/save`due]");
+        assert!(browser.step_field("due", 1));
+        assert_eq!(browser.field_value("due"), Some("2024-02-01"));
+
+        browser.begin_editing("due");
+        browser.cursor_home();
+        browser.step_editing(1);
+        assert_eq!(browser.field_value("due"), Some("2025-02-01"));
+        browser.set_field_value("due", "2024-01-31".to_string());
+        for _ in 0..5 {
+            browser.cursor_right();
         }
+        browser.step_editing(1);
+        assert_eq!(browser.field_value("due"), Some("2024-02-29"));
+        assert_eq!(browser.editing().unwrap().cursor, 5);
+        browser.stop_editing();
+
+        browser.set_field_value("due", "2024-3-7".to_string());
+        let data = browser.collect_form_data(&["due".to_string()]);
+        assert_eq!(data["field_due"], "2024-03-07");
+        assert!(!browser.step_date("missing", DateSegment::Day, 1));
     }
 
     #[test]
-    fn set_field_value() {
+    fn hint_mode_labels_and_picks_interactables() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`<|name`>\n`[Submit`/send`name]");
+        browser.set_content("/", "`[A`/a] `[B`/b] `<|q`>");
+        assert!(browser.enter_hint_mode());
+        let labels: Vec<_> = browser.hints().iter().map(|h| h.label.as_str()).collect();
+        assert_eq!(labels, ["a", "s", "d"]);
+        assert!(matches!(
+            browser.hint_input('s'),
+            Some(Interaction::Link(link)) if link.url == "/b"
+        ));
+        assert!(!browser.in_hint_mode());
+
+        browser.enter_hint_mode();
+        assert!(matches!(
+            browser.hint_input('d'),
+            Some(Interaction::EditField(field)) if field.name == "q"
+        ));
+        browser.enter_hint_mode();
+        assert!(browser.hint_input('x').is_none());
+        assert!(!browser.in_hint_mode());
+
+        let page: String = (0..12).map(|i| format!("`[L{}`/{}]\n", i, i)).collect();
+        browser.set_content("/many", &page);
+        browser.enter_hint_mode();
+        assert_eq!(browser.hints()[9].label, "sa");
+        assert!(browser.hint_input('s').is_none());
+        assert_eq!(browser.view_state().hints.len(), 3);
+        assert!(matches!(
+            browser.hint_input('a'),
+            Some(Interaction::Link(link)) if link.url == "/9"
+        ));
+    }
 
-        browser.set_field_value("name", "Alice".to_string());
+    #[test]
+    fn poll_events_reports_changes_in_order() {
+        let page = "`<|q`>`<?|news|1`News>\n`[Go`/go`q]`[Get`:/file/a.zip]\n`{/live`0`q}";
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", page);
+        assert!(browser.poll_events().is_empty());
+
+        browser.set_record_events(true);
+        browser.set_content("/form", page);
+        browser.set_field_value("q", "rust".to_string());
+        browser.toggle_checkbox("news");
+        browser.focus_link_by_url("/go");
+        browser.interact();
+        browser.focus_link_by_url(":/file/a.zip");
+        browser.interact();
 
-        browser.select_next();
-        let interaction = browser.interact();
-        if let Some(Interaction::Link(link)) = interaction {
-            assert_eq!(link.form_data.get("field_name"), Some(&"Alice".to_string()));
-        } else {
-            panic!("Expected Link interaction");
-        }
+        let events = browser.poll_events();
+        assert!(matches!(&events[0], PageEvent::PartialRefreshDue(info) if info.url == "/live"));
+        assert!(
+            matches!(&events[1], PageEvent::FieldChanged { name, value } if name == "q" && value == "rust")
+        );
+        assert!(
+            matches!(&events[2], PageEvent::CheckboxToggled { name, checked: true } if name == "news")
+        );
+        assert!(matches!(&events[3], PageEvent::NavigationRequested(link) if link.url == "/go"));
+        assert!(
+            matches!(&events[4], PageEvent::DownloadRequested(link) if link.url == ":/file/a.zip")
+        );
+        assert_eq!(events.len(), 5);
+        assert!(browser.poll_events().is_empty());
     }
 
     #[test]
-    fn form_data_collection() {
+    fn reading_positions_restored_on_revisit() {
+        let article = (0..100)
+            .map(|i| format!("line {}\n", i))
+            .collect::<String>();
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content(
-            "/test",
-            "`<|user`>\n`<|msg`>\n`[Submit`/send`user|msg|action=go]",
-        );
+        browser.set_content("/article", &article);
+        browser.scroll_to(30);
+        browser.set_content("/other", "Other");
+        browser.set_content("/article", &article);
+        assert_eq!(browser.scroll(), 0);
 
-        browser.set_field_value("user", "A".to_string());
-        browser.set_field_value("msg", "B".to_string());
+        browser.set_remember_positions(true);
+        browser.scroll_to(30);
+        browser.set_content("/other", "Other");
+        browser.set_content("/article", &article);
+        assert_eq!(browser.scroll(), 30);
+        browser.scroll_to(45);
+        assert_eq!(browser.reading_positions()["/article"], 45);
+
+        let mut next_session = Browser::new(NullRenderer);
+        next_session.restore_reading_positions(browser.reading_positions());
+        assert!(next_session.remembers_positions());
+        next_session.set_content("/article", &article);
+        assert_eq!(next_session.scroll(), 45);
+        next_session.scroll_to(0);
+        assert!(next_session.reading_positions().is_empty());
+    }
 
-        browser.select_next();
-        browser.select_next();
-        let interaction = browser.interact();
+    #[test]
+    fn drafts_saved_when_leaving_page() {
+        let form = "`<|msg`>\n`<?|urgent|1`Urgent>\n`[Send`/send`msg|urgent]";
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", form);
+        browser.set_field_value("msg", "half written".to_string());
+        browser.set_content("/other", "Other");
+        browser.back();
+        assert!(!browser.has_draft());
+        assert_eq!(browser.field_value("msg"), Some(""));
+
+        browser.set_save_drafts(true);
+        browser.set_content("/untouched", form);
+        browser.set_content("/other", "Other");
+        assert!(browser.draft("/untouched").is_none());
+
+        browser.set_content("/form", form);
+        browser.set_field_value("msg", "half written".to_string());
+        browser.set_checkbox("urgent", true);
+        browser.set_content("/other", "Other");
+        browser.back();
+        assert!(browser.has_draft());
+        assert_eq!(browser.field_value("msg"), Some(""));
+
+        assert!(browser.restore_draft());
+        assert_eq!(browser.field_value("msg"), Some("half written"));
+        assert!(browser.checkbox("urgent"));
+        assert!(!browser.has_draft());
+        assert!(!browser.restore_draft());
+    }
 
-        if let Some(Interaction::Link(link)) = interaction {
-            assert_eq!(link.url, "/send");
-            assert_eq!(link.form_data.get("field_user"), Some(&"A".to_string()));
-            assert_eq!(link.form_data.get("field_msg"), Some(&"B".to_string()));
-            assert_eq!(link.form_data.get("var_action"), Some(&"go".to_string()));
-        } else {
-            panic!("Expected Link interaction");
-        }
+    #[test]
+    fn input_str_inserts_at_cursor() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/form", "`<|key`ab>");
+        browser.begin_editing("key");
+        browser.cursor_left();
+        browser.input_str("1\x1b[2\n3\t");
+        assert_eq!(browser.field_value("key"), Some("a1[23b"));
+        assert_eq!(browser.editing().unwrap().cursor, 5);
     }
 
     #[test]
-    fn select_next_prev_cycles() {
+    fn spatial_navigation() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "`[A`/a]\n`[B`/b]\n`[C`/c]");
+        browser.set_content(
+            "/grid",
+            "`[Aaa`/a]    `[Bbb`/b]\n`[Ccc`/c]\n          `[Ddd`/d]",
+        );
 
-        assert_eq!(browser.selected_link(), Some("/a"));
-        browser.select_next();
+        assert!(browser.select_right());
         assert_eq!(browser.selected_link(), Some("/b"));
-        browser.select_next();
+        assert!(!browser.select_right());
+        assert!(browser.select_down());
         assert_eq!(browser.selected_link(), Some("/c"));
-        browser.select_next();
-        assert_eq!(browser.selected_link(), Some("/a"));
-
-        browser.select_prev();
+        assert!(browser.select_down());
+        assert_eq!(browser.selected_link(), Some("/d"));
+        assert!(!browser.select_down());
+        assert!(browser.select_up());
         assert_eq!(browser.selected_link(), Some("/c"));
+        assert!(browser.select_up());
+        assert_eq!(browser.selected_link(), Some("/a"));
+        assert!(!browser.select_left());
     }
 
     #[test]
-    fn radio_button_selection() {
+    fn hover_tracks_link_under_mouse() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content(
-            "/test",
-            "`<^|color|red`Red>\n`<^|color|blue`Blue>\n`<^|color|green`Green>",
-        );
+        browser.set_content("/", "`[Docs`/docs] `<|name`>");
 
-        assert_eq!(
-            form_state(&mut browser).radios.get("color"),
-            Some(&"red".to_string())
-        );
+        assert!(browser.hover(2, 0));
+        assert_eq!(browser.hovered_link(), Some("/docs"));
+        assert_eq!(browser.view_state().hovered_interactable, Some(0));
+        assert!(!browser.hover(3, 0));
 
-        browser.select_next();
-        browser.interact();
-        assert_eq!(
-            form_state(&mut browser).radios.get("color"),
-            Some(&"blue".to_string())
-        );
+        assert!(browser.hover(6, 0));
+        assert_eq!(browser.hovered_link(), None);
+        assert_eq!(browser.view_state().hovered_interactable, Some(1));
+        assert_eq!(browser.selected, 0);
 
-        browser.select_next();
-        browser.interact();
-        assert_eq!(
-            form_state(&mut browser).radios.get("color"),
-            Some(&"green".to_string())
-        );
+        assert!(browser.hover(40, 0));
+        assert_eq!(browser.view_state().hovered_interactable, None);
     }
 
     #[test]
-    fn resize_triggers_rebuild() {
+    fn focus_scrolls_target_into_view() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/test", "Hello world");
-        browser.render();
-
-        browser.resize(40, 20);
-        assert!(browser.render().is_some());
+        browser.resize(80, 10);
+        let mut page = tall_page("`[Top`/top]");
+        page.push_str("\n`<|search`>\n`<^|color|red`>\n`[About`/about]");
+        browser.set_content("/", &page);
+
+        assert!(browser.focus_field("search"));
+        assert_eq!(browser.selected, 1);
+        assert_eq!(browser.scroll(), 42);
+        assert!(browser.focus_link_by_url("/top"));
+        assert_eq!(browser.scroll(), 0);
+        assert!(browser.focus_field("color"));
+        assert_eq!(browser.selected, 2);
+        assert!(browser.focus_nth_interactable(3));
+        assert_eq!(browser.selected_link(), Some("/about"));
+
+        assert!(!browser.focus_field("missing"));
+        assert!(!browser.focus_link_by_url("/missing"));
+        assert!(!browser.focus_nth_interactable(4));
+        assert_eq!(browser.selected_link(), Some("/about"));
     }
 
     #[test]
-    fn navigation_clears_form_state() {
+    fn heading_navigation() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/page1", "`<|name`>");
-
-        browser.set_field_value("name", "X".to_string());
-        assert_eq!(
-            form_state(&mut browser).fields.get("name"),
-            Some(&"X".to_string())
+        browser.resize(80, 10);
+        let page = format!(
+            "{}\n>>Second\n{}\n>Third",
+            tall_page(">First"),
+            tall_page("text")
         );
+        browser.set_content("/", &page);
 
-        browser.set_content("/page2", "`<|name`>");
+        let headings: Vec<_> = browser
+            .headings()
+            .iter()
+            .map(|h| (h.level, h.text.as_str(), h.row))
+            .collect();
         assert_eq!(
-            form_state(&mut browser).fields.get("name"),
-            Some(&"".to_string())
+            headings,
+            [(1, "First", 0), (2, "Second", 51), (1, "Third", 103)]
         );
+
+        assert!(browser.next_heading());
+        assert_eq!(browser.scroll(), 51);
+        assert!(browser.next_heading());
+        assert_eq!(browser.scroll(), 94);
+        assert!(!browser.next_heading());
+        assert_eq!(browser.scroll(), 94);
+        assert!(browser.prev_heading());
+        assert_eq!(browser.scroll(), 51);
+        assert!(browser.scroll_to_heading(0));
+        assert_eq!(browser.scroll(), 0);
+        assert!(!browser.prev_heading());
+        assert!(!browser.scroll_to_heading(3));
     }
 
     #[test]
-    fn back_preserves_scroll_position() {
+    fn error_page_keeps_previous_page() {
         let mut browser = Browser::new(NullRenderer);
-        browser.resize(80, 10);
-        browser.set_content("/page1", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no");
-        browser.scroll_to(5);
+        browser.set_content("/a", "A");
+        browser.set_content("/b", &tall_page("`[Link`/c]"));
+        browser.scroll_to(12);
 
-        browser.set_content("/page2", "Page 2");
+        browser.set_error("/c", "timed out");
+        assert_eq!(
+            browser.state(),
+            &PageState::Error {
+                url: "/c".to_string(),
+                message: "timed out".to_string()
+            }
+        );
+        assert_eq!(browser.url(), Some("/b"));
         assert_eq!(browser.scroll(), 0);
+        assert_eq!(browser.selected_link(), Some("/c"));
 
-        browser.back();
-        assert_eq!(browser.scroll(), 5);
+        assert!(browser.back());
+        assert_eq!(browser.state(), &PageState::Ok);
+        assert_eq!(browser.url(), Some("/b"));
+        assert_eq!(browser.scroll(), 12);
+        assert!(browser.can_go_back());
+
+        browser.set_error("/c", "timed out");
+        browser.set_content("/d", "D");
+        assert!(browser.back());
+        assert_eq!(browser.url(), Some("/b"));
+        assert_eq!(browser.scroll(), 12);
     }
 
     #[test]
-    fn directory_navigation_simulation() {
+    fn failed_navigation_shows_error_page() {
+        let loader = MapLoader(HashMap::from([("/", "Home")]));
         let mut browser = Browser::new(NullRenderer);
+        block_on(browser.navigate(&loader, "/")).unwrap();
+        assert!(block_on(browser.navigate(&loader, "/missing")).is_err());
+
+        assert!(matches!(
+            browser.state(),
+            PageState::Error { url, message } if url == "/missing" && message == "not found: /missing"
+        ));
+        let Some(Interaction::Link(retry)) = browser.interact() else {
+            panic!("Expected retry link");
+        };
+        assert_eq!(retry.url, "/missing");
+    }
 
-        let index = r#">Welcome
-`[Documents`/docs]
-`[Settings`/settings]
-`[About`/about]"#;
+    #[test]
+    fn loading_state_keeps_page_visible() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content("/", "`[Next`/next] `[Other`/other]");
+        browser.render();
 
-        let docs = r#">Documents
-`[Back`/]
--
-`[Report.pdf`/docs/report]
-`[Notes.txt`/docs/notes]"#;
+        browser.set_loading("/next", Some(1.5));
+        assert_eq!(
+            browser.state(),
+            &PageState::Loading {
+                url: "/next".to_string(),
+                progress: Some(1.0)
+            }
+        );
+        assert_eq!(browser.loading(), Some("/next"));
+        assert!(browser.render_dirty);
+        assert_eq!(browser.view_state().page_state, *browser.state());
+        assert_eq!(browser.selected_link(), Some("/next"));
 
-        let report = r#">Report
-`[Back to Documents`/docs]
--
-This is the report content."#;
+        assert!(browser.cancel_loading());
+        assert!(!browser.cancel_loading());
+        assert_eq!(browser.state(), &PageState::Ok);
 
-        browser.set_content("/", index);
+        browser.set_error("/next", "refused");
+        browser.set_loading("/next", None);
         assert_eq!(browser.url(), Some("/"));
-        assert_eq!(browser.selected_link(), Some("/docs"));
-
-        if let Some(Interaction::Link(link)) = browser.interact() {
-            assert_eq!(link.url, "/docs");
-            browser.set_content("/docs", docs);
-        }
-        assert_eq!(browser.url(), Some("/docs"));
-
-        browser.select_next();
-        assert_eq!(browser.selected_link(), Some("/docs/report"));
-
-        if let Some(Interaction::Link(link)) = browser.interact() {
-            browser.set_content(&link.url, report);
-        }
-        assert_eq!(browser.url(), Some("/docs/report"));
-
-        assert!(browser.can_go_back());
-        browser.back();
-        assert_eq!(browser.url(), Some("/docs"));
+        assert_eq!(browser.hitboxes.len(), 2);
+    }
 
-        browser.back();
-        assert_eq!(browser.url(), Some("/"));
+    #[test]
+    fn scroll_helpers() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.resize(80, 11);
+        browser.set_content("/", &tall_page("top"));
+        assert_eq!(browser.scroll_percent(), 0);
+
+        browser.scroll_page_down();
+        assert_eq!(browser.scroll(), 11);
+        browser.scroll_end();
+        assert_eq!(browser.scroll(), 40);
+        assert_eq!(browser.scroll_percent(), 100);
+        browser.scroll_page_up();
+        assert_eq!(browser.scroll(), 29);
+        browser.scroll_fraction(0.25);
+        assert_eq!(browser.scroll(), 10);
+        assert_eq!(browser.scroll_percent(), 25);
+        browser.scroll_fraction(7.0);
+        assert_eq!(browser.scroll(), 40);
+        browser.scroll_home();
+        assert_eq!(browser.scroll(), 0);
 
-        browser.forward();
-        assert_eq!(browser.url(), Some("/docs"));
+        browser.set_content("/short", "short");
+        assert_eq!(browser.scroll_percent(), 100);
     }
 
     #[test]
-    fn login_form_simulation() {
+    fn viewport_metrics() {
         let mut browser = Browser::new(NullRenderer);
+        browser.resize(60, 20);
+        browser.set_content("/", &tall_page("top"));
+        browser.scroll_to(35);
 
-        let login_page = r#">Login
-`<|username`>
-`<!|password`>
-`[Login`/auth`username|password]"#;
+        let viewport = browser.viewport();
+        assert_eq!(
+            viewport,
+            Viewport {
+                width: 60,
+                height: 20,
+                content_height: 51,
+                scroll: 31,
+                max_scroll: 31,
+            }
+        );
+        assert_eq!(viewport.visible_lines(), 31..51);
 
-        browser.set_content("/login", login_page);
+        browser.set_content("/short", "short");
+        assert_eq!(browser.viewport().visible_lines(), 0..1);
+    }
 
-        browser.set_field_value("username", "alice".to_string());
-        browser.set_field_value("password", "secret123".to_string());
+    #[test]
+    fn selected_element_describes_state() {
+        let mut browser = Browser::new(NullRenderer);
+        assert_eq!(browser.selected(), None);
+        browser.set_content(
+            "/",
+            "`[Go`/search`q]\n`<!|pin`1234>\n`<?|agree|yes`Agree>\n`<^|size|s`Small>`<^|size|m`Medium>",
+        );
 
+        assert_eq!(
+            browser.selected(),
+            Some(SelectedElement::Link {
+                url: "/search".to_string(),
+                fields: vec!["q".to_string()],
+            })
+        );
+        browser.select_next();
+        assert_eq!(
+            browser.selected(),
+            Some(SelectedElement::TextField {
+                name: "pin".to_string(),
+                value: "1234".to_string(),
+                masked: true,
+            })
+        );
         browser.select_next();
+        browser.interact();
+        assert_eq!(
+            browser.selected(),
+            Some(SelectedElement::Checkbox {
+                name: "agree".to_string(),
+                value: "yes".to_string(),
+                checked: true,
+            })
+        );
         browser.select_next();
-
-        if let Some(Interaction::Link(link)) = browser.interact() {
-            assert_eq!(link.url, "/auth");
-            assert_eq!(
-                link.form_data.get("field_username"),
-                Some(&"alice".to_string())
-            );
-            assert_eq!(
-                link.form_data.get("field_password"),
-                Some(&"secret123".to_string())
-            );
-        } else {
-            panic!("Expected Link interaction");
-        }
+        browser.select_next();
+        assert_eq!(
+            browser.selected(),
+            Some(SelectedElement::Radio {
+                name: "size".to_string(),
+                value: "m".to_string(),
+                checked: false,
+            })
+        );
     }
 
     #[test]
-    fn search_with_wildcard_fields() {
+    fn public_form_data_and_snapshot() {
         let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/",
+            "`<|user`ada>`<!|pass`>`<?|remember|1`Remember>`<^|plan|free`Free>",
+        );
+        browser.set_field_value("pass", "secret".to_string());
+
+        let snapshot = browser.form_snapshot();
+        assert_eq!(snapshot.fields["user"], "ada");
+        assert_eq!(snapshot.fields["pass"], "secret");
+        assert!(!snapshot.checkboxes["remember"]);
+        assert_eq!(snapshot.radios["plan"], "free");
+
+        let data = browser.collect_form_data(&["user".to_string(), "page=2".to_string()]);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data["field_user"], "ada");
+        assert_eq!(data["var_page"], "2");
+    }
 
-        let search_page = r#"`<|query`>
-`<?|exact|1`Exact match>
-`[Search`/search`*]"#;
-
-        browser.set_content("/search", search_page);
+    #[test]
+    fn required_fields_block_submit() {
+        let mut browser = Browser::new(NullRenderer);
+        browser.set_content(
+            "/login",
+            "`<|user`>\n`<!|pass`>\n`<?|terms|1`Accept>\n`[Login`/login`!user|!pass|!terms|note]",
+        );
+        browser.focus_link_by_url("/login");
 
-        browser.set_field_value("query", "rust".to_string());
+        let Some(Interaction::SubmitBlocked { missing }) = browser.interact() else {
+            panic!("Expected SubmitBlocked interaction");
+        };
+        assert_eq!(missing, ["user", "pass", "terms"]);
+        assert_eq!(
+            browser.selected(),
+            Some(SelectedElement::TextField {
+                name: "user".to_string(),
+                value: String::new(),
+                masked: false,
+            })
+        );
 
-        browser.select_next();
-        browser.interact(); // toggle checkbox
+        browser.set_field_value("user", "ada".to_string());
+        browser.set_field_value("pass", "  ".to_string());
+        browser.focus_link_by_url("/login");
+        let Some(Interaction::SubmitBlocked { missing }) = browser.interact() else {
+            panic!("Expected SubmitBlocked interaction");
+        };
+        assert_eq!(missing, ["pass", "terms"]);
 
-        browser.select_next();
-        if let Some(Interaction::Link(link)) = browser.interact() {
-            assert_eq!(link.url, "/search");
-            assert_eq!(link.form_data.get("field_query"), Some(&"rust".to_string()));
-            assert_eq!(link.form_data.get("field_exact"), Some(&"1".to_string()));
-        } else {
+        browser.set_field_value("pass", "secret".to_string());
+        browser.focus_field("terms");
+        browser.interact();
+        browser.focus_link_by_url("/login");
+        let Some(Interaction::Link(link)) = browser.interact() else {
             panic!("Expected Link interaction");
-        }
+        };
+        assert_eq!(link.form_data["field_user"], "ada");
+        assert_eq!(link.form_data["field_pass"], "secret");
+        assert_eq!(link.form_data["field_terms"], "1");
+        assert_eq!(link.fields, ["user", "pass", "terms", "note"]);
+        assert!(link.form_data.keys().all(|key| !key.contains('!')));
     }
 
     #[test]
-    fn empty_content_handling() {
+    fn programmatic_form_setters() {
         let mut browser = Browser::new(NullRenderer);
-        browser.set_content("/empty", "");
-
-        assert!(browser.content.is_some());
-        assert!(browser.render().is_some());
-        assert!(browser.selected_link().is_none());
+        browser.set_content(
+            "/",
+            "`<|q`default>`<?|safe|1`Safe>`<^|sort|new`New>`<^|sort|top`Top>",
+        );
+        assert_eq!(browser.field_value("q"), Some("default"));
+        assert_eq!(browser.field_value("missing"), None);
+        assert!(!browser.checkbox("safe"));
+        assert_eq!(browser.radio("sort"), Some("new"));
+
+        browser.set_field_value("q", "micron".to_string());
+        browser.set_checkbox("safe", true);
+        browser.set_radio("sort", "top");
+        assert!(!browser.toggle_checkbox("safe"));
+        assert!(browser.toggle_checkbox("safe"));
+
+        let state = form_state(&mut browser);
+        assert_eq!(state.fields["q"], "micron");
+        assert!(state.checkboxes["safe"]);
+        assert_eq!(state.radios["sort"], "top");
+    }
 
-        browser.select_next();
-        browser.select_prev();
-        assert!(browser.interact().is_none());
+    #[test]
+    fn title_from_first_heading() {
+        let mut browser = Browser::new(NullRenderer);
+        assert_eq!(browser.title(), None);
+        browser.set_content("/", "intro\n>>`!Welcome`! home\n>Other");
+        assert_eq!(browser.title().as_deref(), Some("Welcome home"));
+        browser.set_content("/plain", "no headings");
+        assert_eq!(browser.title(), None);
+        browser.set_content("/meta", "#!title=Front page\n>Welcome");
+        assert_eq!(browser.title().as_deref(), Some("Front page"));
     }
 
     #[test]
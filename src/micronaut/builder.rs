@@ -1,11 +1,14 @@
 use crate::{
-    Alignment, Color, Document, Element, Field, FieldKind, Line, LineKind, LinkElement, Partial,
-    Style, StyledText,
+    Alignment, Color, Document, Element, Field, FieldKind, Line, LineKind, LinkElement, Metadata,
+    Partial, Progress, Style, StyledText,
 };
 
 impl Document {
     pub fn new() -> Self {
-        Self { lines: Vec::new() }
+        Self {
+            metadata: Metadata::default(),
+            lines: Vec::new(),
+        }
     }
 
     pub fn push(&mut self, line: Line) {
@@ -23,6 +26,23 @@ impl Document {
             self.lines.push(Line::normal().styled(line_text, style));
         }
     }
+
+    /// Pushes a checkbox line per `(value, label)` item, named `prefix`
+    /// followed by the item's index, and returns the names for a submit
+    /// link to list.
+    pub fn push_checkbox_group(&mut self, prefix: &str, items: &[(&str, &str)]) -> Vec<String> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(idx, (value, label))| {
+                let name = format!("{}{}", prefix, idx);
+                self.lines.push(
+                    Line::normal().field(Field::checkbox(name.as_str(), *value).default(*label)),
+                );
+                name
+            })
+            .collect()
+    }
 }
 
 impl Default for Document {
@@ -31,6 +51,46 @@ impl Default for Document {
     }
 }
 
+impl Metadata {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(pos).1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.get("title")
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.get("author")
+    }
+
+    /// Cache lifetime in seconds, from the NomadNet `#!c=` header.
+    pub fn cache(&self) -> Option<u32> {
+        self.get("c")?.trim().parse().ok()
+    }
+}
+
 impl Line {
     pub fn new(kind: LineKind) -> Self {
         Self {
@@ -61,6 +121,24 @@ impl Line {
         Self::new(LineKind::Comment)
     }
 
+    pub fn include(url: &str) -> Self {
+        Self::new(LineKind::Directive).text(&format!("include {}", url))
+    }
+
+    pub fn directive(&self) -> Option<(&str, &str)> {
+        if self.kind != LineKind::Directive {
+            return None;
+        }
+        let Some(Element::Text(text)) = self.elements.first() else {
+            return None;
+        };
+        let text = text.text.trim();
+        Some(
+            text.split_once(' ')
+                .map_or((text, ""), |(name, args)| (name, args.trim())),
+        )
+    }
+
     pub fn indent(mut self, depth: u8) -> Self {
         self.indent_depth = depth.min(3);
         self
@@ -143,6 +221,11 @@ impl Line {
         self
     }
 
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.elements.push(Element::Progress(progress));
+        self
+    }
+
     pub fn element(mut self, element: Element) -> Self {
         self.elements.push(element);
         self
@@ -206,6 +289,7 @@ impl LinkElement {
             label: url.clone(),
             url,
             fields: Vec::new(),
+            required: Vec::new(),
             style: Style::default(),
         }
     }
@@ -220,6 +304,16 @@ impl LinkElement {
         self
     }
 
+    /// Adds a field that has to be filled in before the link submits.
+    pub fn required_field(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.required.contains(&name) {
+            self.required.push(name.clone());
+        }
+        self.fields.push(name);
+        self
+    }
+
     pub fn style(mut self, style: Style) -> Self {
         self.style = style;
         self
@@ -233,6 +327,7 @@ impl Field {
             default: String::new(),
             width: None,
             masked: false,
+            tab_index: None,
             kind: FieldKind::Text,
         }
     }
@@ -243,17 +338,23 @@ impl Field {
             default: String::new(),
             width: None,
             masked: true,
+            tab_index: None,
             kind: FieldKind::Text,
         }
     }
 
     pub fn checkbox(name: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
         Self {
             name: name.into(),
-            default: value.into(),
+            default: value.clone(),
             width: None,
             masked: false,
-            kind: FieldKind::Checkbox { checked: false },
+            tab_index: None,
+            kind: FieldKind::Checkbox {
+                value,
+                checked: false,
+            },
         }
     }
 
@@ -263,6 +364,7 @@ impl Field {
             default: String::new(),
             width: None,
             masked: false,
+            tab_index: None,
             kind: FieldKind::Radio {
                 value: value.into(),
                 checked: false,
@@ -270,6 +372,49 @@ impl Field {
         }
     }
 
+    pub fn number(name: impl Into<String>, value: i64) -> Self {
+        Self {
+            name: name.into(),
+            default: value.to_string(),
+            width: None,
+            masked: false,
+            tab_index: None,
+            kind: FieldKind::Number {
+                min: None,
+                max: None,
+                step: 1,
+            },
+        }
+    }
+
+    /// `value` is a `YYYY-MM-DD` date, or empty.
+    pub fn date(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            default: value.into(),
+            width: None,
+            masked: false,
+            tab_index: None,
+            kind: FieldKind::Date,
+        }
+    }
+
+    /// Bounds a number field; ignored on other kinds.
+    pub fn range(mut self, lo: i64, hi: i64) -> Self {
+        if let FieldKind::Number { min, max, .. } = &mut self.kind {
+            *min = Some(lo);
+            *max = Some(hi);
+        }
+        self
+    }
+
+    pub fn step(mut self, by: i64) -> Self {
+        if let FieldKind::Number { step, .. } = &mut self.kind {
+            *step = by.max(1);
+        }
+        self
+    }
+
     pub fn default(mut self, default: impl Into<String>) -> Self {
         self.default = default.into();
         self
@@ -280,16 +425,36 @@ impl Field {
         self
     }
 
+    pub fn tab_index(mut self, index: u16) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
     pub fn checked(mut self) -> Self {
         match &mut self.kind {
-            FieldKind::Checkbox { checked } => *checked = true,
+            FieldKind::Checkbox { checked, .. } => *checked = true,
             FieldKind::Radio { checked, .. } => *checked = true,
-            FieldKind::Text => {}
+            FieldKind::Text | FieldKind::Number { .. } | FieldKind::Date => {}
         }
         self
     }
 }
 
+impl Progress {
+    pub fn new(value: f64, max: f64) -> Self {
+        Self {
+            value,
+            max,
+            label: String::new(),
+        }
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+}
+
 impl Partial {
     pub fn new(url: impl Into<String>) -> Self {
         Self {
@@ -356,6 +521,46 @@ mod tests {
         assert_eq!(doc.to_string(), "Name: `<20|username`guest>");
     }
 
+    #[test]
+    fn build_checkbox_group() {
+        let mut doc = Document::new();
+        let names = doc.push_checkbox_group("file_", &[("a.txt", "A"), ("b.txt", "B")]);
+        let mut submit = LinkElement::new(":/page/get.mu").label("Download");
+        for name in &names {
+            submit = submit.field(name.as_str());
+        }
+        doc.push(Line::normal().link(submit));
+
+        assert_eq!(names, ["file_0", "file_1"]);
+        assert_eq!(
+            doc.to_string(),
+            "`<?|file_0|a.txt`A>\n`<?|file_1|b.txt`B>\n`[Download`:/page/get.mu`file_0|file_1]"
+        );
+    }
+
+    #[test]
+    fn build_number_field() {
+        let mut doc = Document::new();
+        doc.push(
+            Line::normal()
+                .text("Volume ")
+                .field(Field::number("volume", 40).range(0, 100).step(10).width(3)),
+        );
+
+        assert_eq!(doc.to_string(), "Volume `<+3|volume|0|100|10`40>");
+
+        let mut doc = Document::new();
+        doc.push(Line::normal().field(Field::date("due", "2024-05-01")));
+        assert_eq!(doc.to_string(), "`<@|due`2024-05-01>");
+    }
+
+    #[test]
+    fn build_progress() {
+        let mut doc = Document::new();
+        doc.push(Line::normal().progress(Progress::new(3.0, 4.0).label("Synced")));
+        assert_eq!(doc.to_string(), "`(3`4`Synced)");
+    }
+
     #[test]
     fn build_multiline_ascii() {
         let mut doc = Document::new();
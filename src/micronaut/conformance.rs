@@ -0,0 +1,244 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{Alignment, Color, Document, Element, FieldKind, Line, LineKind, Style, parse};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    pub name: String,
+    pub input: String,
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", self.name)?;
+        let expected: Vec<_> = self.expected.lines().collect();
+        let actual: Vec<_> = self.actual.lines().collect();
+        for i in 0..expected.len().max(actual.len()) {
+            match (expected.get(i), actual.get(i)) {
+                (Some(e), Some(a)) if e == a => writeln!(f, "  {}", e)?,
+                (e, a) => {
+                    if let Some(e) = e {
+                        writeln!(f, "- {}", e)?;
+                    }
+                    if let Some(a) = a {
+                        writeln!(f, "+ {}", a)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads every `name.mu` in `dir` together with its `name.dump`.
+pub fn load_fixtures(dir: &Path) -> io::Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "mu") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        fixtures.push(Fixture {
+            input: fs::read_to_string(&path)?,
+            expected: fs::read_to_string(path.with_extension("dump"))?,
+            name,
+        });
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+pub fn check(fixture: &Fixture) -> Result<(), Mismatch> {
+    let actual = dump(&parse(&fixture.input));
+    if actual.trim_end() == fixture.expected.trim_end() {
+        Ok(())
+    } else {
+        Err(Mismatch {
+            name: fixture.name.clone(),
+            expected: fixture.expected.clone(),
+            actual,
+        })
+    }
+}
+
+pub fn check_dir(dir: &Path) -> io::Result<Vec<Mismatch>> {
+    Ok(load_fixtures(dir)?
+        .iter()
+        .filter_map(|fixture| check(fixture).err())
+        .collect())
+}
+
+/// One line per document line followed by one indented line per element,
+/// e.g. `heading(1) depth=1 left` / `  text "Hi" bold fg=#ff0000`.
+pub fn dump(doc: &Document) -> String {
+    let mut out = String::new();
+    for (key, value) in &doc.metadata.entries {
+        let _ = writeln!(out, "meta {}={:?}", key, value);
+    }
+    for line in &doc.lines {
+        dump_line(line, &mut out);
+    }
+    out
+}
+
+fn dump_line(line: &Line, out: &mut String) {
+    let kind = match line.kind {
+        LineKind::Normal => "normal".to_string(),
+        LineKind::Heading(level) => format!("heading({})", level),
+        LineKind::Divider(ch) => format!("divider({:?})", ch),
+        LineKind::Comment => "comment".to_string(),
+        LineKind::Directive => "directive".to_string(),
+        LineKind::LiteralStart => "literal-start".to_string(),
+        LineKind::LiteralEnd => "literal-end".to_string(),
+    };
+    let alignment = match line.alignment {
+        Alignment::Left => "left",
+        Alignment::Center => "center",
+        Alignment::Right => "right",
+    };
+    let _ = writeln!(out, "{} depth={} {}", kind, line.indent_depth, alignment);
+    for element in &line.elements {
+        let _ = match element {
+            Element::Text(text) => {
+                writeln!(out, "  text {:?}{}", text.text, style(text.style))
+            }
+            Element::Link(link) => writeln!(
+                out,
+                "  link {:?} -> {:?}{}{}{}",
+                link.label,
+                link.url,
+                list(" fields=", &link.fields),
+                list(" required=", &link.required),
+                style(link.style)
+            ),
+            Element::Field(field) => {
+                let kind = match &field.kind {
+                    FieldKind::Text => "text".to_string(),
+                    FieldKind::Checkbox { value, checked } => {
+                        format!("checkbox value={:?} checked={}", value, checked)
+                    }
+                    FieldKind::Radio { value, checked } => {
+                        format!("radio value={:?} checked={}", value, checked)
+                    }
+                    FieldKind::Number { min, max, step } => {
+                        format!("number min={:?} max={:?} step={}", min, max, step)
+                    }
+                    FieldKind::Date => "date".to_string(),
+                };
+                writeln!(
+                    out,
+                    "  field {:?} {} default={:?}{}{}{}",
+                    field.name,
+                    kind,
+                    field.default,
+                    field
+                        .width
+                        .map(|w| format!(" width={}", w))
+                        .unwrap_or_default(),
+                    if field.masked { " masked" } else { "" },
+                    field
+                        .tab_index
+                        .map(|i| format!(" tab={}", i))
+                        .unwrap_or_default(),
+                )
+            }
+            Element::Progress(progress) => writeln!(
+                out,
+                "  progress {}/{} label={:?}",
+                progress.value, progress.max, progress.label
+            ),
+            Element::Partial(partial) => writeln!(
+                out,
+                "  partial {:?}{}{}",
+                partial.url,
+                partial
+                    .refresh
+                    .map(|r| format!(" refresh={}", r))
+                    .unwrap_or_default(),
+                list(" fields=", &partial.fields),
+            ),
+        };
+    }
+}
+
+fn style(style: Style) -> String {
+    let mut out = String::new();
+    for (on, name) in [
+        (style.bold, " bold"),
+        (style.italic, " italic"),
+        (style.underline, " underline"),
+    ] {
+        if on {
+            out.push_str(name);
+        }
+    }
+    if let Some(fg) = style.fg {
+        out.push_str(&format!(" fg={}", color(fg)));
+    }
+    if let Some(bg) = style.bg {
+        out.push_str(&format!(" bg={}", color(bg)));
+    }
+    out
+}
+
+fn color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn list(prefix: &str, items: &[String]) -> String {
+    if items.is_empty() {
+        String::new()
+    } else {
+        format!("{}{}", prefix, items.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_fixtures() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance");
+        assert!(!load_fixtures(&dir).unwrap().is_empty());
+        let mismatches = check_dir(&dir).unwrap();
+        assert!(
+            mismatches.is_empty(),
+            "{}",
+            mismatches
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>()
+        );
+    }
+
+    #[test]
+    fn mismatch_shows_diff() {
+        let fixture = Fixture {
+            name: "bold".to_string(),
+            input: "`!Hi".to_string(),
+            expected: "normal depth=0 left\n  text \"Hi\"\n".to_string(),
+        };
+        let mismatch = check(&fixture).unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            "bold:\n  normal depth=0 left\n-   text \"Hi\"\n+   text \"Hi\" bold\n"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::DateSegment;
+
+/// A calendar date as `(year, month, day)`.
+pub(crate) type Date = (i32, u32, u32);
+
+/// Reads `YYYY-MM-DD`, also accepting unpadded months and days like
+/// `2024-1-5`.
+pub(crate) fn parse_date(text: &str) -> Option<Date> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    ((1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day))
+        .then_some((year, month, day))
+}
+
+/// ISO 8601, `YYYY-MM-DD`.
+pub(crate) fn format_date((year, month, day): Date) -> String {
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Moves `date` by `amount` of `segment`. Changing the month or year keeps
+/// the day where the new month has it and otherwise uses its last day.
+pub(crate) fn step_date(date: Date, segment: DateSegment, amount: i32) -> Date {
+    let (year, month, day) = date;
+    match segment {
+        DateSegment::Day => from_days(to_days(date) + amount as i64),
+        DateSegment::Month | DateSegment::Year => {
+            let months = match segment {
+                DateSegment::Year => amount as i64 * 12,
+                _ => amount as i64,
+            };
+            let index = year as i64 * 12 + month as i64 - 1 + months;
+            let year = index.div_euclid(12) as i32;
+            let month = index.rem_euclid(12) as u32 + 1;
+            (year, month, day.min(days_in_month(year, month)))
+        }
+    }
+}
+
+/// Days since 1970-01-01.
+fn to_days((year, month, day): Date) -> i64 {
+    let year = year as i64 - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub(crate) fn from_days(days: i64) -> Date {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year as i32, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_and_formats_dates() {
+        assert_eq!(parse_date("2024-2-29"), Some((2024, 2, 29)));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("soon"), None);
+        assert_eq!(format_date((987, 3, 4)), "0987-03-04");
+
+        assert_eq!(from_days(0), (1970, 1, 1));
+        assert_eq!(to_days((2000, 3, 1)), 11_017);
+        assert_eq!(step_date((2024, 12, 31), DateSegment::Day, 1), (2025, 1, 1));
+        assert_eq!(
+            step_date((2024, 1, 31), DateSegment::Month, 1),
+            (2024, 2, 29)
+        );
+        assert_eq!(
+            step_date((2024, 1, 15), DateSegment::Month, -1),
+            (2023, 12, 15)
+        );
+        assert_eq!(
+            step_date((2024, 2, 29), DateSegment::Year, 1),
+            (2025, 2, 28)
+        );
+    }
+}
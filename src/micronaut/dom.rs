@@ -0,0 +1,137 @@
+use crate::{Alignment, Document, Element, FieldKind, Line, LineKind, Style};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomLine {
+    pub kind: LineKind,
+    pub alignment: Alignment,
+    pub indent_depth: u8,
+    pub runs: Vec<DomRun>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomRun {
+    pub text: String,
+    pub style: Style,
+    pub target: Option<RunTarget>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunTarget {
+    Link {
+        url: String,
+        fields: Vec<String>,
+    },
+    Field {
+        name: String,
+        kind: FieldKind,
+        masked: bool,
+        width: Option<u16>,
+    },
+    Partial {
+        url: String,
+        refresh: Option<u32>,
+    },
+    Progress {
+        value: f64,
+        max: f64,
+    },
+}
+
+impl Document {
+    pub fn to_dom(&self) -> Vec<DomLine> {
+        self.lines
+            .iter()
+            .filter(|line| {
+                !matches!(
+                    line.kind,
+                    LineKind::Comment
+                        | LineKind::Directive
+                        | LineKind::LiteralStart
+                        | LineKind::LiteralEnd
+                )
+            })
+            .map(dom_line)
+            .collect()
+    }
+}
+
+fn dom_line(line: &Line) -> DomLine {
+    let runs = line
+        .elements
+        .iter()
+        .map(|element| match element {
+            Element::Text(text) => DomRun {
+                text: text.text.clone(),
+                style: text.style,
+                target: None,
+            },
+            Element::Link(link) => DomRun {
+                text: link.label.clone(),
+                style: link.style,
+                target: Some(RunTarget::Link {
+                    url: link.url.clone(),
+                    fields: link.fields.clone(),
+                }),
+            },
+            Element::Field(field) => DomRun {
+                text: field.default.clone(),
+                style: Style::default(),
+                target: Some(RunTarget::Field {
+                    name: field.name.clone(),
+                    kind: field.kind.clone(),
+                    masked: field.masked,
+                    width: field.width,
+                }),
+            },
+            Element::Partial(partial) => DomRun {
+                text: String::new(),
+                style: Style::default(),
+                target: Some(RunTarget::Partial {
+                    url: partial.url.clone(),
+                    refresh: partial.refresh,
+                }),
+            },
+            Element::Progress(progress) => DomRun {
+                text: progress.label.clone(),
+                style: Style::default(),
+                target: Some(RunTarget::Progress {
+                    value: progress.value,
+                    max: progress.max,
+                }),
+            },
+        })
+        .collect();
+    DomLine {
+        kind: line.kind,
+        alignment: line.alignment,
+        indent_depth: line.indent_depth,
+        runs,
+    }
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dom_runs_carry_metadata() {
+        let dom = crate::parse("# hidden\n`c`!Hi`! `[Go`:/a.mu`name]`<8|name`Ada>").to_dom();
+        assert_eq!(dom.len(), 1);
+        let line = &dom[0];
+        assert_eq!(line.alignment, Alignment::Center);
+        assert_eq!(line.runs[0].text, "Hi");
+        assert!(line.runs[0].style.bold);
+        assert_eq!(
+            line.runs[2].target,
+            Some(RunTarget::Link {
+                url: ":/a.mu".to_string(),
+                fields: vec!["name".to_string()],
+            })
+        );
+        assert_eq!(line.runs[3].text, "Ada");
+        assert!(matches!(
+            &line.runs[3].target,
+            Some(RunTarget::Field { name, width: Some(8), .. }) if name == "name"
+        ));
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use crate::Document;
+#[cfg(feature = "parser")]
+use crate::{Line, parse};
+
+#[cfg(feature = "parser")]
+const MAX_INCLUDE_DEPTH: usize = 8;
+#[cfg(feature = "parser")]
+const MAX_INCLUDES: usize = 64;
+#[cfg(feature = "parser")]
+const MAX_INCLUDED_LINES: usize = 10_000;
+
+/// How roomy the viewport is, for `#@if width=narrow` style blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WidthClass {
+    Narrow,
+    Normal,
+    Wide,
+}
+
+impl WidthClass {
+    /// Narrow below `narrow` columns, wide from `wide`.
+    pub fn of(width: u16, narrow: u16, wide: u16) -> Self {
+        if width < narrow {
+            WidthClass::Narrow
+        } else if width >= wide {
+            WidthClass::Wide
+        } else {
+            WidthClass::Normal
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WidthClass::Narrow => "narrow",
+            WidthClass::Normal => "normal",
+            WidthClass::Wide => "wide",
+        }
+    }
+}
+
+pub trait IncludeResolver {
+    fn include(&self, url: &str) -> Option<String>;
+}
+
+impl<F: Fn(&str) -> Option<String>> IncludeResolver for F {
+    fn include(&self, url: &str) -> Option<String> {
+        self(url)
+    }
+}
+
+/// Replaces `#@include <url>` lines with the parsed content the resolver
+/// returns. Includes nest up to a fixed depth, and a page resolves at most 64
+/// includes adding at most 10 000 lines between them. Unresolved, cyclic and
+/// over-limit includes are left in place.
+#[cfg(feature = "parser")]
+pub fn expand_includes(doc: &Document, resolver: &impl IncludeResolver) -> Document {
+    let mut stack = Vec::new();
+    let mut expanded = Document {
+        metadata: doc.metadata.clone(),
+        lines: Vec::new(),
+    };
+    let mut budget = IncludeBudget {
+        includes: MAX_INCLUDES,
+        lines: MAX_INCLUDED_LINES,
+    };
+    expand_lines(&doc.lines, resolver, &mut stack, &mut budget, &mut expanded);
+    expanded
+}
+
+#[cfg(feature = "parser")]
+struct IncludeBudget {
+    includes: usize,
+    lines: usize,
+}
+
+#[cfg(feature = "parser")]
+fn expand_lines(
+    lines: &[Line],
+    resolver: &impl IncludeResolver,
+    stack: &mut Vec<String>,
+    budget: &mut IncludeBudget,
+    out: &mut Document,
+) {
+    for line in lines {
+        let Some(("include", url)) = line.directive() else {
+            if !stack.is_empty() {
+                if budget.lines == 0 {
+                    return;
+                }
+                budget.lines -= 1;
+            }
+            out.lines.push(line.clone());
+            continue;
+        };
+        let allowed = stack.len() < MAX_INCLUDE_DEPTH
+            && !stack.iter().any(|u| u == url)
+            && budget.includes > 0
+            && budget.lines > 0;
+        let included = allowed.then(|| resolver.include(url)).flatten();
+        let Some(content) = included else {
+            out.lines.push(line.clone());
+            continue;
+        };
+        budget.includes -= 1;
+        let doc = parse(&content);
+        for (key, value) in doc.metadata.entries {
+            if out.metadata.get(&key).is_none() {
+                out.metadata.set(key, value);
+            }
+        }
+        stack.push(url.to_string());
+        expand_lines(&doc.lines, resolver, stack, budget, out);
+        stack.pop();
+    }
+}
+
+impl Document {
+    /// Evaluates `#@if`, `#@else` and `#@end` blocks against `vars`, keeping
+    /// only the lines whose conditions hold. A condition is `name` (set and
+    /// non-empty), `!name`, `name=value` or `name!=value`; blocks nest.
+    pub fn expand(&self, vars: &HashMap<String, String>) -> Document {
+        let mut stack: Vec<(bool, bool)> = Vec::new();
+        let mut lines = Vec::new();
+        for line in &self.lines {
+            let visible = stack.iter().all(|(_, taken)| *taken);
+            match line.directive() {
+                Some(("if", condition)) => {
+                    let holds = visible && evaluate(condition, vars);
+                    stack.push((visible, holds));
+                }
+                Some(("else", _)) => {
+                    if let Some((outer, taken)) = stack.last_mut() {
+                        *taken = *outer && !*taken;
+                    }
+                }
+                Some(("end", _)) => {
+                    stack.pop();
+                }
+                _ if visible => lines.push(line.clone()),
+                _ => {}
+            }
+        }
+        Document {
+            metadata: self.metadata.clone(),
+            lines,
+        }
+    }
+}
+
+impl Document {
+    /// The class of a `width` column viewport, split at 60 and 120 columns
+    /// unless the page sets its own `#!narrow=` and `#!wide=` breakpoints.
+    pub fn width_class(&self, width: u16) -> WidthClass {
+        let breakpoint = |key: &str, default: u16| {
+            self.metadata
+                .get(key)
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(default)
+        };
+        WidthClass::of(width, breakpoint("narrow", 60), breakpoint("wide", 120))
+    }
+
+    /// Like `expand` with `width` set to `class`, but only blocks whose
+    /// condition tests `width` are evaluated; every other directive is kept.
+    pub fn for_width(&self, class: WidthClass) -> Document {
+        let vars = HashMap::from([("width".to_string(), class.as_str().to_string())]);
+        let mut stack: Vec<Option<(bool, bool)>> = Vec::new();
+        let mut lines = Vec::new();
+        for line in &self.lines {
+            let visible = stack.iter().flatten().all(|(_, taken)| *taken);
+            let width_block = matches!(stack.last(), Some(Some(_)));
+            match line.directive() {
+                Some(("if", condition)) if is_width_condition(condition) => {
+                    let holds = visible && evaluate(condition, &vars);
+                    stack.push(Some((visible, holds)));
+                    continue;
+                }
+                Some(("if", _)) => stack.push(None),
+                Some(("else", _)) if width_block => {
+                    if let Some(Some((outer, taken))) = stack.last_mut() {
+                        *taken = *outer && !*taken;
+                    }
+                    continue;
+                }
+                Some(("end", _)) if width_block => {
+                    stack.pop();
+                    continue;
+                }
+                Some(("end", _)) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+            if visible {
+                lines.push(line.clone());
+            }
+        }
+        Document {
+            metadata: self.metadata.clone(),
+            lines,
+        }
+    }
+}
+
+fn is_width_condition(condition: &str) -> bool {
+    let name = condition.split(['=', '!']).find(|part| !part.is_empty());
+    name.is_some_and(|name| name.trim() == "width")
+}
+
+fn evaluate(condition: &str, vars: &HashMap<String, String>) -> bool {
+    let var = |name: &str| vars.get(name.trim()).map(String::as_str);
+    if let Some((name, value)) = condition.split_once("!=") {
+        return var(name) != Some(value.trim());
+    }
+    if let Some((name, value)) = condition.split_once('=') {
+        return var(name) == Some(value.trim());
+    }
+    match condition.strip_prefix('!') {
+        Some(name) => var(name).is_none_or(str::is_empty),
+        None => var(condition).is_some_and(|value| !value.is_empty()),
+    }
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    fn pages(url: &str) -> Option<String> {
+        match url {
+            "/header.mu" => Some("#!author=ada\n>Site\n#@include /nav.mu".to_string()),
+            "/nav.mu" => Some("`[Home`:/page/index.mu]".to_string()),
+            "/loop.mu" => Some("loop\n#@include /loop.mu".to_string()),
+            url if url.starts_with("/fan") => {
+                let next = format!("#@include {}x\n", url);
+                Some(format!("{}{}", next.repeat(10), "x".repeat(20)))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn includes_expand_recursively() {
+        let doc = parse("#!title=News\n#@include /header.mu\nbody\n#@include /missing.mu");
+        let expanded = expand_includes(&doc, &pages);
+        assert_eq!(
+            expanded.to_string(),
+            "#!title=News\n#!author=ada\n>Site\n<`[Home`:/page/index.mu]\nbody\n#@include /missing.mu"
+        );
+    }
+
+    #[test]
+    fn cyclic_includes_stop() {
+        let doc = parse("#@include /loop.mu");
+        let expanded = expand_includes(&doc, &pages);
+        assert_eq!(expanded.to_string(), "loop\n#@include /loop.mu");
+    }
+
+    #[test]
+    fn fan_out_includes_are_bounded() {
+        let doc = parse("top\n#@include /fan\nbottom");
+        let expanded = expand_includes(&doc, &pages);
+        let included = expanded.lines.len() - 2;
+        assert!(included <= MAX_INCLUDES * 11, "{} lines", included);
+        assert_eq!(expanded.lines.first(), parse("top").lines.first());
+        assert_eq!(expanded.lines.last(), parse("bottom").lines.first());
+
+        let huge = |_: &str| Some("line\n".repeat(MAX_INCLUDED_LINES + 5));
+        let doc = parse("#@include /a\n#@include /b\nend");
+        let expanded = expand_includes(&doc, &huge);
+        assert_eq!(expanded.lines.len(), MAX_INCLUDED_LINES + 2);
+        assert_eq!(
+            expanded.lines[MAX_INCLUDED_LINES].directive(),
+            Some(("include", "/b"))
+        );
+    }
+
+    #[test]
+    fn width_blocks_pick_by_class() {
+        let doc = parse(concat!(
+            "#!narrow=40\n",
+            "#@if width=narrow\n",
+            "`[Menu`:/page/menu.mu]\n",
+            "#@else\n",
+            "#@if user\n",
+            "full layout\n",
+            "#@end\n",
+            "#@end\n",
+            "#@if width=wide\n",
+            "sidebar\n",
+            "#@end",
+        ));
+        assert_eq!(doc.width_class(39), WidthClass::Narrow);
+        assert_eq!(doc.width_class(50), WidthClass::Normal);
+        assert_eq!(doc.width_class(120), WidthClass::Wide);
+
+        let narrow = doc.for_width(doc.width_class(30));
+        assert_eq!(narrow.to_string(), "#!narrow=40\n`[Menu`:/page/menu.mu]");
+        let wide = doc.for_width(WidthClass::Wide);
+        assert_eq!(
+            wide.to_string(),
+            "#!narrow=40\n#@if user\nfull layout\n#@end\nsidebar"
+        );
+    }
+
+    #[test]
+    fn conditional_blocks() {
+        let doc = parse(concat!(
+            "#@if user\n",
+            "Welcome back\n",
+            "#@if role=admin\n",
+            "`[Admin`:/page/admin.mu]\n",
+            "#@end\n",
+            "#@else\n",
+            "`[Log in`:/page/login.mu]\n",
+            "#@end\n",
+            "#@if width!=narrow\n",
+            "wide banner\n",
+            "#@end\n",
+            "#@if !user\n",
+            "guest footer\n",
+            "#@end",
+        ));
+        let vars = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let anonymous = doc.expand(&vars(&[("width", "narrow")]));
+        assert_eq!(
+            anonymous.to_string(),
+            "`[Log in`:/page/login.mu]\nguest footer"
+        );
+
+        let admin = doc.expand(&vars(&[("user", "ada"), ("role", "admin")]));
+        assert_eq!(
+            admin.to_string(),
+            "Welcome back\n`[Admin`:/page/admin.mu]\nwide banner"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use quick_xml::Reader;
+use quick_xml::escape::resolve_xml_entity;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::{Color, Document, Line, LinkElement, Style};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Feed {
+    pub title: String,
+    pub link: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub title: String,
+    pub link: Option<String>,
+    pub date: Option<String>,
+    pub summary: Option<String>,
+}
+
+pub fn parse_feed(xml: &str) -> Result<Feed, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut feed = Feed::default();
+    let mut entry: Option<FeedEntry> = None;
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let name = local_name(&start);
+                if name == "item" || name == "entry" {
+                    entry = Some(FeedEntry::default());
+                }
+                if name == "link" {
+                    set_atom_link(&start, &path, &mut feed, entry.as_mut())?;
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Empty(start) if local_name(&start) == "link" => {
+                set_atom_link(&start, &path, &mut feed, entry.as_mut())?;
+            }
+            Event::Text(content) => text.push_str(&content.decode()?),
+            Event::CData(content) => text.push_str(&content.decode()?),
+            Event::GeneralRef(reference) => {
+                if let Some(ch) = reference.resolve_char_ref()? {
+                    text.push(ch);
+                } else if let Some(resolved) = resolve_xml_entity(&reference.decode()?) {
+                    text.push_str(resolved);
+                }
+            }
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    continue;
+                };
+                let value = text.trim().to_string();
+                text.clear();
+                let parent = path.last().map(String::as_str);
+                match (&mut entry, name.as_str()) {
+                    (Some(_), "item" | "entry") => feed.entries.extend(entry.take()),
+                    (Some(current), "title") if matches!(parent, Some("item" | "entry")) => {
+                        current.title = value;
+                    }
+                    (Some(current), "link") if current.link.is_none() && !value.is_empty() => {
+                        current.link = Some(value);
+                    }
+                    (Some(current), "pubDate" | "published" | "updated" | "date")
+                        if current.date.is_none() =>
+                    {
+                        current.date = Some(value);
+                    }
+                    (Some(current), "description" | "summary" | "content")
+                        if current.summary.is_none() =>
+                    {
+                        current.summary = Some(plain_summary(&value));
+                    }
+                    (None, "title") if matches!(parent, Some("channel" | "feed")) => {
+                        feed.title = value;
+                    }
+                    (None, "link")
+                        if matches!(parent, Some("channel"))
+                            && feed.link.is_none()
+                            && !value.is_empty() =>
+                    {
+                        feed.link = Some(value);
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(feed)
+}
+
+fn local_name(start: &BytesStart) -> String {
+    String::from_utf8_lossy(start.local_name().as_ref()).into_owned()
+}
+
+fn set_atom_link(
+    start: &BytesStart,
+    path: &[String],
+    feed: &mut Feed,
+    entry: Option<&mut FeedEntry>,
+) -> Result<(), quick_xml::Error> {
+    let Some(href) = start.try_get_attribute("href")? else {
+        return Ok(());
+    };
+    if let Some(rel) = start.try_get_attribute("rel")?
+        && rel.unescape_value()? != "alternate"
+    {
+        return Ok(());
+    }
+    let href = href.unescape_value()?.into_owned();
+    match entry {
+        Some(entry) if entry.link.is_none() => entry.link = Some(href),
+        None if feed.link.is_none() && path.last().is_some_and(|p| p == "feed") => {
+            feed.link = Some(href)
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn plain_summary(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl Feed {
+    pub fn to_document(&self) -> Document {
+        let dim = Style::new().italic().fg(Color::gray(60));
+        let mut doc = Document::new();
+        let title = if self.title.is_empty() {
+            "Feed"
+        } else {
+            &self.title
+        };
+        doc.push(Line::heading(1).text(title));
+        if let Some(link) = &self.link {
+            doc.push(Line::normal().link(LinkElement::new(link)));
+        }
+        if self.entries.is_empty() {
+            doc.push(Line::normal().italic("No entries"));
+        }
+        for entry in &self.entries {
+            doc.push(Line::heading(2).text(&entry.title));
+            if let Some(date) = &entry.date {
+                doc.push(Line::normal().styled(date, dim));
+            }
+            // One plain line per summary line, so a summary line like
+            // `- item` or `# tag` is serialized escaped instead of as markup.
+            let summary = entry.summary.as_deref().unwrap_or_default();
+            for line in summary.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                doc.push(Line::normal().text(line));
+            }
+            if let Some(link) = &entry.link {
+                doc.push(Line::normal().link(LinkElement::new(link)));
+            }
+        }
+        doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0">
+  <channel>
+    <title>Node News</title>
+    <link>http://example.com/</link>
+    <image><title>Logo</title><url>http://example.com/logo.png</url></image>
+    <item>
+      <title>First &amp; foremost</title>
+      <link>http://example.com/1</link>
+      <pubDate>Mon, 01 Jan 2024 10:00:00 GMT</pubDate>
+      <description><![CDATA[<p>Hello <b>world</b></p>]]></description>
+    </item>
+    <item>
+      <title>Second</title>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Node</title>
+  <link rel="self" href="http://example.com/feed.xml"/>
+  <link href="http://example.com/"/>
+  <entry>
+    <title>Entry</title>
+    <link rel="alternate" href="http://example.com/entry"/>
+    <updated>2024-02-03T04:05:06Z</updated>
+    <summary>Short summary</summary>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn parse_rss() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!(feed.title, "Node News");
+        assert_eq!(feed.link.as_deref(), Some("http://example.com/"));
+        assert_eq!(feed.entries.len(), 2);
+        assert_eq!(
+            feed.entries[0],
+            FeedEntry {
+                title: "First & foremost".to_string(),
+                link: Some("http://example.com/1".to_string()),
+                date: Some("Mon, 01 Jan 2024 10:00:00 GMT".to_string()),
+                summary: Some("Hello world".to_string()),
+            }
+        );
+        assert_eq!(feed.entries[1].link, None);
+    }
+
+    #[test]
+    fn parse_atom() {
+        let feed = parse_feed(ATOM).unwrap();
+        assert_eq!(feed.title, "Atom Node");
+        assert_eq!(feed.link.as_deref(), Some("http://example.com/"));
+        assert_eq!(
+            feed.entries[0].link.as_deref(),
+            Some("http://example.com/entry")
+        );
+        assert_eq!(
+            feed.entries[0].date.as_deref(),
+            Some("2024-02-03T04:05:06Z")
+        );
+        assert_eq!(feed.entries[0].summary.as_deref(), Some("Short summary"));
+    }
+
+    #[test]
+    fn feed_page() {
+        let doc = parse_feed(ATOM).unwrap().to_document();
+        assert_eq!(
+            doc.to_string(),
+            ">Atom Node\n`[http://example.com/]\n>>Entry\n`*`Fg602024-02-03T04:05:06Z\n`*`fShort summary\n`[http://example.com/entry]"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "parser")]
+    fn summary_markers_stay_text() {
+        let feed = Feed {
+            title: "News".to_string(),
+            link: None,
+            entries: vec![FeedEntry {
+                title: "Entry".to_string(),
+                summary: Some("- not a divider\n# not a comment\n> not a heading".to_string()),
+                ..Default::default()
+            }],
+        };
+        let doc = crate::parse(&feed.to_document().to_string());
+        let summary: Vec<_> = doc.lines[2..]
+            .iter()
+            .map(|line| match (&line.kind, line.elements.as_slice()) {
+                (crate::LineKind::Normal, [crate::Element::Text(text)]) => text.text.as_str(),
+                _ => panic!("expected a plain line, got {:?}", line),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            ["- not a divider", "# not a comment", "> not a heading"]
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::micronaut::date::{format_date, from_days};
+use crate::micronaut::expand::IncludeResolver;
+use crate::micronaut::loader::{PageLoader, literal_page};
+use crate::micronaut::types::Link;
+use crate::{Document, Line, LinkElement, Style};
+
+pub const FILE_SCHEME: &str = "file://";
+
+#[derive(Debug, Clone, Default)]
+pub struct FileLoader {
+    root: Option<PathBuf>,
+}
+
+impl FileLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// The file a `file://` url points at. With a [`root`](Self::root), the
+    /// path is canonicalized and `None` if it doesn't exist or resolves
+    /// outside the root, through `..` or symlinks alike.
+    pub fn path(&self, url: &str) -> Option<PathBuf> {
+        self.file_path(url).ok()
+    }
+
+    fn file_path(&self, url: &str) -> io::Result<PathBuf> {
+        let path = url.strip_prefix(FILE_SCHEME).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("not a file url: {}", url),
+            )
+        })?;
+        self.confine(Path::new(path))
+    }
+
+    fn confine(&self, path: &Path) -> io::Result<PathBuf> {
+        let Some(root) = &self.root else {
+            return Ok(path.to_path_buf());
+        };
+        let root = fs::canonicalize(root)?;
+        let path = fs::canonicalize(root.join(path))?;
+        if !path.starts_with(&root) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is outside {}", path.display(), root.display()),
+            ));
+        }
+        Ok(path)
+    }
+
+    pub fn resolve(&self, base: &str, target: &str) -> Option<String> {
+        if target.starts_with(FILE_SCHEME) {
+            return Some(target.to_string());
+        }
+        if target.contains("://") || target.starts_with(':') {
+            return None;
+        }
+        if target.starts_with('/') {
+            return Some(file_url(Path::new(target)));
+        }
+        let base = self.path(base)?;
+        let dir = if base.is_dir() {
+            base.as_path()
+        } else {
+            base.parent().unwrap_or(Path::new(""))
+        };
+        Some(file_url(&dir.join(target)))
+    }
+
+    pub fn directory_page(&self, dir: &Path) -> io::Result<Document> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let mut name = entry.file_name().to_string_lossy().into_owned();
+            if metadata.is_dir() {
+                name.push('/');
+            }
+            entries.push((metadata.is_dir(), name, entry.path(), metadata));
+        }
+        entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        let mut doc = Document::new();
+        doc.push(Line::heading(1).text(&dir.display().to_string()));
+        let at_root = self
+            .root
+            .as_ref()
+            .is_some_and(|root| fs::canonicalize(root).ok() == fs::canonicalize(dir).ok());
+        if let Some(parent) = dir.parent().filter(|_| !at_root) {
+            doc.push(Line::normal().link(LinkElement::new(file_url(parent)).label("../")));
+        }
+        if entries.is_empty() {
+            doc.push(Line::normal().italic("Empty directory"));
+        }
+        let name_width = entries
+            .iter()
+            .map(|(_, name, _, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let dim = Style::new().fg(crate::Color::gray(50));
+        for (is_dir, name, path, metadata) in &entries {
+            let size = if *is_dir {
+                String::new()
+            } else {
+                format_size(metadata.len())
+            };
+            let modified = metadata
+                .modified()
+                .map(format_timestamp)
+                .unwrap_or_default();
+            let padding = name_width - name.chars().count();
+            doc.push(
+                Line::normal()
+                    .link(LinkElement::new(file_url(path)).label(name.as_str()))
+                    .styled(&format!("{:padding$}  {:>9}  {}", "", size, modified), dim),
+            );
+        }
+        Ok(doc)
+    }
+}
+
+impl PageLoader for FileLoader {
+    type Error = io::Error;
+
+    async fn load(&self, link: &Link) -> Result<String, io::Error> {
+        let path = self.file_path(&link.url)?;
+        if path.is_dir() {
+            return Ok(self.directory_page(&path)?.to_string());
+        }
+        let content = fs::read_to_string(&path)?;
+        if path.extension().is_some_and(|ext| ext == "mu") {
+            Ok(content)
+        } else {
+            Ok(literal_page(&content))
+        }
+    }
+}
+
+impl IncludeResolver for FileLoader {
+    fn include(&self, url: &str) -> Option<String> {
+        let path = match url.strip_prefix(FILE_SCHEME) {
+            Some(path) => path,
+            // Root-relative, like a NomadNet node's `/page/...` links
+            None if self.root.is_some() => url.trim_start_matches('/'),
+            None => url,
+        };
+        fs::read_to_string(self.confine(Path::new(path)).ok()?).ok()
+    }
+}
+
+pub fn file_url(path: &Path) -> String {
+    format!("{}{}", FILE_SCHEME, path.display())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let (hour, minute) = ((secs % 86_400) / 3600, (secs % 3600) / 60);
+    format!("{} {:02}:{:02}", format_date(from_days(days)), hour, minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::micronaut::loader::block_on;
+    use std::time::Duration;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("micronaut-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("index.mu"), ">Index").unwrap();
+        fs::write(dir.join("notes.txt"), "`!plain`!").unwrap();
+        dir
+    }
+
+    #[test]
+    fn formats_sizes_and_dates() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01 00:00");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29 12:34"
+        );
+    }
+
+    #[test]
+    fn includes_from_root() {
+        let dir = temp_dir("include");
+        fs::write(dir.join("sub/footer.mu"), "-\nfooter").unwrap();
+        let loader = FileLoader::new().root(&dir);
+        let doc = crate::parse("body\n#@include /sub/footer.mu");
+        assert_eq!(
+            crate::expand_includes(&doc, &loader).to_string(),
+            "body\n-\nfooter"
+        );
+        assert_eq!(loader.include("missing.mu"), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resolves_relative_urls() {
+        let loader = FileLoader::new();
+        assert_eq!(
+            loader
+                .resolve("file:///docs/index.mu", "guide.mu")
+                .as_deref(),
+            Some("file:///docs/guide.mu")
+        );
+        assert_eq!(
+            loader
+                .resolve("file:///docs/index.mu", "/etc/motd")
+                .as_deref(),
+            Some("file:///etc/motd")
+        );
+        assert_eq!(loader.resolve("file:///docs/index.mu", ":/page/a.mu"), None);
+        assert_eq!(
+            loader.resolve("file:///docs/index.mu", "https://example.com"),
+            None
+        );
+    }
+
+    #[test]
+    fn loads_files_and_directories() {
+        let dir = temp_dir("loader");
+        let loader = FileLoader::new().root(&dir);
+
+        let page = block_on(loader.load(&Link::new("file://index.mu"))).unwrap();
+        assert_eq!(page, ">Index");
+
+        let page = block_on(loader.load(&Link::new("file://notes.txt"))).unwrap();
+        assert_eq!(page, "`=\n`!plain`!\n`=");
+
+        let page = block_on(loader.load(&Link::new(file_url(&dir)))).unwrap();
+        let doc = crate::parse(&page);
+        let links: Vec<_> = doc
+            .lines
+            .iter()
+            .flat_map(|line| &line.elements)
+            .filter_map(|element| match element {
+                crate::Element::Link(link) => Some(link.label.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(links, ["sub/", "index.mu", "notes.txt"]);
+        assert!(page.contains("6 B"));
+
+        let err = block_on(loader.load(&Link::new(":/page/index.mu"))).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn paths_stay_inside_root() {
+        let outside = temp_dir("outside");
+        let dir = outside.join("root");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/page.mu"), "inside").unwrap();
+        let loader = FileLoader::new().root(&dir);
+
+        let page = block_on(loader.load(&Link::new("file://sub/../sub/page.mu"))).unwrap();
+        assert_eq!(page, "inside");
+        let inside = file_url(&dir.join("sub/page.mu"));
+        assert_eq!(block_on(loader.load(&Link::new(inside))).unwrap(), "inside");
+
+        let err = block_on(loader.load(&Link::new("file://../index.mu"))).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let absolute = file_url(&outside.join("index.mu"));
+        let err = block_on(loader.load(&Link::new(absolute.as_str()))).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(loader.path(&absolute), None);
+
+        assert_eq!(loader.include("../index.mu"), None);
+        assert_eq!(loader.include("/sub/../../index.mu"), None);
+        assert_eq!(loader.include("/sub/page.mu").as_deref(), Some("inside"));
+
+        fs::remove_dir_all(outside).unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(feature = "render")]
+use crate::Link;
+
+/// Percent-encodes everything but unreserved characters (`A-Z a-z 0-9 - . _ ~`).
+pub fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Joins pairs as `key=value&key=value`, percent-encoding both sides.
+pub fn encode_form<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut out = String::new();
+    for (key, value) in pairs {
+        if !out.is_empty() {
+            out.push('&');
+        }
+        out.push_str(&percent_encode(key));
+        out.push('=');
+        out.push_str(&percent_encode(value));
+    }
+    out
+}
+
+#[cfg(feature = "render")]
+impl Link {
+    /// Form data in the order NomadNet nodes expect: `field_*` entries, then
+    /// `var_*` entries, each sorted by name.
+    pub fn form_pairs(&self) -> Vec<(&str, &str)> {
+        let mut pairs: Vec<_> = self
+            .form_data
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        pairs.sort_by_key(|(key, _)| (!key.starts_with("field_"), *key));
+        pairs
+    }
+
+    pub fn query_string(&self) -> String {
+        encode_form(self.form_pairs())
+    }
+
+    /// The url with the form data appended as a query string, ahead of any
+    /// `#fragment`.
+    pub fn request_url(&self) -> String {
+        if self.form_data.is_empty() {
+            return self.url.clone();
+        }
+        let (base, fragment) = match self.url.split_once('#') {
+            Some((base, fragment)) => (base, Some(fragment)),
+            None => (self.url.as_str(), None),
+        };
+        let separator = if base.contains('?') { '&' } else { '?' };
+        let mut url = format!("{}{}{}", base, separator, self.query_string());
+        if let Some(fragment) = fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_reserved_and_unicode() {
+        assert_eq!(percent_encode("a b&c=d/é~"), "a%20b%26c%3Dd%2F%C3%A9~");
+        assert_eq!(encode_form([("q", "1+1"), ("x", "")]), "q=1%2B1&x=");
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn request_url_orders_fields_before_vars() {
+        let mut link = Link::new(":/page/search.mu?lang=en#top");
+        link.form_data
+            .insert("var_page".to_string(), "2".to_string());
+        link.form_data
+            .insert("field_query".to_string(), "rust lang".to_string());
+        link.form_data
+            .insert("field_exact".to_string(), "1".to_string());
+        assert_eq!(
+            link.form_pairs(),
+            [
+                ("field_exact", "1"),
+                ("field_query", "rust lang"),
+                ("var_page", "2"),
+            ]
+        );
+        assert_eq!(
+            link.request_url(),
+            ":/page/search.mu?lang=en&field_exact=1&field_query=rust%20lang&var_page=2#top"
+        );
+        assert_eq!(Link::new("/a").request_url(), "/a");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{Document, Element, Line, LineKind, LinkElement};
+
+pub fn parse_gemtext(input: &str) -> Document {
+    let mut doc = Document::new();
+    let mut preformatted = false;
+    for line in input.lines() {
+        if line.starts_with("```") {
+            preformatted = !preformatted;
+            doc.push(Line::new(if preformatted {
+                LineKind::LiteralStart
+            } else {
+                LineKind::LiteralEnd
+            }));
+            continue;
+        }
+        if preformatted {
+            doc.push(Line::normal().text(line));
+            continue;
+        }
+        doc.push(parse_gemtext_line(line));
+    }
+    doc
+}
+
+fn parse_gemtext_line(line: &str) -> Line {
+    if let Some(rest) = line.strip_prefix("=>") {
+        let rest = rest.trim();
+        let (url, label) = match rest.split_once(char::is_whitespace) {
+            Some((url, label)) => (url, label.trim()),
+            None => (rest, ""),
+        };
+        let mut link = LinkElement::new(url);
+        if !label.is_empty() {
+            link = link.label(label);
+        }
+        return Line::normal().link(link);
+    }
+    for level in (1..=3).rev() {
+        let marker = &"###"[..level];
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Line::heading(level as u8).text(rest.trim_start());
+        }
+    }
+    if let Some(rest) = line.strip_prefix("* ") {
+        return Line::normal().text(&format!("\u{2022} {}", rest));
+    }
+    if let Some(rest) = line.strip_prefix('>') {
+        return Line::normal().indent(1).italic(rest.trim_start());
+    }
+    Line::normal().text(line)
+}
+
+impl Document {
+    pub fn to_gemtext(&self) -> String {
+        let mut out = Vec::new();
+        let mut literal = false;
+        for line in &self.lines {
+            match line.kind {
+                LineKind::Normal if literal => out.push(line_text(line)),
+                LineKind::Comment | LineKind::Directive => {}
+                LineKind::LiteralStart | LineKind::LiteralEnd => {
+                    literal = line.kind == LineKind::LiteralStart;
+                    out.push("```".to_string());
+                }
+                LineKind::Divider(ch) => out.push(ch.to_string().repeat(40)),
+                LineKind::Heading(level) => {
+                    out.push(format!(
+                        "{} {}",
+                        "#".repeat(level as usize),
+                        line_text(line)
+                    ));
+                }
+                LineKind::Normal => push_normal_line(line, &mut out),
+            }
+        }
+        out.join("\n")
+    }
+}
+
+fn push_normal_line(line: &Line, out: &mut Vec<String>) {
+    let links: Vec<&LinkElement> = line
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            Element::Link(link) => Some(link),
+            _ => None,
+        })
+        .collect();
+    let only_links = line.elements.iter().all(|element| match element {
+        Element::Link(_) => true,
+        Element::Text(text) => text.text.trim().is_empty(),
+        Element::Field(_) | Element::Partial(_) | Element::Progress(_) => false,
+    });
+    if links.is_empty() || !only_links {
+        let text = line_text(line);
+        if is_gemtext_marker(&text) {
+            out.push(format!(" {}", text));
+        } else {
+            out.push(text);
+        }
+    }
+    for link in links {
+        if link.label == link.url {
+            out.push(format!("=> {}", link.url));
+        } else {
+            out.push(format!("=> {} {}", link.url, link.label));
+        }
+    }
+}
+
+fn line_text(line: &Line) -> String {
+    let mut text = String::new();
+    for element in &line.elements {
+        match element {
+            Element::Text(styled) => text.push_str(&styled.text),
+            Element::Link(link) => text.push_str(&link.label),
+            Element::Field(field) => text.push_str(&field.default),
+            Element::Progress(progress) => text.push_str(&progress.label),
+            Element::Partial(_) => {}
+        }
+    }
+    text
+}
+
+fn is_gemtext_marker(text: &str) -> bool {
+    ["=>", "#", "* ", ">", "```"]
+        .iter()
+        .any(|marker| text.starts_with(marker))
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gemtext_to_micron() {
+        let doc = parse_gemtext(
+            "# Title\n## Section\nplain `text`\n=> gemini://example.org Example\n=> /about\n* item\n> quoted",
+        );
+        assert_eq!(
+            doc.to_string(),
+            ">Title\n>>Section\nplain \\`text\\`\n`[Example`gemini://example.org]\n`[/about]\n\u{2022} item\n`*quoted"
+        );
+        assert_eq!(doc.lines[6].indent_depth, 1);
+
+        let doc = parse_gemtext("```\n# not a heading\n=> not a link\n```");
+        assert_eq!(doc.lines.len(), 4);
+        assert_eq!(doc.lines[0].kind, LineKind::LiteralStart);
+        assert!(
+            doc.lines[1..3]
+                .iter()
+                .all(|line| line.kind == LineKind::Normal)
+        );
+        assert_eq!(doc.lines[3].kind, LineKind::LiteralEnd);
+        assert!(matches!(&doc.lines[2].elements[0], Element::Text(t) if t.text == "=> not a link"));
+        assert_eq!(doc.to_string(), "`=\n# not a heading\n=> not a link\n`=");
+    }
+
+    #[test]
+    fn micron_to_gemtext() {
+        let doc = crate::parse(
+            ">Title\nSee `[the docs`:/page/docs.mu] for more\n`[Home`:/page/index.mu]\n-\n# comment\n#hashtag\n`!bold`! text",
+        );
+        assert_eq!(
+            doc.to_gemtext(),
+            format!(
+                "# Title\nSee the docs for more\n=> :/page/docs.mu the docs\n=> :/page/index.mu Home\n{}\nbold text",
+                "\u{2500}".repeat(40)
+            )
+        );
+    }
+
+    #[test]
+    fn gemtext_roundtrip() {
+        let input = "# Title\nSome text\n=> gemini://example.org Example";
+        assert_eq!(parse_gemtext(input).to_gemtext(), input);
+
+        let input = "Code:\n```\n# kept\n* as is\n```\ndone";
+        assert_eq!(parse_gemtext(input).to_gemtext(), input);
+    }
+
+    #[test]
+    fn literal_blocks_become_fences() {
+        let options = crate::ParseOptions {
+            literal_markers: true,
+            ..Default::default()
+        };
+        let source = "before\n`=\n> quoted `!raw`!\n`=\nafter";
+        let gemtext = crate::parse_with(source, &options).to_gemtext();
+        assert_eq!(gemtext, "before\n```\n> quoted `!raw`!\n```\nafter");
+        assert_eq!(parse_gemtext(&gemtext).to_string(), source);
+    }
+}
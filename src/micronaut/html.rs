@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use crate::{Alignment, Color, Document, Element, Field, FieldKind, Line, LineKind, Style};
+
+impl Document {
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<div class=\"micron\">\n");
+        for line in &self.lines {
+            write_line(line, &mut html);
+        }
+        html.push_str("</div>\n");
+        html
+    }
+}
+
+fn write_line(line: &Line, html: &mut String) {
+    let align = match line.alignment {
+        Alignment::Left => "",
+        Alignment::Center => " style=\"text-align:center\"",
+        Alignment::Right => " style=\"text-align:right\"",
+    };
+    let tag = match line.kind {
+        LineKind::Comment | LineKind::Directive | LineKind::LiteralStart | LineKind::LiteralEnd => {
+            return;
+        }
+        LineKind::Divider(_) => {
+            html.push_str("<hr>\n");
+            return;
+        }
+        LineKind::Heading(level) => format!("h{}", level),
+        LineKind::Normal => "p".to_string(),
+    };
+    let _ = write!(html, "<{}{}>", tag, align);
+    for element in &line.elements {
+        write_element(element, html);
+    }
+    let _ = writeln!(html, "</{}>", tag);
+}
+
+fn write_element(element: &Element, html: &mut String) {
+    match element {
+        Element::Text(text) => write_styled(&text.text, &text.style, html),
+        Element::Link(link) => {
+            html.push_str("<a href=\"");
+            escape(&link.url, html);
+            html.push('"');
+            if !link.fields.is_empty() {
+                html.push_str(" data-fields=\"");
+                escape(&link.fields.join("|"), html);
+                html.push('"');
+            }
+            html.push('>');
+            write_styled(&link.label, &link.style, html);
+            html.push_str("</a>");
+        }
+        Element::Field(field) => write_field(field, html),
+        Element::Partial(partial) => {
+            html.push_str("<span class=\"partial\" data-url=\"");
+            escape(&partial.url, html);
+            html.push('"');
+            if let Some(refresh) = partial.refresh {
+                let _ = write!(html, " data-refresh=\"{}\"", refresh);
+            }
+            html.push_str("></span>");
+        }
+        Element::Progress(progress) => {
+            let _ = write!(
+                html,
+                "<progress value=\"{}\" max=\"{}\"></progress>",
+                progress.value, progress.max
+            );
+            if !progress.label.is_empty() {
+                html.push(' ');
+                escape(&progress.label, html);
+            }
+        }
+    }
+}
+
+fn write_field(field: &Field, html: &mut String) {
+    html.push_str("<input name=\"");
+    escape(&field.name, html);
+    html.push('"');
+    match &field.kind {
+        FieldKind::Text => {
+            let kind = if field.masked { "password" } else { "text" };
+            let _ = write!(html, " type=\"{}\" value=\"", kind);
+            escape(&field.default, html);
+            html.push('"');
+            if let Some(width) = field.width {
+                let _ = write!(html, " size=\"{}\"", width);
+            }
+            html.push('>');
+        }
+        FieldKind::Number { min, max, step } => {
+            html.push_str(" type=\"number\" value=\"");
+            escape(&field.default, html);
+            html.push('"');
+            if let Some(min) = min {
+                let _ = write!(html, " min=\"{}\"", min);
+            }
+            if let Some(max) = max {
+                let _ = write!(html, " max=\"{}\"", max);
+            }
+            let _ = write!(html, " step=\"{}\">", step);
+        }
+        FieldKind::Date => {
+            html.push_str(" type=\"date\" value=\"");
+            escape(&field.default, html);
+            html.push_str("\">");
+        }
+        FieldKind::Checkbox { value, checked } => {
+            html.push_str(" type=\"checkbox\"");
+            if !value.is_empty() {
+                html.push_str(" value=\"");
+                escape(value, html);
+                html.push('"');
+            }
+            if *checked {
+                html.push_str(" checked");
+            }
+            html.push('>');
+            escape(&field.default, html);
+        }
+        FieldKind::Radio { value, checked } => {
+            html.push_str(" type=\"radio\" value=\"");
+            escape(value, html);
+            html.push('"');
+            if *checked {
+                html.push_str(" checked");
+            }
+            html.push('>');
+            escape(&field.default, html);
+        }
+    }
+}
+
+fn write_styled(text: &str, style: &Style, html: &mut String) {
+    let mut css = String::new();
+    if let Some(fg) = style.fg {
+        let _ = write!(css, "color:{};", hex(fg));
+    }
+    if let Some(bg) = style.bg {
+        let _ = write!(css, "background:{};", hex(bg));
+    }
+    if style.bold {
+        css.push_str("font-weight:bold;");
+    }
+    if style.italic {
+        css.push_str("font-style:italic;");
+    }
+    if style.underline {
+        css.push_str("text-decoration:underline;");
+    }
+    if css.is_empty() {
+        escape(text, html);
+    } else {
+        let _ = write!(html, "<span style=\"{}\">", css);
+        escape(text, html);
+        html.push_str("</span>");
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn escape(text: &str, html: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(ch),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    #[test]
+    fn export_html() {
+        let doc = crate::parse(
+            ">Title\n`c`!Bold`! & <plain>\n-\n`a`[Go`:/page/a.mu`name]`<8|name`Ada>\n# hidden",
+        );
+        assert_eq!(
+            doc.to_html(),
+            "<div class=\"micron\">\n\
+             <h1>Title</h1>\n\
+             <p style=\"text-align:center\"><span style=\"font-weight:bold;\">Bold</span> &amp; &lt;plain&gt;</p>\n\
+             <hr>\n\
+             <p><a href=\":/page/a.mu\" data-fields=\"name\">Go</a><input name=\"name\" type=\"text\" value=\"Ada\" size=\"8\"></p>\n\
+             </div>\n"
+        );
+    }
+}
@@ -0,0 +1,503 @@
+use crate::micronaut::loader::{PageLoader, literal_page, spawn_blocking};
+use crate::micronaut::types::Link;
+use crate::{Document, Element, Line, LineKind, LinkElement, Style};
+
+const DEFAULT_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+pub struct HttpLoader {
+    agent: ureq::Agent,
+    max_bytes: u64,
+}
+
+impl HttpLoader {
+    pub fn new() -> Self {
+        Self::with_agent(ureq::agent())
+    }
+
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        Self {
+            agent,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+impl Default for HttpLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PageLoader for HttpLoader {
+    type Error = ureq::Error;
+
+    async fn load(&self, link: &Link) -> Result<String, ureq::Error> {
+        let agent = self.agent.clone();
+        let max_bytes = self.max_bytes;
+        let link = link.clone();
+        spawn_blocking(move || fetch(&agent, max_bytes, &link)).await
+    }
+}
+
+fn fetch(agent: &ureq::Agent, max_bytes: u64, link: &Link) -> Result<String, ureq::Error> {
+    let response = if link.form_data.is_empty() {
+        with_headers(agent.get(&link.url), link).call()?
+    } else {
+        with_headers(agent.post(&link.url), link).send_form(link.form_pairs())?
+    };
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = response
+        .into_body()
+        .with_config()
+        .limit(max_bytes)
+        .read_to_string()?;
+    Ok(http_page(&link.url, &content_type, body))
+}
+
+fn with_headers<B>(mut request: ureq::RequestBuilder<B>, link: &Link) -> ureq::RequestBuilder<B> {
+    for (name, value) in &link.headers {
+        request = request.header(name, value);
+    }
+    request
+}
+
+/// Turns an HTTP response into micron: micron passes through, HTML is
+/// converted, and anything else is shown as literal text.
+pub fn http_page(url: &str, content_type: &str, body: String) -> String {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if is_micron(url, &mime) {
+        body
+    } else if mime == "text/html" || mime == "application/xhtml+xml" {
+        html_document(url, &body).to_string()
+    } else {
+        literal_page(&body)
+    }
+}
+
+fn is_micron(url: &str, mime: &str) -> bool {
+    if mime == "text/micron" {
+        return true;
+    }
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    (mime.is_empty() || mime == "text/plain" || mime == "application/octet-stream")
+        && path.ends_with(".mu")
+}
+
+/// A readable approximation of an HTML page: headings, paragraphs, lists,
+/// links, bold / italic / underline, rules and preformatted blocks.
+/// Scripts, styles and everything else are dropped.
+fn html_document(url: &str, html: &str) -> Document {
+    let mut page = HtmlPage {
+        url,
+        doc: Document::new(),
+        line: Line::normal(),
+        style: Style::default(),
+        link: None,
+        pre: false,
+    };
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        page.text(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        if !closing && matches!(name.as_str(), "script" | "style" | "title" | "noscript") {
+            let close = format!("</{}", name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .and_then(|pos| rest[pos..].find('>').map(|end| &rest[pos + end + 1..]))
+                .unwrap_or("");
+            continue;
+        }
+        page.tag(&name, closing, &tag[name_end..]);
+    }
+    page.text(rest);
+    page.flush();
+    page.doc
+}
+
+struct HtmlPage<'a> {
+    url: &'a str,
+    doc: Document,
+    line: Line,
+    style: Style,
+    link: Option<(String, String)>,
+    pre: bool,
+}
+
+impl HtmlPage<'_> {
+    fn text(&mut self, raw: &str) {
+        let mut text = decode_entities(raw);
+        if self.pre {
+            if self.line.elements.is_empty()
+                && self.doc.lines.last().map(|line| line.kind) == Some(LineKind::LiteralStart)
+                && text.starts_with('\n')
+            {
+                text.remove(0);
+            }
+            let mut lines = text.split('\n');
+            if let Some(first) = lines.next() {
+                self.push_text(first);
+            }
+            for line in lines {
+                self.flush();
+                self.push_text(line);
+            }
+            return;
+        }
+        let mut collapsed = String::new();
+        for word in text.split_whitespace() {
+            if !collapsed.is_empty() {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        if text.starts_with(char::is_whitespace) && !self.after_space() {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() && !collapsed.ends_with(' ')
+        {
+            collapsed.push(' ');
+        }
+        self.push_text(&collapsed);
+    }
+
+    /// Whether the text so far ends a word, so leading whitespace is dropped.
+    fn after_space(&self) -> bool {
+        let last = match &self.link {
+            Some((_, label)) => label.chars().next_back(),
+            None => match self.line.elements.last() {
+                Some(Element::Text(text)) => text.text.chars().next_back(),
+                Some(_) => Some('x'),
+                None => None,
+            },
+        };
+        last.is_none_or(|c| c == ' ')
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match &mut self.link {
+            Some((_, label)) => label.push_str(text),
+            None => {
+                let line = std::mem::replace(&mut self.line, Line::normal());
+                self.line = line.styled(text, self.style);
+            }
+        }
+    }
+
+    fn tag(&mut self, name: &str, closing: bool, attributes: &str) {
+        match name {
+            "br" => self.flush(),
+            "hr" => {
+                self.flush();
+                self.doc.push(Line::divider());
+            }
+            "b" | "strong" => self.style.bold = !closing,
+            "i" | "em" => self.style.italic = !closing,
+            "u" => self.style.underline = !closing,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                if !closing {
+                    let level = name[1..].parse::<u8>().unwrap_or(1).min(3);
+                    self.line = Line::heading(level);
+                }
+            }
+            "pre" => {
+                if !closing || !self.line.elements.is_empty() {
+                    self.flush();
+                }
+                self.pre = !closing;
+                self.doc.push(Line::new(if closing {
+                    LineKind::LiteralEnd
+                } else {
+                    LineKind::LiteralStart
+                }));
+            }
+            "li" => {
+                self.flush();
+                if !closing {
+                    self.push_text("\u{2022} ");
+                }
+            }
+            "a" if closing => {
+                if let Some((url, label)) = self.link.take() {
+                    let label = label.trim();
+                    let mut link = LinkElement::new(url).style(self.style);
+                    if !label.is_empty() {
+                        link = link.label(label);
+                    }
+                    self.line = std::mem::replace(&mut self.line, Line::normal()).link(link);
+                }
+            }
+            "a" => {
+                if let Some(href) = attribute(attributes, "href")
+                    .filter(|href| !href.is_empty() && !href.starts_with('#'))
+                {
+                    self.link = Some((resolve(self.url, &decode_entities(href)), String::new()));
+                }
+            }
+            "p" | "div" | "section" | "article" | "header" | "footer" | "nav" | "main" | "ul"
+            | "ol" | "dl" | "dt" | "dd" | "table" | "tr" | "blockquote" | "form" => self.flush(),
+            _ => {}
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.pre && !self.line.elements.is_empty() {
+            let line = std::mem::replace(&mut self.line, Line::normal());
+            self.doc.push(line);
+            return;
+        }
+        if let Some(Element::Text(text)) = self.line.elements.last_mut() {
+            let trimmed = text.text.trim_end().len();
+            text.text.truncate(trimmed);
+        }
+        let line = std::mem::replace(&mut self.line, Line::normal());
+        let empty = line.elements.iter().all(|element| match element {
+            Element::Text(text) => text.text.is_empty(),
+            _ => false,
+        });
+        if !empty {
+            self.doc.push(line);
+        } else if self.pre {
+            self.doc.push(Line::normal());
+        }
+    }
+}
+
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let lower = attributes.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name) {
+        let start = from + pos;
+        from = start + name.len();
+        let before = lower[..start].chars().next_back();
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let value = attributes[from..]
+            .trim_start()
+            .strip_prefix('=')?
+            .trim_start();
+        return Some(match value.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let value = &value[1..];
+                &value[..value.find(quote).unwrap_or(value.len())]
+            }
+            _ => {
+                &value[..value
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(value.len())]
+            }
+        });
+    }
+    None
+}
+
+fn resolve(base: &str, href: &str) -> String {
+    if href.contains("://") || href.starts_with("mailto:") {
+        return href.to_string();
+    }
+    let (scheme, rest) = base.split_once("://").unwrap_or(("https", base));
+    let origin_end = rest.find('/').unwrap_or(rest.len());
+    if let Some(path) = href.strip_prefix("//") {
+        format!("{}://{}", scheme, path)
+    } else if href.starts_with('/') {
+        format!("{}://{}{}", scheme, &rest[..origin_end], href)
+    } else {
+        let path = rest.split(['?', '#']).next().unwrap_or(rest);
+        let dir_end = path.rfind('/').filter(|&end| end >= origin_end);
+        match dir_end {
+            Some(end) => format!("{}://{}{}", scheme, &path[..=end], href),
+            None => format!("{}://{}/{}", scheme, path, href),
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                _ => {
+                    let code = entity.strip_prefix('#')?;
+                    let code = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::micronaut::ast::Element;
+
+    #[test]
+    fn micron_content_passes_through() {
+        let body = ">Title\n`!bold`!".to_string();
+        assert_eq!(
+            http_page(
+                "https://example.com/a",
+                "text/micron; charset=utf-8",
+                body.clone()
+            ),
+            body
+        );
+        assert_eq!(
+            http_page(
+                "https://example.com/index.mu?x=1",
+                "text/plain",
+                body.clone()
+            ),
+            body
+        );
+    }
+
+    #[test]
+    fn html_is_converted() {
+        let html = "<html><head><title>T</title><style>p { color: red }</style></head>\
+            <body><h1>Hello &amp; welcome</h1>\
+            <p>Some <b>bold</b> <i>text</i>, see <a href=\"/docs/\">the docs</a>.</p>\
+            <script>let a = '<p>';</script>\
+            <ul><li>One</li><li><a href='next.html'>Two</a></li></ul><hr>\
+            <pre>\nfn main() {}\n</pre></body></html>";
+        let page = http_page(
+            "https://example.com/a/page.html",
+            "text/html",
+            html.to_string(),
+        );
+        assert_eq!(
+            page,
+            ">Hello & welcome\n\
+            Some `!bold`! `*text`*, see `[the docs`https://example.com/docs/].\n\
+            \u{2022} One\n\
+            \u{2022} `[Two`https://example.com/a/next.html]\n\
+            -\n\
+            `=\n\
+            fn main() {}\n\
+            `="
+        );
+    }
+
+    #[test]
+    fn link_headers_are_sent() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/page.mu", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = Vec::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                request.push(std::mem::take(&mut line).trim_end().to_ascii_lowercase());
+            }
+            let body = "hi";
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            request
+        });
+
+        let mut link = Link::new(url);
+        link.headers
+            .insert("Authorization".to_string(), "Bearer t".to_string());
+        let page = crate::micronaut::loader::block_on(HttpLoader::new().load(&link)).unwrap();
+        assert_eq!(page, "hi");
+        let request = server.join().unwrap();
+        assert!(request.contains(&"authorization: bearer t".to_string()));
+    }
+
+    #[test]
+    fn load_runs_off_the_calling_thread() {
+        let caller = std::thread::current().id();
+        let worker =
+            crate::micronaut::loader::block_on(spawn_blocking(move || std::thread::current().id()));
+        assert_ne!(worker, caller);
+    }
+
+    #[test]
+    fn other_content_is_shown_literally() {
+        let page = http_page(
+            "https://example.com/",
+            "text/plain",
+            "<h1>`!Hi`!</h1>\n`=\nbye".to_string(),
+        );
+        let doc = crate::parse(&page);
+        assert_eq!(doc.lines.len(), 3);
+        let Element::Text(text) = &doc.lines[0].elements[0] else {
+            panic!("Expected text");
+        };
+        assert_eq!(text.text, "<h1>`!Hi`!</h1>");
+        assert!(!text.style.bold);
+        let Element::Text(text) = &doc.lines[1].elements[0] else {
+            panic!("Expected text");
+        };
+        assert_eq!(text.text, "`=");
+    }
+}
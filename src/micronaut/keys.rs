@@ -0,0 +1,493 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::collections::HashMap;
+
+use crate::micronaut::browser::Browser;
+use crate::micronaut::render::Renderer;
+use crate::micronaut::types::Interaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    SelectNext,
+    SelectPrev,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    NextHeading,
+    PrevHeading,
+    Activate,
+    Back,
+    Forward,
+    Cancel,
+    CopyUrl,
+    Hints,
+}
+
+impl Action {
+    pub const ALL: [Action; 18] = [
+        Action::SelectNext,
+        Action::SelectPrev,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::NextHeading,
+        Action::PrevHeading,
+        Action::Activate,
+        Action::Back,
+        Action::Forward,
+        Action::Cancel,
+        Action::CopyUrl,
+        Action::Hints,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::SelectNext => "Select next element",
+            Action::SelectPrev => "Select previous element",
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Select left",
+            Action::Right => "Select right",
+            Action::PageUp => "Page up",
+            Action::PageDown => "Page down",
+            Action::Top => "Go to top",
+            Action::Bottom => "Go to bottom",
+            Action::NextHeading => "Next heading",
+            Action::PrevHeading => "Previous heading",
+            Action::Activate => "Open link / edit field",
+            Action::Back => "Back",
+            Action::Forward => "Forward",
+            Action::Cancel => "Clear selection",
+            Action::CopyUrl => "Copy link url",
+            Action::Hints => "Pick a link by its hint",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BrowserEvent {
+    Ignored,
+    Handled,
+    Interaction(Interaction),
+    Copied(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<(KeyCode, KeyModifiers), Action>,
+}
+
+impl KeyMap {
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub fn bind(mut self, code: KeyCode, modifiers: KeyModifiers, action: Action) -> Self {
+        self.bindings.insert((code, modifiers), action);
+        self
+    }
+
+    pub fn unbind(mut self, code: KeyCode, modifiers: KeyModifiers) -> Self {
+        self.bindings.remove(&(code, modifiers));
+        self
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<(KeyModifiers, String)> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|((code, modifiers), _)| (*modifiers, key_label(*code, *modifiers)))
+            .collect();
+        keys.sort_by(|a, b| (a.0.bits(), &a.1).cmp(&(b.0.bits(), &b.1)));
+        keys.into_iter().map(|(_, label)| label).collect()
+    }
+
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .get(&(key.code, key.modifiers))
+            .or_else(|| {
+                self.bindings
+                    .get(&(key.code, key.modifiers - KeyModifiers::SHIFT))
+            })
+            .copied()
+    }
+}
+
+pub fn key_label(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let key = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        other => format!("{:?}", other),
+    };
+    let mut label = String::new();
+    for (modifier, name) in [
+        (KeyModifiers::CONTROL, "Ctrl+"),
+        (KeyModifiers::ALT, "Alt+"),
+        (KeyModifiers::SHIFT, "Shift+"),
+    ] {
+        if modifiers.contains(modifier) {
+            label.push_str(name);
+        }
+    }
+    label.push_str(&key);
+    label
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::empty()
+            .bind(KeyCode::Tab, KeyModifiers::NONE, Action::SelectNext)
+            .bind(KeyCode::BackTab, KeyModifiers::NONE, Action::SelectPrev)
+            .bind(KeyCode::Up, KeyModifiers::NONE, Action::Up)
+            .bind(KeyCode::Down, KeyModifiers::NONE, Action::Down)
+            .bind(KeyCode::Left, KeyModifiers::NONE, Action::Left)
+            .bind(KeyCode::Right, KeyModifiers::NONE, Action::Right)
+            .bind(KeyCode::PageUp, KeyModifiers::NONE, Action::PageUp)
+            .bind(KeyCode::PageDown, KeyModifiers::NONE, Action::PageDown)
+            .bind(KeyCode::Home, KeyModifiers::NONE, Action::Top)
+            .bind(KeyCode::End, KeyModifiers::NONE, Action::Bottom)
+            .bind(KeyCode::Char(']'), KeyModifiers::NONE, Action::NextHeading)
+            .bind(KeyCode::Char('['), KeyModifiers::NONE, Action::PrevHeading)
+            .bind(KeyCode::Enter, KeyModifiers::NONE, Action::Activate)
+            .bind(KeyCode::Backspace, KeyModifiers::NONE, Action::Back)
+            .bind(KeyCode::Left, KeyModifiers::ALT, Action::Back)
+            .bind(KeyCode::Right, KeyModifiers::ALT, Action::Forward)
+            .bind(KeyCode::Esc, KeyModifiers::NONE, Action::Cancel)
+            .bind(KeyCode::Char('y'), KeyModifiers::NONE, Action::CopyUrl)
+            .bind(KeyCode::Char('f'), KeyModifiers::NONE, Action::Hints)
+    }
+}
+
+impl<R: Renderer> Browser<R> {
+    pub fn keymap(&self) -> &KeyMap {
+        &self.keymap
+    }
+
+    pub fn set_keymap(&mut self, keymap: KeyMap) {
+        self.keymap = keymap;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> BrowserEvent {
+        if key.kind == KeyEventKind::Release {
+            return BrowserEvent::Ignored;
+        }
+        if self.editing().is_some() {
+            return self.handle_edit_key(key);
+        }
+        if self.in_hint_mode() {
+            return self.handle_hint_key(key);
+        }
+        let steps = match key.code {
+            KeyCode::Char('+') => 1,
+            KeyCode::Char('-') => -1,
+            _ => 0,
+        };
+        if steps != 0 && self.step_selected(steps) {
+            return BrowserEvent::Handled;
+        }
+        let Some(action) = self.keymap.action(&key) else {
+            return BrowserEvent::Ignored;
+        };
+        self.perform(action)
+    }
+
+    pub fn perform(&mut self, action: Action) -> BrowserEvent {
+        match action {
+            Action::SelectNext => self.select_next(),
+            Action::SelectPrev => self.select_prev(),
+            Action::Up => {
+                if !self.select_up() {
+                    self.scroll_by(-1);
+                }
+            }
+            Action::Down => {
+                if !self.select_down() {
+                    self.scroll_by(1);
+                }
+            }
+            Action::Left => {
+                self.select_left();
+            }
+            Action::Right => {
+                self.select_right();
+            }
+            Action::PageUp => self.scroll_page_up(),
+            Action::PageDown => self.scroll_page_down(),
+            Action::Top => self.scroll_home(),
+            Action::Bottom => self.scroll_end(),
+            Action::NextHeading => {
+                self.next_heading();
+            }
+            Action::PrevHeading => {
+                self.prev_heading();
+            }
+            Action::Activate => {
+                return match self.interact() {
+                    Some(interaction) => BrowserEvent::Interaction(interaction),
+                    None => BrowserEvent::Handled,
+                };
+            }
+            Action::Back => {
+                if !self.back() {
+                    return BrowserEvent::Ignored;
+                }
+            }
+            Action::Forward => {
+                if !self.forward() {
+                    return BrowserEvent::Ignored;
+                }
+            }
+            Action::Cancel => {
+                if self.selection().is_none() {
+                    return BrowserEvent::Ignored;
+                }
+                self.clear_selection();
+            }
+            Action::CopyUrl => {
+                return match self.copy_selected_url() {
+                    Some(url) => BrowserEvent::Copied(url),
+                    None => BrowserEvent::Ignored,
+                };
+            }
+            Action::Hints => {
+                if !self.enter_hint_mode() {
+                    return BrowserEvent::Ignored;
+                }
+            }
+        }
+        BrowserEvent::Handled
+    }
+
+    pub fn handle_paste(&mut self, text: &str) -> BrowserEvent {
+        if self.editing().is_none() {
+            return BrowserEvent::Ignored;
+        }
+        self.input_str(text);
+        BrowserEvent::Handled
+    }
+
+    fn handle_hint_key(&mut self, key: KeyEvent) -> BrowserEvent {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                match self.hint_input(c) {
+                    Some(interaction) => BrowserEvent::Interaction(interaction),
+                    None => BrowserEvent::Handled,
+                }
+            }
+            _ => {
+                self.exit_hint_mode();
+                BrowserEvent::Handled
+            }
+        }
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) -> BrowserEvent {
+        let word = key.modifiers.contains(KeyModifiers::CONTROL)
+            || key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Enter => {
+                return match self.submit_editing() {
+                    Some(interaction) => BrowserEvent::Interaction(interaction),
+                    None => BrowserEvent::Handled,
+                };
+            }
+            KeyCode::Esc => {
+                self.stop_editing();
+            }
+            KeyCode::Up | KeyCode::Down => {
+                let steps = if key.code == KeyCode::Up { 1 } else { -1 };
+                if !self.step_editing(steps) {
+                    return BrowserEvent::Ignored;
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input_char(c)
+            }
+            KeyCode::Backspace => self.input_backspace(),
+            KeyCode::Delete => self.input_delete(),
+            KeyCode::Left if word => self.cursor_word_left(),
+            KeyCode::Right if word => self.cursor_word_right(),
+            KeyCode::Left => self.cursor_left(),
+            KeyCode::Right => self.cursor_right(),
+            KeyCode::Home => self.cursor_home(),
+            KeyCode::End => self.cursor_end(),
+            _ => return BrowserEvent::Ignored,
+        }
+        BrowserEvent::Handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RatatuiRenderer;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn default_bindings_navigate_and_activate() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`[One`/one] `[Two`/two]\n`<|name`>");
+
+        assert!(matches!(
+            browser.handle_key(key(KeyCode::Tab)),
+            BrowserEvent::Handled
+        ));
+        assert_eq!(browser.selected_link(), Some("/two"));
+        browser.handle_key(key(KeyCode::Left));
+        assert_eq!(browser.selected_link(), Some("/one"));
+
+        let BrowserEvent::Interaction(Interaction::Link(link)) =
+            browser.handle_key(key(KeyCode::Enter))
+        else {
+            panic!("Expected Link interaction");
+        };
+        assert_eq!(link.url, "/one");
+
+        browser.handle_key(key(KeyCode::Down));
+        let BrowserEvent::Interaction(Interaction::EditField(field)) =
+            browser.handle_key(key(KeyCode::Enter))
+        else {
+            panic!("Expected EditField interaction");
+        };
+        assert_eq!(field.name, "name");
+        assert!(matches!(
+            browser.handle_key(key(KeyCode::Esc)),
+            BrowserEvent::Ignored
+        ));
+    }
+
+    #[test]
+    fn keys_edit_field_while_editing() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`<|name`>");
+        browser.begin_editing("name");
+
+        for c in "Ada L".chars() {
+            browser.handle_key(key(KeyCode::Char(c)));
+        }
+        browser.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL));
+        browser.handle_key(key(KeyCode::Backspace));
+        assert!(matches!(
+            browser.handle_key(key(KeyCode::Tab)),
+            BrowserEvent::Ignored
+        ));
+
+        let BrowserEvent::Interaction(Interaction::Submitted(field)) =
+            browser.handle_key(key(KeyCode::Enter))
+        else {
+            panic!("Expected Submitted interaction");
+        };
+        assert_eq!(field.value, "AdaL");
+        assert!(browser.editing().is_none());
+    }
+
+    #[test]
+    fn paste_only_while_editing() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`<|name`>");
+        assert!(matches!(browser.handle_paste("Ada"), BrowserEvent::Ignored));
+
+        browser.begin_editing("name");
+        assert!(matches!(
+            browser.handle_paste("Ada\r\n"),
+            BrowserEvent::Handled
+        ));
+        assert_eq!(browser.field_value("name"), Some("Ada"));
+    }
+
+    #[test]
+    fn key_labels_for_actions() {
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.keys_for(Action::Back), vec!["Backspace", "Alt+Left"]);
+        assert_eq!(keymap.keys_for(Action::SelectPrev), vec!["Shift+Tab"]);
+        assert_eq!(
+            key_label(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            "Ctrl+d"
+        );
+        assert!(KeyMap::empty().keys_for(Action::Back).is_empty());
+    }
+
+    #[test]
+    fn custom_keymap() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`[One`/one] `[Two`/two]");
+        browser.set_keymap(
+            KeyMap::default()
+                .bind(KeyCode::Char('j'), KeyModifiers::NONE, Action::SelectNext)
+                .unbind(KeyCode::Tab, KeyModifiers::NONE),
+        );
+
+        assert!(matches!(
+            browser.handle_key(key(KeyCode::Tab)),
+            BrowserEvent::Ignored
+        ));
+        browser.handle_key(key(KeyCode::Char('j')));
+        assert_eq!(browser.selected_link(), Some("/two"));
+    }
+
+    #[test]
+    fn hint_keys_overlay_and_follow_links() {
+        use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.resize(20, 1);
+        browser.set_content("/", "`[One`/one] `[Two`/two]");
+        browser.handle_key(key(KeyCode::Char('f')));
+        assert!(browser.in_hint_mode());
+
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        browser.render().unwrap().clone().render(area, &mut buf);
+        let row: String = (0..7).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(row, "ane swo");
+
+        let BrowserEvent::Interaction(Interaction::Link(link)) =
+            browser.handle_key(key(KeyCode::Char('s')))
+        else {
+            panic!("Expected Link interaction");
+        };
+        assert_eq!(link.url, "/two");
+
+        browser.handle_key(key(KeyCode::Char('f')));
+        browser.handle_key(key(KeyCode::Esc));
+        assert!(!browser.in_hint_mode());
+    }
+
+    #[test]
+    fn number_fields_step_with_plus_minus_and_arrows() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.resize(40, 2);
+        browser.set_content("/", "`<+|count|1|9`4>");
+        browser.handle_key(key(KeyCode::Char('+')));
+        assert_eq!(browser.field_value("count"), Some("5"));
+        browser.handle_key(key(KeyCode::Char('-')));
+        browser.handle_key(key(KeyCode::Char('-')));
+        assert_eq!(browser.field_value("count"), Some("3"));
+
+        browser.begin_editing("count");
+        browser.handle_key(key(KeyCode::Up));
+        assert_eq!(browser.field_value("count"), Some("4"));
+        browser.handle_key(key(KeyCode::Char('0')));
+        browser.handle_key(key(KeyCode::Down));
+        assert_eq!(browser.field_value("count"), Some("9"));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Document, Element, LineKind, LinkElement, StyledText};
+
+const SCHEMES: [&str; 4] = ["https://", "http://", "nomadnetwork://", "lxmf@"];
+
+impl Document {
+    /// Turns bare `http(s)://`, `nomadnetwork://` and `lxmf@` references in
+    /// plain text into links labelled with the url itself. Literal blocks
+    /// are left as written.
+    pub fn linkify(&mut self) {
+        let mut literal = false;
+        for line in &mut self.lines {
+            match line.kind {
+                LineKind::LiteralStart => literal = true,
+                LineKind::LiteralEnd => literal = false,
+                _ => {}
+            }
+            if line.kind != LineKind::Normal || literal {
+                continue;
+            }
+            let mut elements = Vec::with_capacity(line.elements.len());
+            for element in line.elements.drain(..) {
+                match element {
+                    Element::Text(text) if find_url(&text.text).is_some() => {
+                        linkify_text(&text, &mut elements)
+                    }
+                    other => elements.push(other),
+                }
+            }
+            line.elements = elements;
+        }
+    }
+}
+
+fn linkify_text(text: &StyledText, out: &mut Vec<Element>) {
+    let mut rest = text.text.as_str();
+    while let Some((start, end)) = find_url(rest) {
+        if start > 0 {
+            out.push(Element::Text(StyledText {
+                text: rest[..start].to_string(),
+                style: text.style,
+            }));
+        }
+        out.push(Element::Link(LinkElement {
+            label: rest[start..end].to_string(),
+            url: rest[start..end].to_string(),
+            fields: Vec::new(),
+            required: Vec::new(),
+            style: text.style,
+        }));
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        out.push(Element::Text(StyledText {
+            text: rest.to_string(),
+            style: text.style,
+        }));
+    }
+}
+
+fn find_url(text: &str) -> Option<(usize, usize)> {
+    text.char_indices()
+        .filter(|&(i, _)| {
+            i == 0 || text[..i].ends_with(|c: char| c.is_whitespace() || "(<[\"'".contains(c))
+        })
+        .find_map(|(start, _)| {
+            let scheme = SCHEMES.iter().find(|s| text[start..].starts_with(*s))?;
+            let len = text[start..]
+                .find(char::is_whitespace)
+                .unwrap_or(text.len() - start);
+            let url =
+                text[start..start + len].trim_end_matches(|c: char| ".,;:!?)]>'\"".contains(c));
+            (url.len() > scheme.len()).then_some((start, start + url.len()))
+        })
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    fn links(doc: &Document) -> Vec<&str> {
+        doc.lines
+            .iter()
+            .flat_map(|line| &line.elements)
+            .filter_map(|element| match element {
+                Element::Link(link) => Some(link.url.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bare_urls_become_links() {
+        let mut doc = crate::parse(
+            "see https://example.com/a?b=1, or (nomadnetwork://abcd:/page/index.mu).\n\
+             `!mail lxmf@1234abcd`! now\n\
+             >https://heading.example\n\
+             not a link: xhttp://nope http:// lxmf@",
+        );
+        doc.linkify();
+        assert_eq!(
+            links(&doc),
+            [
+                "https://example.com/a?b=1",
+                "nomadnetwork://abcd:/page/index.mu",
+                "lxmf@1234abcd",
+            ]
+        );
+        assert_eq!(
+            doc.to_string(),
+            "see `[https://example.com/a?b=1], or (`[nomadnetwork://abcd:/page/index.mu]).\n\
+             `!mail `[lxmf@1234abcd]`! now\n\
+             >https://heading.example\n\
+             not a link: xhttp://nope http:// lxmf@"
+        );
+    }
+
+    #[test]
+    fn literal_blocks_are_left_alone() {
+        let options = crate::ParseOptions {
+            literal_markers: true,
+            ..Default::default()
+        };
+        let source = "`=\nsee https://x.example/a\n`=";
+        let mut doc = crate::parse_with(source, &options);
+        doc.linkify();
+        assert!(links(&doc).is_empty());
+        assert_eq!(doc.to_string(), source);
+    }
+}
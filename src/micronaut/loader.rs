@@ -0,0 +1,102 @@
+use std::fmt::Display;
+use std::future::{Future, poll_fn};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::micronaut::types::Link;
+
+pub trait PageLoader {
+    type Error: Display;
+
+    fn load(&self, link: &Link) -> impl Future<Output = Result<String, Self::Error>>;
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs a future to completion on the current thread, parking it while the
+/// future waits. Enough to drive a `PageLoader` from a synchronous app.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Runs blocking work like a synchronous HTTP request on its own thread,
+/// so awaiting it doesn't stall the executor.
+#[cfg(feature = "http")]
+pub(crate) fn spawn_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> impl Future<Output = T> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let waker = Arc::new(std::sync::Mutex::new(None::<Waker>));
+    let wake = Arc::clone(&waker);
+    thread::spawn(move || {
+        let _ = tx.send(work());
+        if let Some(waker) = wake.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            waker.wake();
+        }
+    });
+    poll_fn(move |cx| {
+        *waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+        match rx.try_recv() {
+            Ok(value) => Poll::Ready(value),
+            Err(std::sync::mpsc::TryRecvError::Empty) => Poll::Pending,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                panic!("blocking work panicked")
+            }
+        }
+    })
+}
+
+pub(crate) fn literal_page(text: &str) -> String {
+    let mut page = String::from("`=\n");
+    for line in text.lines() {
+        if line == "`=" {
+            page.push_str("\\`=");
+        } else {
+            page.push_str(line);
+        }
+        page.push('\n');
+    }
+    page.push_str("`=");
+    page
+}
+
+pub(crate) async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut futures: Vec<_> = futures.into_iter().map(|f| Some(Box::pin(f))).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+    poll_fn(|cx| {
+        let mut pending = false;
+        for (slot, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if let Some(future) = slot {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(value) => {
+                        *output = Some(value);
+                        *slot = None;
+                    }
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
@@ -0,0 +1,162 @@
+use std::mem::{size_of, size_of_val};
+
+use crate::{Document, Element, FieldKind, Line};
+
+impl Document {
+    /// Releases spare capacity in every line, element and string.
+    pub fn shrink_to_fit(&mut self) {
+        self.metadata.entries.shrink_to_fit();
+        for (key, value) in &mut self.metadata.entries {
+            key.shrink_to_fit();
+            value.shrink_to_fit();
+        }
+        self.lines.shrink_to_fit();
+        for line in &mut self.lines {
+            line.elements.shrink_to_fit();
+            for element in &mut line.elements {
+                shrink_element(element);
+            }
+        }
+    }
+
+    /// Estimated heap and inline size in bytes, counting allocated capacity
+    /// rather than length.
+    pub fn memory_footprint(&self) -> usize {
+        let metadata = self.metadata.entries.capacity() * size_of::<(String, String)>()
+            + self
+                .metadata
+                .entries
+                .iter()
+                .map(|(key, value)| key.capacity() + value.capacity())
+                .sum::<usize>();
+        let lines = self.lines.capacity() * size_of::<Line>()
+            + self.lines.iter().map(line_footprint).sum::<usize>();
+        size_of::<Document>() + metadata + lines
+    }
+}
+
+fn shrink_element(element: &mut Element) {
+    match element {
+        Element::Text(text) => text.text.shrink_to_fit(),
+        Element::Link(link) => {
+            link.label.shrink_to_fit();
+            link.url.shrink_to_fit();
+            shrink_strings(&mut link.fields);
+        }
+        Element::Field(field) => {
+            field.name.shrink_to_fit();
+            field.default.shrink_to_fit();
+            if let FieldKind::Checkbox { value, .. } | FieldKind::Radio { value, .. } =
+                &mut field.kind
+            {
+                value.shrink_to_fit();
+            }
+        }
+        Element::Partial(partial) => {
+            partial.url.shrink_to_fit();
+            shrink_strings(&mut partial.fields);
+        }
+        Element::Progress(progress) => progress.label.shrink_to_fit(),
+    }
+}
+
+fn shrink_strings(strings: &mut Vec<String>) {
+    strings.shrink_to_fit();
+    for string in strings {
+        string.shrink_to_fit();
+    }
+}
+
+fn line_footprint(line: &Line) -> usize {
+    line.elements.capacity() * size_of::<Element>()
+        + line.elements.iter().map(element_heap).sum::<usize>()
+}
+
+fn element_heap(element: &Element) -> usize {
+    match element {
+        Element::Text(text) => text.text.capacity(),
+        Element::Link(link) => {
+            link.label.capacity() + link.url.capacity() + strings_heap(&link.fields)
+        }
+        Element::Field(field) => {
+            let value = match &field.kind {
+                FieldKind::Checkbox { value, .. } | FieldKind::Radio { value, .. } => {
+                    value.capacity()
+                }
+                _ => 0,
+            };
+            field.name.capacity() + field.default.capacity() + value
+        }
+        Element::Partial(partial) => partial.url.capacity() + strings_heap(&partial.fields),
+        Element::Progress(progress) => progress.label.capacity(),
+    }
+}
+
+fn strings_heap(strings: &[String]) -> usize {
+    size_of_val(strings) + strings.iter().map(String::capacity).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Field, LinkElement};
+
+    #[test]
+    fn shrink_reduces_footprint() {
+        let mut doc = Document::new();
+        doc.lines.reserve(64);
+        let mut label = String::with_capacity(256);
+        label.push_str("Home");
+        doc.push(Line::normal().text("hi").link(LinkElement {
+            label,
+            url: ":/page/index.mu".to_string(),
+            fields: Vec::with_capacity(8),
+            required: Vec::new(),
+            style: Default::default(),
+        }));
+
+        let before = doc.memory_footprint();
+        let copy = doc.clone();
+        doc.shrink_to_fit();
+        let after = doc.memory_footprint();
+        assert!(after < before);
+        assert_eq!(doc, copy);
+        assert_eq!(
+            after,
+            size_of::<Document>()
+                + size_of::<Line>()
+                + 2 * size_of::<Element>()
+                + "hi".len()
+                + "Home".len()
+                + ":/page/index.mu".len()
+        );
+    }
+
+    #[test]
+    fn checkbox_values_are_counted() {
+        let mut value = String::with_capacity(128);
+        value.push_str("yes");
+        let mut doc = Document::new();
+        doc.push(Line::normal().field(Field {
+            kind: FieldKind::Checkbox {
+                value,
+                checked: true,
+            },
+            ..Field::checkbox("ok", "yes")
+        }));
+
+        let before = doc.memory_footprint();
+        doc.shrink_to_fit();
+        let after = doc.memory_footprint();
+        assert!(before - after >= 128 - "yes".len());
+        assert_eq!(
+            after,
+            size_of::<Document>()
+                + size_of::<Line>()
+                + size_of::<Element>()
+                + "ok".len()
+                + "yes".len()
+                + "yes".len()
+        );
+    }
+}
@@ -1,28 +1,126 @@
+#[cfg(all(
+    target_arch = "wasm32",
+    any(feature = "ratatui-renderer", feature = "http", feature = "clipboard")
+))]
+compile_error!(
+    "the `ratatui-renderer`, `http` and `clipboard` features need a native terminal, socket stack or clipboard and don't build for wasm32"
+);
+
 mod ast;
 #[cfg(feature = "browser")]
+pub mod automation;
+#[cfg(feature = "browser")]
+mod bookmarks;
+#[cfg(feature = "browser")]
 mod browser;
 mod builder;
-mod parser;
+#[cfg(feature = "parser")]
+pub mod conformance;
+#[cfg(feature = "browser")]
+mod date;
+mod dom;
+mod expand;
+#[cfg(feature = "feed")]
+mod feed;
+#[cfg(feature = "browser")]
+mod file;
+mod form;
+#[cfg(feature = "gemtext")]
+mod gemtext;
+mod html;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "ratatui")]
+mod keys;
+mod linkify;
+#[cfg(feature = "browser")]
+mod loader;
+mod memory;
 #[cfg(feature = "ratatui")]
+mod panels;
+#[cfg(feature = "parser")]
+mod parser;
+#[cfg(feature = "ratatui-renderer")]
 mod ratatui;
+#[cfg(feature = "render")]
+mod render;
+#[cfg(feature = "rns")]
+mod rns;
+mod sanitize;
 mod serialize;
+#[cfg(feature = "server")]
+mod server;
+mod speech;
 #[cfg(feature = "browser")]
+mod tabs;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "render")]
 mod types;
+mod user_style;
+mod validate;
 #[cfg(feature = "ratatui")]
 mod widget;
 
 pub use ast::{
-    Alignment, Color, Document, Element, Field, FieldKind, Line, LineKind, LinkElement, Partial,
-    Style, StyledText,
+    Alignment, Color, Document, Element, Field, FieldKind, Line, LineKind, LinkElement, Metadata,
+    Partial, Progress, Style, StyledText,
 };
-pub use parser::parse;
+pub use dom::{DomLine, DomRun, RunTarget};
+#[cfg(feature = "parser")]
+pub use expand::expand_includes;
+pub use expand::{IncludeResolver, WidthClass};
+pub use form::{encode_form, percent_encode};
+#[cfg(feature = "parser")]
+pub use parser::{ParseOptions, UnterminatedLiteral, parse, parse_metadata, parse_with};
+pub use sanitize::{ColorPolicy, SanitizePolicy};
+pub use speech::FormValues;
+pub use user_style::UserStyle;
+pub use validate::{Issue, IssueKind};
+
+#[cfg(feature = "feed")]
+pub use feed::{Feed, FeedEntry, parse_feed};
+#[cfg(feature = "gemtext")]
+pub use gemtext::parse_gemtext;
 
 #[cfg(feature = "browser")]
-pub use browser::{Browser, Renderer};
+pub use bookmarks::{Bookmark, Bookmarks};
 #[cfg(feature = "browser")]
-pub use types::{Interaction, Link, PartialInfo, TextField};
+pub use browser::Browser;
+#[cfg(feature = "browser")]
+pub use file::{FILE_SCHEME, FileLoader, file_url};
+#[cfg(feature = "http")]
+pub use http::{HttpLoader, http_page};
+#[cfg(feature = "browser")]
+pub use loader::{PageLoader, block_on};
+#[cfg(feature = "render")]
+pub use render::{RenderOutput, Renderer};
+#[cfg(feature = "rns")]
+pub use rns::{
+    DestinationHash, NOMADNET_SCHEME, NomadUrl, RnsError, RnsLoader, RnsTransport,
+    parse_destination_hash,
+};
+#[cfg(feature = "server")]
+pub use server::{Handler, Request, Response, serve};
+#[cfg(feature = "browser")]
+pub use tabs::Tabs;
+#[cfg(feature = "browser")]
+pub use types::PendingLoad;
+#[cfg(feature = "render")]
+pub use types::{
+    DateSegment, FieldCursor, FormState, Heading, Hint, HistoryItem, HistoryLimits, HistoryRecord,
+    Hitbox, Interactable, Interaction, Link, PageEvent, PageLink, PageState, PartialInfo,
+    PartialLimits, PrefetchLimits, RadioDefault, SavedHistory, SelectedElement, TabFilter,
+    TextField, TextSelection, UncheckedCheckbox, ViewState, Viewport,
+};
 
+#[cfg(feature = "ratatui-renderer")]
+pub use self::ratatui::{
+    ColorMode, ColorVision, LayoutKey, LineKey, MicronText, PageAlignment, RatatuiRenderer, Theme,
+};
+#[cfg(feature = "ratatui")]
+pub use self::widget::{BrowserWidget, EditOverlay, OverlayButton};
+#[cfg(feature = "ratatui")]
+pub use keys::{Action, BrowserEvent, KeyMap};
 #[cfg(feature = "ratatui")]
-pub use self::ratatui::RatatuiRenderer;
-// #[cfg(feature = "ratatui")]
-// pub use self::widget::BrowserWidget;
+pub use panels::{BookmarksPanel, HistoryPicker, KeymapHelp, PanelEvent, PanelState};
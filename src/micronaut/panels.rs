@@ -0,0 +1,517 @@
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Alignment, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+};
+
+use std::time::SystemTime;
+
+use crate::micronaut::bookmarks::Bookmarks;
+use crate::micronaut::browser::Browser;
+use crate::micronaut::keys::{Action, KeyMap};
+use crate::micronaut::render::Renderer;
+use crate::micronaut::types::HistoryItem;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PanelEvent<T> {
+    Ignored,
+    Handled,
+    Chosen(T),
+}
+
+impl<T> PanelEvent<T> {
+    fn map<U>(self, f: impl FnOnce(T) -> Option<U>) -> PanelEvent<U> {
+        match self {
+            PanelEvent::Ignored => PanelEvent::Ignored,
+            PanelEvent::Handled => PanelEvent::Handled,
+            PanelEvent::Chosen(value) => match f(value) {
+                Some(value) => PanelEvent::Chosen(value),
+                None => PanelEvent::Handled,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PanelState {
+    list: ListState,
+    area: Rect,
+}
+
+impl PanelState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.list.selected()
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.list.select(index);
+    }
+
+    fn step(&mut self, len: usize, delta: isize) -> PanelEvent<usize> {
+        if len == 0 {
+            return PanelEvent::Ignored;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let next = current.saturating_add(delta).clamp(0, len as isize - 1);
+        self.list.select(Some(next as usize));
+        PanelEvent::Handled
+    }
+
+    fn handle_key(&mut self, len: usize, key: KeyEvent) -> PanelEvent<usize> {
+        if key.kind == KeyEventKind::Release {
+            return PanelEvent::Ignored;
+        }
+        let page = self.area.height.max(1) as isize;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.step(len, -1),
+            KeyCode::Down | KeyCode::Char('j') => self.step(len, 1),
+            KeyCode::PageUp => self.step(len, -page),
+            KeyCode::PageDown => self.step(len, page),
+            KeyCode::Home | KeyCode::Char('g') => self.step(len, isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.step(len, isize::MAX),
+            KeyCode::Enter => match self.list.selected() {
+                Some(index) if index < len => PanelEvent::Chosen(index),
+                _ => PanelEvent::Ignored,
+            },
+            _ => PanelEvent::Ignored,
+        }
+    }
+
+    fn handle_mouse(&mut self, len: usize, event: MouseEvent) -> PanelEvent<usize> {
+        if !self.area.contains(Position::new(event.column, event.row)) {
+            return PanelEvent::Ignored;
+        }
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index = self.list.offset() + (event.row - self.area.y) as usize;
+                if index >= len {
+                    return PanelEvent::Ignored;
+                }
+                self.list.select(Some(index));
+                PanelEvent::Chosen(index)
+            }
+            MouseEventKind::ScrollUp => self.step(len, -1),
+            MouseEventKind::ScrollDown => self.step(len, 1),
+            _ => PanelEvent::Ignored,
+        }
+    }
+
+    fn render(
+        &mut self,
+        block: Block,
+        items: Vec<ListItem>,
+        empty: &str,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        self.area = inner;
+        if items.is_empty() {
+            self.list.select(None);
+            Paragraph::new(Line::styled(empty, Style::default().fg(Color::DarkGray)))
+                .alignment(Alignment::Center)
+                .render(inner, buf);
+            return;
+        }
+        let selected = self.list.selected().unwrap_or(0).min(items.len() - 1);
+        self.list.select(Some(selected));
+        StatefulWidget::render(
+            List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            inner,
+            buf,
+            &mut self.list,
+        );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BookmarksPanel<'a> {
+    bookmarks: &'a Bookmarks,
+    block: Block<'a>,
+}
+
+impl<'a> BookmarksPanel<'a> {
+    pub fn new(bookmarks: &'a Bookmarks) -> Self {
+        Self {
+            bookmarks,
+            block: Block::default().title(" Bookmarks ").borders(Borders::ALL),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = block;
+        self
+    }
+
+    pub fn handle_key(&self, state: &mut PanelState, key: KeyEvent) -> PanelEvent<String> {
+        state
+            .handle_key(self.bookmarks.len(), key)
+            .map(|index| self.url(index))
+    }
+
+    pub fn handle_mouse(&self, state: &mut PanelState, event: MouseEvent) -> PanelEvent<String> {
+        state
+            .handle_mouse(self.bookmarks.len(), event)
+            .map(|index| self.url(index))
+    }
+
+    fn url(&self, index: usize) -> Option<String> {
+        self.bookmarks.list().get(index).map(|b| b.url.clone())
+    }
+}
+
+impl StatefulWidget for BookmarksPanel<'_> {
+    type State = PanelState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let items = self
+            .bookmarks
+            .list()
+            .iter()
+            .map(|bookmark| {
+                let mut spans = Vec::new();
+                if !bookmark.title.is_empty() {
+                    spans.push(Span::styled(
+                        bookmark.title.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(Span::styled(
+                    bookmark.url.clone(),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        state.render(self.block, items, "No bookmarks", area, buf);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryPicker<'a> {
+    items: Vec<HistoryItem>,
+    block: Block<'a>,
+    now: SystemTime,
+}
+
+impl<'a> HistoryPicker<'a> {
+    pub fn new<R: Renderer>(browser: &Browser<R>) -> Self {
+        Self {
+            items: browser.history(),
+            block: Block::default().title(" History ").borders(Borders::ALL),
+            now: SystemTime::now(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = block;
+        self
+    }
+
+    pub fn now(mut self, now: SystemTime) -> Self {
+        self.now = now;
+        self
+    }
+
+    pub fn handle_key(&self, state: &mut PanelState, key: KeyEvent) -> PanelEvent<isize> {
+        self.init(state);
+        state
+            .handle_key(self.items.len(), key)
+            .map(|index| self.offset(index))
+    }
+
+    pub fn handle_mouse(&self, state: &mut PanelState, event: MouseEvent) -> PanelEvent<isize> {
+        state
+            .handle_mouse(self.items.len(), event)
+            .map(|index| self.offset(index))
+    }
+
+    fn init(&self, state: &mut PanelState) {
+        if state.selected().is_none() {
+            let current = self.items.iter().position(|item| item.offset == 0);
+            state.select(current);
+        }
+    }
+
+    fn offset(&self, index: usize) -> Option<isize> {
+        let offset = self.items.get(index)?.offset;
+        (offset != 0).then_some(offset)
+    }
+}
+
+impl StatefulWidget for HistoryPicker<'_> {
+    type State = PanelState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        self.init(state);
+        let dim = Style::default().fg(Color::DarkGray);
+        let items = self
+            .items
+            .iter()
+            .map(|item| {
+                let marker = if item.offset == 0 { "> " } else { "  " };
+                let mut spans = vec![Span::raw(marker)];
+                if let Some(title) = &item.title {
+                    spans.push(Span::styled(
+                        title.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(Span::styled(item.url.clone(), dim));
+                if let Some(visited) = item.visited {
+                    spans.push(Span::styled(
+                        format!(" {}", relative_time(self.now, visited)),
+                        dim,
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        state.render(self.block, items, "No history", area, buf);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeymapHelp<'a> {
+    entries: Vec<(String, String)>,
+    block: Block<'a>,
+}
+
+impl<'a> KeymapHelp<'a> {
+    pub fn new(keymap: &KeyMap) -> Self {
+        let entries = Action::ALL
+            .iter()
+            .filter_map(|action| {
+                let keys = keymap.keys_for(*action);
+                (!keys.is_empty()).then(|| (keys.join(", "), action.description().to_string()))
+            })
+            .collect();
+        Self {
+            entries,
+            block: Block::default().title(" Keys ").borders(Borders::ALL),
+        }
+    }
+
+    pub fn entry(mut self, keys: impl Into<String>, description: impl Into<String>) -> Self {
+        self.entries.push((keys.into(), description.into()));
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = block;
+        self
+    }
+
+    pub fn popup_area(&self, area: Rect) -> Rect {
+        let keys_width = self.keys_width();
+        let text_width = self
+            .entries
+            .iter()
+            .map(|(_, description)| keys_width + 2 + description.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let width = (text_width + 4).min(area.width);
+        let height = (self.entries.len() as u16 + 2).min(area.height);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    fn keys_width(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Widget for KeymapHelp<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let popup = self.popup_area(area);
+        let keys_width = self.keys_width();
+        Clear.render(popup, buf);
+        let lines: Vec<Line> = self
+            .entries
+            .into_iter()
+            .map(|(keys, description)| {
+                Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:width$}", keys, width = keys_width),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("  "),
+                    Span::raw(description),
+                ])
+            })
+            .collect();
+        Paragraph::new(lines).block(self.block).render(popup, buf);
+    }
+}
+
+fn relative_time(now: SystemTime, then: SystemTime) -> String {
+    let secs = now.duration_since(then).unwrap_or_default().as_secs();
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn rows(buf: &Buffer) -> Vec<String> {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn click(column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn bookmarks_panel_lists_and_chooses() {
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.add(":/page/index.mu", "Home");
+        bookmarks.add(":/page/news.mu", "");
+        let mut state = PanelState::new();
+        let area = Rect::new(0, 0, 24, 4);
+        let mut buf = Buffer::empty(area);
+        BookmarksPanel::new(&bookmarks).render(area, &mut buf, &mut state);
+        assert_eq!(rows(&buf)[1], "│Home :/page/index.mu  │");
+        assert_eq!(rows(&buf)[2], "│:/page/news.mu        │");
+        assert!(buf[(1, 1)].modifier.contains(Modifier::REVERSED));
+
+        let panel = BookmarksPanel::new(&bookmarks);
+        assert_eq!(
+            panel.handle_key(&mut state, key(KeyCode::Down)),
+            PanelEvent::Handled
+        );
+        assert_eq!(
+            panel.handle_key(&mut state, key(KeyCode::Enter)),
+            PanelEvent::Chosen(":/page/news.mu".to_string())
+        );
+        assert_eq!(
+            panel.handle_mouse(&mut state, click(3, 1)),
+            PanelEvent::Chosen(":/page/index.mu".to_string())
+        );
+        assert_eq!(state.selected(), Some(0));
+        assert_eq!(
+            panel.handle_mouse(&mut state, click(3, 3)),
+            PanelEvent::Ignored
+        );
+    }
+
+    #[test]
+    fn empty_bookmarks_panel() {
+        let bookmarks = Bookmarks::new();
+        let mut state = PanelState::new();
+        let area = Rect::new(0, 0, 16, 3);
+        let mut buf = Buffer::empty(area);
+        BookmarksPanel::new(&bookmarks).render(area, &mut buf, &mut state);
+        assert_eq!(rows(&buf)[1], "│ No bookmarks │");
+        assert_eq!(
+            BookmarksPanel::new(&bookmarks).handle_key(&mut state, key(KeyCode::Enter)),
+            PanelEvent::Ignored
+        );
+    }
+
+    #[test]
+    fn history_picker_jumps_by_offset() {
+        let mut browser = Browser::new(crate::RatatuiRenderer::new());
+        browser.set_content("/a", ">Start");
+        browser.set_content("/b", "plain");
+        browser.set_content("/c", ">End");
+        browser.back();
+
+        let later = SystemTime::now() + std::time::Duration::from_secs(150);
+        let picker = HistoryPicker::new(&browser).now(later);
+        let mut state = PanelState::new();
+        let area = Rect::new(0, 0, 30, 5);
+        let mut buf = Buffer::empty(area);
+        picker.clone().render(area, &mut buf, &mut state);
+        assert_eq!(rows(&buf)[1], format!("│{:28}│", "  Start /a 2m ago"));
+        assert_eq!(rows(&buf)[2], format!("│{:28}│", "> /b 2m ago"));
+        assert_eq!(state.selected(), Some(1));
+
+        assert_eq!(
+            picker.handle_key(&mut state, key(KeyCode::Enter)),
+            PanelEvent::Handled
+        );
+        picker.handle_key(&mut state, key(KeyCode::Up));
+        let PanelEvent::Chosen(offset) = picker.handle_key(&mut state, key(KeyCode::Enter)) else {
+            panic!("Expected a chosen entry");
+        };
+        assert_eq!(offset, -1);
+        assert!(browser.go(offset));
+        assert_eq!(browser.url(), Some("/a"));
+        assert_eq!(
+            picker.handle_mouse(&mut state, click(2, 3)),
+            PanelEvent::Chosen(1)
+        );
+    }
+
+    #[test]
+    fn relative_times() {
+        let now = SystemTime::now();
+        let ago = |secs| relative_time(now, now - std::time::Duration::from_secs(secs));
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(600), "10m ago");
+        assert_eq!(ago(7200), "2h ago");
+        assert_eq!(ago(200_000), "2d ago");
+    }
+
+    #[test]
+    fn keymap_help_lists_bound_actions() {
+        let keymap = KeyMap::empty()
+            .bind(KeyCode::Char('j'), KeyModifiers::NONE, Action::Down)
+            .bind(KeyCode::Down, KeyModifiers::NONE, Action::Down)
+            .bind(KeyCode::Left, KeyModifiers::ALT, Action::Back);
+        let help = KeymapHelp::new(&keymap).entry("q", "Quit");
+        let area = Rect::new(0, 0, 30, 7);
+        assert_eq!(help.popup_area(area), Rect::new(3, 1, 23, 5));
+
+        let mut buf = Buffer::empty(area);
+        help.render(area, &mut buf);
+        let rows = rows(&buf);
+        assert_eq!(rows[1], "   ┌ Keys ───────────────┐    ");
+        assert_eq!(rows[2], format!("   │{:21}│    ", " Down, j   Move down"));
+        assert_eq!(rows[3], format!("   │{:21}│    ", " Alt+Left  Back"));
+        assert_eq!(rows[4], format!("   │{:21}│    ", " q         Quit"));
+    }
+}
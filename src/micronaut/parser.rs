@@ -19,6 +19,7 @@ struct ParseState {
     underline: bool,
     alignment: Alignment,
     first_text_alignment: Option<Alignment>,
+    tab_indices: bool,
 }
 
 impl ParseState {
@@ -42,26 +43,103 @@ impl ParseState {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Keep `` `= `` lines as `LiteralStart` / `LiteralEnd` lines instead of
+    /// dropping them.
+    pub literal_markers: bool,
+    pub unterminated_literal: UnterminatedLiteral,
+    /// Read a trailing `#N` on a field name as its tab index, e.g.
+    /// `` `<user#2`> ``. This is an extension other clients don't know, and
+    /// off by default since `#` is allowed in ordinary field names.
+    pub tab_indices: bool,
+}
+
+/// What to do with a `` `= `` that is never closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnterminatedLiteral {
+    /// The rest of the document is literal text, as in NomadNet.
+    #[default]
+    ToEnd,
+    /// The opening `` `= `` is dropped and what follows is parsed as markup.
+    Markup,
+}
+
 pub fn parse(input: &str) -> Document {
-    let mut state = ParseState::default();
-    let lines: Vec<Line> = input
-        .lines()
-        .filter_map(|line| parse_line(line, &mut state))
-        .collect();
-    Document { lines }
+    parse_with(input, &ParseOptions::default())
+}
+
+pub fn parse_with(input: &str, options: &ParseOptions) -> Document {
+    let mut state = ParseState {
+        tab_indices: options.tab_indices,
+        ..Default::default()
+    };
+    let mut metadata = Metadata::default();
+    let mut input_lines = input.lines().peekable();
+    while let Some((key, value)) = input_lines.peek().and_then(|line| metadata_entry(line)) {
+        metadata.set(key, value);
+        input_lines.next();
+    }
+    let input_lines: Vec<&str> = input_lines.collect();
+
+    let mut lines: Vec<Line> = Vec::new();
+    let mut open_literal: Option<(usize, usize, ParseState)> = None;
+    let mut i = 0;
+    while i < input_lines.len() {
+        if input_lines[i] == "`=" {
+            let kind = if state.literal_mode {
+                open_literal = None;
+                LineKind::LiteralEnd
+            } else {
+                open_literal = Some((i, lines.len(), state.clone()));
+                LineKind::LiteralStart
+            };
+            state.literal_mode = !state.literal_mode;
+            if options.literal_markers {
+                lines.push(Line {
+                    kind,
+                    indent_depth: state.depth,
+                    alignment: state.alignment,
+                    elements: vec![],
+                });
+            }
+        } else if let Some(line) = parse_line(input_lines[i], &mut state) {
+            lines.push(line);
+        }
+        i += 1;
+
+        if i == input_lines.len() && options.unterminated_literal == UnterminatedLiteral::Markup {
+            if let Some((at, len, saved)) = open_literal.take() {
+                lines.truncate(len);
+                state = saved;
+                i = at + 1;
+            }
+        }
+    }
+    Document { metadata, lines }
+}
+
+/// Reads just the leading `#!` header lines, without parsing the page.
+pub fn parse_metadata(input: &str) -> Metadata {
+    let mut metadata = Metadata::default();
+    for (key, value) in input.lines().map_while(metadata_entry) {
+        metadata.set(key, value);
+    }
+    metadata
+}
+
+fn metadata_entry(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.strip_prefix("#!")?.split_once('=')?;
+    let key = key.trim();
+    (!key.is_empty()).then_some((key, value.trim()))
 }
 
 fn parse_line(line: &str, state: &mut ParseState) -> Option<Line> {
     let mut line = line;
     let mut pre_escape = false;
 
-    if line == "`=" {
-        state.literal_mode = !state.literal_mode;
-        return None;
-    }
-
     if !state.literal_mode {
-        if line.starts_with('>') && line.contains("`<") {
+        if line.starts_with('>') && has_field(line) {
             line = line.trim_start_matches('>');
         }
 
@@ -71,6 +149,18 @@ fn parse_line(line: &str, state: &mut ParseState) -> Option<Line> {
         }
 
         if !pre_escape {
+            if let Some(rest) = line.strip_prefix("#@") {
+                return Some(Line {
+                    kind: LineKind::Directive,
+                    indent_depth: state.depth,
+                    alignment: state.alignment,
+                    elements: vec![Element::Text(StyledText {
+                        text: rest.trim().to_string(),
+                        style: Style::default(),
+                    })],
+                });
+            }
+
             if line.starts_with('#') {
                 return Some(Line {
                     kind: LineKind::Comment,
@@ -232,14 +322,22 @@ fn parse_elements_inner<'a>(input: &mut Stream<'a>, pre_escape: bool) -> ModalRe
                 if escape {
                     text_buf.push(ch);
                     escape = false;
-                } else {
+                } else if is_escapable(input.input[1..].chars().next()) {
                     escape = true;
+                } else {
+                    text_buf.push(ch);
                 }
                 let _ = take(1usize).parse_next(input)?;
                 continue;
             }
 
-            if ch == '`' {
+            // An unclosed `( is ordinary text, like the emoticon in "smile `(:".
+            let unclosed_progress = input
+                .input
+                .strip_prefix("`(")
+                .is_some_and(|rest| find_unescaped(rest, &[')']).is_none());
+
+            if ch == '`' && !unclosed_progress {
                 if escape {
                     text_buf.push(ch);
                     escape = false;
@@ -247,14 +345,35 @@ fn parse_elements_inner<'a>(input: &mut Stream<'a>, pre_escape: bool) -> ModalRe
                     continue;
                 }
 
+                let before = (input.state.current_style(), input.state.alignment);
+                let flushed = !text_buf.is_empty();
                 flush_text(&mut text_buf, &mut input.state, &mut elements);
 
-                if let Ok(elem) = parse_backtick_sequence(input) {
+                let start = input.input;
+                let parsed = parse_backtick_sequence(input);
+                if parsed.is_err() {
+                    // Malformed markup, like a field missing its `>`, is text.
+                    input.input = start;
+                    if flushed {
+                        if let Some(Element::Text(last)) = elements.pop() {
+                            text_buf = last.text;
+                        }
+                    }
+                }
+                if let Ok(elem) = parsed {
                     if let Some(e) = elem {
                         if input.state.first_text_alignment.is_none() {
                             input.state.first_text_alignment = Some(input.state.alignment);
                         }
                         elements.push(e);
+                    } else if flushed
+                        && before == (input.state.current_style(), input.state.alignment)
+                    {
+                        // A backtick that changed nothing, like `a`>b`, keeps
+                        // the text in one run.
+                        if let Some(Element::Text(last)) = elements.pop() {
+                            text_buf = last.text;
+                        }
                     }
                     continue;
                 }
@@ -309,10 +428,10 @@ fn parse_backtick_sequence<'a>(input: &mut Stream<'a>) -> ModalResult<Option<Ele
         }
         'F' => {
             let _ = take(1usize).parse_next(input)?;
-            if input.input.len() >= 3
-                && let Ok(color) = parse_color(input)
-            {
-                input.state.fg = Some(color);
+            if input.input.len() >= 3 {
+                if let Ok(color) = parse_color(input) {
+                    input.state.fg = Some(color);
+                }
             }
             Ok(None)
         }
@@ -323,10 +442,10 @@ fn parse_backtick_sequence<'a>(input: &mut Stream<'a>) -> ModalResult<Option<Ele
         }
         'B' => {
             let _ = take(1usize).parse_next(input)?;
-            if input.input.len() >= 3
-                && let Ok(color) = parse_color(input)
-            {
-                input.state.bg = Some(color);
+            if input.input.len() >= 3 {
+                if let Ok(color) = parse_color(input) {
+                    input.state.bg = Some(color);
+                }
             }
             Ok(None)
         }
@@ -379,6 +498,11 @@ fn parse_backtick_sequence<'a>(input: &mut Stream<'a>) -> ModalResult<Option<Ele
             let partial = parse_partial(input)?;
             Ok(Some(Element::Partial(partial)))
         }
+        '(' => {
+            let _ = take(1usize).parse_next(input)?;
+            let progress = parse_progress(input)?;
+            Ok(Some(Element::Progress(progress)))
+        }
         _ => Ok(None),
     }
 }
@@ -396,17 +520,84 @@ fn parse_color<'a>(input: &mut Stream<'a>) -> ModalResult<Color> {
         });
     }
 
-    let r = u8::from_str_radix(&hex[0..1], 16).unwrap_or(0) * 17;
-    let g = u8::from_str_radix(&hex[1..2], 16).unwrap_or(0) * 17;
-    let b = u8::from_str_radix(&hex[2..3], 16).unwrap_or(0) * 17;
-    Ok(Color { r, g, b })
+    let mut digits = hex.chars().map(|c| c.to_digit(16).unwrap_or(0) as u8 * 17);
+    let mut digit = || digits.next().unwrap_or(0);
+    Ok(Color {
+        r: digit(),
+        g: digit(),
+        b: digit(),
+    })
+}
+
+/// Consumes input up to the first of `ends` not preceded by a backslash,
+/// returning it still escaped.
+fn take_escaped<'a>(input: &mut Stream<'a>, ends: &[char]) -> &'a str {
+    let len = find_unescaped(input.input, ends).unwrap_or(input.input.len());
+    let (taken, rest) = input.input.split_at(len);
+    input.input = rest;
+    taken
+}
+
+/// A backslash only escapes markup delimiters and itself, so paths like
+/// `C:\dir` keep their backslashes.
+fn is_escapable(next: Option<char>) -> bool {
+    matches!(next, Some('`' | '|' | ']' | '>' | ')' | '}' | '\\'))
+}
+
+/// Whether `line` opens a field with an unescaped `` `< ``.
+fn has_field(line: &str) -> bool {
+    let mut rest = line;
+    while let Some(i) = find_unescaped(rest, &['`']) {
+        if rest[i..].starts_with("`<") {
+            return true;
+        }
+        rest = &rest[i + 1..];
+    }
+    false
+}
+
+fn find_unescaped(data: &str, delims: &[char]) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in data.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && is_escapable(data[i + 1..].chars().next()) {
+            escaped = true;
+        } else if delims.contains(&c) {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn split_escaped(data: &str, delim: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = data;
+    while let Some(i) = find_unescaped(rest, &[delim]) {
+        parts.push(&rest[..i]);
+        rest = &rest[i + delim.len_utf8()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+fn unescape(data: &str) -> String {
+    let mut out = String::with_capacity(data.len());
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if is_escapable(chars.peek().copied()) => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
 }
 
 fn parse_link<'a>(input: &mut Stream<'a>) -> ModalResult<LinkElement> {
-    let link_data: &str = take_while(0.., |c| c != ']').parse_next(input)?;
+    let link_data = take_escaped(input, &[']']);
     let _ = ']'.parse_next(input)?;
 
-    let components: Vec<&str> = link_data.split('`').collect();
+    let components = split_escaped(link_data, '`');
 
     let (label, url, fields) = match components.len() {
         1 => ("", components[0], ""),
@@ -415,20 +606,32 @@ fn parse_link<'a>(input: &mut Stream<'a>) -> ModalResult<LinkElement> {
         _ => ("", "", ""),
     };
 
-    let effective_label = if label.is_empty() {
-        url.to_string()
+    let effective_label = if label.is_empty() { url } else { label };
+
+    let mut required = Vec::new();
+    let fields = if fields.is_empty() {
+        vec![]
     } else {
-        label.to_string()
+        split_escaped(fields, '|')
+            .into_iter()
+            .map(|spec| match spec.strip_prefix('!') {
+                Some(name) if !name.is_empty() => {
+                    let name = unescape(name);
+                    if !required.contains(&name) {
+                        required.push(name.clone());
+                    }
+                    name
+                }
+                _ => unescape(spec),
+            })
+            .collect()
     };
 
     Ok(LinkElement {
-        label: effective_label,
-        url: url.to_string(),
-        fields: if fields.is_empty() {
-            vec![]
-        } else {
-            fields.split('|').map(String::from).collect()
-        },
+        label: unescape(effective_label),
+        url: unescape(url),
+        fields,
+        required,
         style: input.state.current_style(),
     })
 }
@@ -437,61 +640,159 @@ fn parse_field<'a>(input: &mut Stream<'a>) -> ModalResult<Field> {
     let masked = opt('!').parse_next(input)?.is_some();
     let is_checkbox = opt('?').parse_next(input)?.is_some();
     let is_radio = opt('^').parse_next(input)?.is_some();
+    let is_number = typed_field(input.input, '+', true);
+    let is_date = typed_field(input.input, '@', false);
+    if is_number || is_date {
+        let _ = take(1usize).parse_next(input)?;
+    }
+
+    if is_date {
+        let _ = '|'.parse_next(input)?;
+        let (name, tab_index) =
+            split_tab_index(take_escaped(input, &['`']), input.state.tab_indices);
+        let _ = '`'.parse_next(input)?;
+        let default = take_escaped(input, &['>']);
+        let _ = '>'.parse_next(input)?;
+        return Ok(Field {
+            name: unescape(name),
+            default: unescape(default),
+            width: None,
+            masked: false,
+            tab_index,
+            kind: FieldKind::Date,
+        });
+    }
 
     if is_checkbox || is_radio {
         let _ = opt('|').parse_next(input)?;
-        let name: &str = take_while(0.., |c| c != '|').parse_next(input)?;
+        let name = take_escaped(input, &['|']);
+        let (name, tab_index) = split_tab_index(name, input.state.tab_indices);
         let _ = '|'.parse_next(input)?;
-        let value: &str = take_while(0.., |c| c != '`' && c != '|').parse_next(input)?;
+        let value = take_escaped(input, &['`', '|']);
         let checked = opt(preceded('|', '*')).parse_next(input)?.is_some();
         let _ = '`'.parse_next(input)?;
-        let label: &str = take_while(0.., |c| c != '>').parse_next(input)?;
+        let label = take_escaped(input, &['>']);
         let _ = '>'.parse_next(input)?;
 
-        let effective_value = if value.is_empty() {
-            label.to_string()
-        } else {
-            value.to_string()
-        };
+        let radio_value = if value.is_empty() { label } else { value };
 
         return Ok(Field {
-            name: name.to_string(),
-            default: label.to_string(),
+            name: unescape(name),
+            default: unescape(label),
             width: None,
             masked: false,
+            tab_index,
             kind: if is_checkbox {
-                FieldKind::Checkbox { checked }
+                FieldKind::Checkbox {
+                    value: unescape(value),
+                    checked,
+                }
             } else {
                 FieldKind::Radio {
-                    value: effective_value,
+                    value: unescape(radio_value),
                     checked,
                 }
             },
         });
     }
 
-    let width_and_name: &str = take_while(0.., |c| c != '`').parse_next(input)?;
-    let (width, name) = if let Some((w, n)) = width_and_name.split_once('|') {
-        (w.parse().ok(), n)
-    } else {
-        (None, width_and_name)
+    if is_number {
+        let spec = split_escaped(take_escaped(input, &['`']), '|');
+        let _ = '`'.parse_next(input)?;
+        let default = take_escaped(input, &['>']);
+        let _ = '>'.parse_next(input)?;
+
+        let number = |idx: usize| spec.get(idx).and_then(|part| part.trim().parse().ok());
+        let (name, tab_index) = split_tab_index(
+            spec.get(1).copied().unwrap_or_default(),
+            input.state.tab_indices,
+        );
+        return Ok(Field {
+            name: unescape(name),
+            default: unescape(default),
+            width: number(0).and_then(|width: i64| u16::try_from(width).ok()),
+            masked: false,
+            tab_index,
+            kind: FieldKind::Number {
+                min: number(2),
+                max: number(3),
+                step: number(4).filter(|step| *step > 0).unwrap_or(1),
+            },
+        });
+    }
+
+    let width_and_name = take_escaped(input, &['`']);
+    let (width, name) = match find_unescaped(width_and_name, &['|']) {
+        Some(i) => (width_and_name[..i].parse().ok(), &width_and_name[i + 1..]),
+        None => (None, width_and_name),
     };
+    let (name, tab_index) = split_tab_index(name, input.state.tab_indices);
 
     let _ = '`'.parse_next(input)?;
-    let default: &str = take_while(0.., |c| c != '>').parse_next(input)?;
+    let default = take_escaped(input, &['>']);
     let _ = '>'.parse_next(input)?;
 
     Ok(Field {
-        name: name.to_string(),
-        default: default.to_string(),
+        name: unescape(name),
+        default: unescape(default),
         width,
         masked,
+        tab_index,
         kind: FieldKind::Text,
     })
 }
 
+/// Whether a field spec is `` `<+4|name`` style, a sigil, an optional width
+/// and a non-empty name. Anything else, like `` `<+name`x> ``, stays a text
+/// field with the sigil in its name, as other clients read it.
+fn typed_field(spec: &str, sigil: char, width: bool) -> bool {
+    let Some(rest) = spec.strip_prefix(sigil) else {
+        return false;
+    };
+    let rest = if width {
+        rest.trim_start_matches(|c: char| c.is_ascii_digit())
+    } else {
+        rest
+    };
+    rest.strip_prefix('|')
+        .is_some_and(|name| !name.is_empty() && !name.starts_with(['`', '|', '>']))
+}
+
+pub(crate) fn split_tab_index(name: &str, enabled: bool) -> (&str, Option<u16>) {
+    if !enabled {
+        return (name, None);
+    }
+    match name.rsplit_once('#') {
+        Some((base, index)) if !base.is_empty() => match index.parse() {
+            Ok(index) => (base, Some(index)),
+            Err(_) => (name, None),
+        },
+        _ => (name, None),
+    }
+}
+
+/// `` `(value`max`label) ``, with `max` defaulting to 100.
+fn parse_progress<'a>(input: &mut Stream<'a>) -> ModalResult<Progress> {
+    let parts = split_escaped(take_escaped(input, &[')']), '`');
+    let _ = ')'.parse_next(input)?;
+    let number = |idx: usize| {
+        parts
+            .get(idx)
+            .and_then(|part| part.trim().parse::<f64>().ok())
+            .filter(|n| n.is_finite())
+    };
+    Ok(Progress {
+        value: number(0).unwrap_or(0.0),
+        max: number(1).unwrap_or(100.0),
+        label: parts
+            .get(2)
+            .map(|label| unescape(label))
+            .unwrap_or_default(),
+    })
+}
+
 fn parse_partial<'a>(input: &mut Stream<'a>) -> ModalResult<Partial> {
-    let url: &str = take_while(0.., |c| c != '`' && c != '}').parse_next(input)?;
+    let url = take_escaped(input, &['`', '}']);
 
     let refresh = if opt('`').parse_next(input)?.is_some() {
         let r: &str = take_while(0.., |c| c != '`' && c != '}').parse_next(input)?;
@@ -501,8 +802,8 @@ fn parse_partial<'a>(input: &mut Stream<'a>) -> ModalResult<Partial> {
     };
 
     let fields = if opt('`').parse_next(input)?.is_some() {
-        let f: &str = take_while(0.., |c| c != '}').parse_next(input)?;
-        f.split('|').map(String::from).collect()
+        let f = take_escaped(input, &['}']);
+        split_escaped(f, '|').into_iter().map(unescape).collect()
     } else {
         vec![]
     };
@@ -510,7 +811,7 @@ fn parse_partial<'a>(input: &mut Stream<'a>) -> ModalResult<Partial> {
     let _ = '}'.parse_next(input)?;
 
     Ok(Partial {
-        url: url.to_string(),
+        url: unescape(url),
         refresh,
         fields,
     })
@@ -579,6 +880,16 @@ mod tests {
         assert!(doc.lines[0].elements.is_empty());
     }
 
+    #[test]
+    fn test_metadata_header() {
+        let doc = parse("#!title=Node News\n#!c = 300\n#!author=ada\n>News\n#!c=0");
+        assert_eq!(doc.metadata.title(), Some("Node News"));
+        assert_eq!(doc.metadata.author(), Some("ada"));
+        assert_eq!(doc.metadata.cache(), Some(300));
+        assert_eq!(doc.lines.len(), 2);
+        assert_eq!(doc.lines[1].kind, LineKind::Comment);
+    }
+
     #[test]
     fn test_divider_default() {
         let doc = parse("-");
@@ -729,6 +1040,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_required_link_fields_are_stripped() {
+        let doc = parse("`[Submit`/submit`!name|email|!|!!x]");
+        let Element::Link(l) = &doc.lines[0].elements[0] else {
+            panic!("Expected Link");
+        };
+        assert_eq!(l.fields, vec!["name", "email", "!", "!x"]);
+        assert_eq!(l.required, vec!["name", "!x"]);
+        assert_eq!(parse(&doc.to_string()), doc);
+    }
+
     #[test]
     fn test_link_inherits_style() {
         let doc = parse("`!`[Bold Link`/]");
@@ -776,7 +1098,13 @@ mod tests {
         if let Element::Field(f) = &doc.lines[0].elements[0] {
             assert_eq!(f.name, "remember");
             assert_eq!(f.default, "Keep me logged in");
-            assert_eq!(f.kind, FieldKind::Checkbox { checked: false });
+            assert_eq!(
+                f.kind,
+                FieldKind::Checkbox {
+                    value: "yes".to_string(),
+                    checked: false,
+                }
+            );
         }
     }
 
@@ -784,10 +1112,93 @@ mod tests {
     fn test_field_checkbox_checked() {
         let doc = parse("`<?|accept|yes|*`I accept>");
         if let Element::Field(f) = &doc.lines[0].elements[0] {
-            assert_eq!(f.kind, FieldKind::Checkbox { checked: true });
+            assert_eq!(
+                f.kind,
+                FieldKind::Checkbox {
+                    value: "yes".to_string(),
+                    checked: true,
+                }
+            );
         }
     }
 
+    fn parse_tab_indices(input: &str) -> Document {
+        parse_with(
+            input,
+            &ParseOptions {
+                tab_indices: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_field_number() {
+        let doc = parse_tab_indices("`<+4|volume#2|0|100|5`50> `<+|retries`3>");
+        let Element::Field(f) = &doc.lines[0].elements[0] else {
+            panic!("Expected Field");
+        };
+        assert_eq!(f.name, "volume");
+        assert_eq!(f.default, "50");
+        assert_eq!(f.width, Some(4));
+        assert_eq!(f.tab_index, Some(2));
+        assert_eq!(
+            f.kind,
+            FieldKind::Number {
+                min: Some(0),
+                max: Some(100),
+                step: 5,
+            }
+        );
+        let Element::Field(f) = &doc.lines[0].elements[2] else {
+            panic!("Expected Field");
+        };
+        assert_eq!(
+            f.kind,
+            FieldKind::Number {
+                min: None,
+                max: None,
+                step: 1,
+            }
+        );
+        assert_eq!(doc.to_string(), "`<+4|volume#2|0|100|5`50> `<+|retries`3>");
+
+        for source in ["`<+name`x>", "`<+|`x>", "`<+4`x>"] {
+            let doc = parse(source);
+            let Element::Field(f) = &doc.lines[0].elements[0] else {
+                panic!("Expected Field");
+            };
+            assert_eq!(f.kind, FieldKind::Text, "{}", source);
+        }
+        let doc = parse("`<+name`x>");
+        let Element::Field(f) = &doc.lines[0].elements[0] else {
+            panic!("Expected Field");
+        };
+        assert_eq!((f.name.as_str(), f.default.as_str()), ("+name", "x"));
+        assert_eq!(parse(&doc.to_string()).lines, doc.lines);
+    }
+
+    #[test]
+    fn test_field_date() {
+        let doc = parse_tab_indices("`<@|due#1`2024-05-01> `<@|start`>");
+        let Element::Field(f) = &doc.lines[0].elements[0] else {
+            panic!("Expected Field");
+        };
+        assert_eq!(f.name, "due");
+        assert_eq!(f.default, "2024-05-01");
+        assert_eq!(f.tab_index, Some(1));
+        assert_eq!(f.kind, FieldKind::Date);
+        assert_eq!(doc.to_string(), "`<@|due#1`2024-05-01> `<@|start`>");
+
+        let doc = parse("`<@home`x>");
+        let Element::Field(f) = &doc.lines[0].elements[0] else {
+            panic!("Expected Field");
+        };
+        assert_eq!(f.kind, FieldKind::Text);
+        assert_eq!((f.name.as_str(), f.default.as_str()), ("@home", "x"));
+        assert_eq!(parse(&doc.to_string()).lines, doc.lines);
+    }
+
     #[test]
     fn test_field_radio() {
         let doc = parse("`<^|color|red`Red>");
@@ -805,8 +1216,10 @@ mod tests {
     #[test]
     fn test_field_radio_checked() {
         let doc = parse("`<^|color|blue|*`Blue>");
-        if let Element::Field(f) = &doc.lines[0].elements[0]
-            && let FieldKind::Radio { checked, .. } = &f.kind
+        if let Element::Field(Field {
+            kind: FieldKind::Radio { checked, .. },
+            ..
+        }) = &doc.lines[0].elements[0]
         {
             assert!(checked);
         }
@@ -842,6 +1255,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_progress() {
+        let doc = parse("Disk `(42`200`Used \\) space) `(7)");
+        let Element::Progress(p) = &doc.lines[0].elements[1] else {
+            panic!("Expected Progress");
+        };
+        assert_eq!(
+            p,
+            &Progress {
+                value: 42.0,
+                max: 200.0,
+                label: "Used ) space".to_string(),
+            }
+        );
+        let Element::Progress(p) = &doc.lines[0].elements[3] else {
+            panic!("Expected Progress");
+        };
+        assert_eq!((p.value, p.max, p.label.as_str()), (7.0, 100.0, ""));
+        assert_eq!(doc.to_string(), "Disk `(42`200`Used \\) space) `(7`100)");
+    }
+
+    #[test]
+    fn test_unclosed_progress_is_text() {
+        for input in ["a `(50 no close", "smile `(:"] {
+            let doc = parse(input);
+            assert_eq!(doc.lines.len(), 1);
+            let Element::Text(text) = &doc.lines[0].elements[0] else {
+                panic!("expected text");
+            };
+            assert_eq!(text.text, input);
+            assert_eq!(parse(&doc.to_string()).lines, doc.lines);
+        }
+    }
+
     #[test]
     fn test_literal_mode() {
         let doc = parse("`=\n`!not bold`!\n`=");
@@ -1214,6 +1661,59 @@ This is `!NomadNet`!.
         assert_eq!(doc.lines[1].alignment, Alignment::Left);
     }
 
+    #[test]
+    fn test_backslash_before_ordinary_char_is_literal() {
+        let doc = parse(r"`[a`C:\dir\x]`<path`C:\tmp> C:\Users");
+        let Element::Link(link) = &doc.lines[0].elements[0] else {
+            panic!("expected link");
+        };
+        assert_eq!(link.url, r"C:\dir\x");
+        let Element::Field(field) = &doc.lines[0].elements[1] else {
+            panic!("expected field");
+        };
+        assert_eq!(field.default, r"C:\tmp");
+        let Element::Text(text) = &doc.lines[0].elements[2] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.text, r" C:\Users");
+        assert_eq!(parse(&doc.to_string()).lines, doc.lines);
+    }
+
+    #[test]
+    fn test_escaped_delimiters_in_data() {
+        let doc = parse(
+            r"`[a\]b`:/p.mu?q=x\`y`note=a\|b|x]`<12|msg`1 \> 0>`<^|op|\|\||*`or>`{:/f.mu`5`k=\}}",
+        );
+        let Element::Link(link) = &doc.lines[0].elements[0] else {
+            panic!("expected link");
+        };
+        assert_eq!(link.label, "a]b");
+        assert_eq!(link.url, ":/p.mu?q=x`y");
+        assert_eq!(link.fields, ["note=a|b", "x"]);
+        let Element::Field(text) = &doc.lines[0].elements[1] else {
+            panic!("expected field");
+        };
+        assert_eq!(
+            (text.name.as_str(), text.default.as_str()),
+            ("msg", "1 > 0")
+        );
+        assert_eq!(text.width, Some(12));
+        let Element::Field(radio) = &doc.lines[0].elements[2] else {
+            panic!("expected field");
+        };
+        assert_eq!(
+            radio.kind,
+            FieldKind::Radio {
+                value: "||".to_string(),
+                checked: true,
+            }
+        );
+        let Element::Partial(partial) = &doc.lines[0].elements[3] else {
+            panic!("expected partial");
+        };
+        assert_eq!(partial.fields, ["k=}"]);
+    }
+
     #[test]
     fn test_link_single_field() {
         let doc = parse("`[Go`/action`field1]");
@@ -1248,27 +1748,69 @@ This is `!NomadNet`!.
         }
     }
 
+    #[test]
+    fn test_field_tab_index() {
+        let source = "`<12|user#2`guest>`<?|opt#1|yes`Opt>`<tag#x`>";
+        let fields = |doc: &Document| {
+            doc.lines[0]
+                .elements
+                .iter()
+                .filter_map(|e| match e {
+                    Element::Field(f) => Some((f.name.clone(), f.tab_index)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            fields(&parse_tab_indices(source)),
+            [
+                ("user".to_string(), Some(2)),
+                ("opt".to_string(), Some(1)),
+                ("tag#x".to_string(), None)
+            ]
+        );
+        // Without the extension `#` is part of the name, as in NomadNet.
+        assert_eq!(
+            fields(&parse(source)),
+            [
+                ("user#2".to_string(), None),
+                ("opt#1".to_string(), None),
+                ("tag#x".to_string(), None)
+            ]
+        );
+    }
+
     #[test]
     fn test_checkbox_with_default_label() {
         let doc = parse("`<?|notify|yes`Send notifications>");
         if let Element::Field(f) = &doc.lines[0].elements[0] {
             assert_eq!(f.name, "notify");
             assert_eq!(f.default, "Send notifications");
-            assert_eq!(f.kind, FieldKind::Checkbox { checked: false });
+            assert_eq!(
+                f.kind,
+                FieldKind::Checkbox {
+                    value: "yes".to_string(),
+                    checked: false,
+                }
+            );
         }
     }
 
     #[test]
     fn test_radio_group() {
         let doc = parse("`<^|size|s`Small> `<^|size|m`Medium> `<^|size|l|*`Large>");
-        if let Element::Field(f) = &doc.lines[0].elements[0]
-            && let FieldKind::Radio { value, checked } = &f.kind
+        if let Element::Field(Field {
+            kind: FieldKind::Radio { value, checked },
+            ..
+        }) = &doc.lines[0].elements[0]
         {
             assert_eq!(value, "s");
             assert!(!checked);
         }
-        if let Element::Field(f) = &doc.lines[0].elements[4]
-            && let FieldKind::Radio { value, checked } = &f.kind
+        if let Element::Field(Field {
+            kind: FieldKind::Radio { value, checked },
+            ..
+        }) = &doc.lines[0].elements[4]
         {
             assert_eq!(value, "l");
             assert!(checked);
@@ -1348,6 +1890,55 @@ This is `!NomadNet`!.
         }
     }
 
+    #[test]
+    fn test_literal_markers() {
+        let options = ParseOptions {
+            literal_markers: true,
+            ..Default::default()
+        };
+        let doc = parse_with("before\n`=\n`!raw\n`=\nafter", &options);
+        let kinds: Vec<_> = doc.lines.iter().map(|line| line.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                LineKind::Normal,
+                LineKind::LiteralStart,
+                LineKind::Normal,
+                LineKind::LiteralEnd,
+                LineKind::Normal,
+            ]
+        );
+        assert_eq!(
+            doc.lines[2].elements,
+            vec![Element::Text(StyledText {
+                text: "`!raw".to_string(),
+                style: Style::default(),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_literal_policy() {
+        let input = "before\n`=\n`!bold`!";
+        let to_end = parse(input);
+        if let Element::Text(t) = &to_end.lines[1].elements[0] {
+            assert_eq!(t.text, "`!bold`!");
+        }
+
+        let options = ParseOptions {
+            unterminated_literal: UnterminatedLiteral::Markup,
+            ..Default::default()
+        };
+        let markup = parse_with(input, &options);
+        assert_eq!(markup.lines.len(), 2);
+        if let Element::Text(t) = &markup.lines[1].elements[0] {
+            assert_eq!(t.text, "bold");
+            assert!(t.style.bold);
+        } else {
+            panic!("expected text");
+        }
+    }
+
     #[test]
     fn test_escape_in_middle() {
         let doc = parse("hello\\`world");
@@ -1514,8 +2105,10 @@ This is `!NomadNet`!.
     #[test]
     fn test_radio_value_fallback_to_label() {
         let doc = parse("`<^|choice|`Option A>");
-        if let Element::Field(f) = &doc.lines[0].elements[0]
-            && let FieldKind::Radio { value, .. } = &f.kind
+        if let Element::Field(Field {
+            kind: FieldKind::Radio { value, .. },
+            ..
+        }) = &doc.lines[0].elements[0]
         {
             assert_eq!(value, "Option A");
         }
@@ -1524,8 +2117,10 @@ This is `!NomadNet`!.
     #[test]
     fn test_radio_explicit_value() {
         let doc = parse("`<^|choice|val`Option A>");
-        if let Element::Field(f) = &doc.lines[0].elements[0]
-            && let FieldKind::Radio { value, .. } = &f.kind
+        if let Element::Field(Field {
+            kind: FieldKind::Radio { value, .. },
+            ..
+        }) = &doc.lines[0].elements[0]
         {
             assert_eq!(value, "val");
         }
@@ -1,7 +1,11 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
 use ratatui::style::{Color as RatColor, Modifier, Style as RatStyle};
 use ratatui::text::{Line as RatLine, Span, Text};
-use ratatui::widgets::Paragraph;
+use ratatui::widgets::{Paragraph, Widget};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Mutex;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
@@ -31,12 +35,18 @@ fn is_emoji_char(c: char) -> bool {
         0x1F300..=0x1F9FF | 0x2600..=0x26FF | 0x2700..=0x27BF | 0x1FA00..=0x1FAFF
     )
 }
-use crate::micronaut::browser::{RenderOutput, Renderer};
 use crate::micronaut::parser::parse;
-use crate::micronaut::types::{FormState, Hitbox, Interactable};
+use crate::micronaut::render::{RenderOutput, Renderer};
+use crate::micronaut::types::{
+    FieldCursor, FormState, Hitbox, Interactable, PageState, TextSelection, ViewState,
+};
 
 const SECTION_INDENT: u16 = 2;
 const DEFAULT_FIELD_WIDTH: u16 = 24;
+const DEFAULT_NUMBER_WIDTH: u16 = 6;
+const DATE_WIDTH: u16 = 10;
+const MIN_PROGRESS_WIDTH: usize = 4;
+const LOADING_BAR_WIDTH: usize = 20;
 
 fn compute_partial_id(partial: &Partial) -> String {
     use std::collections::hash_map::DefaultHasher;
@@ -52,10 +62,7 @@ fn compute_partial_id(partial: &Partial) -> String {
 fn render_partial_content(
     doc: &Document,
     start_row: usize,
-    width: u16,
-    form_state: &FormState,
-    partial_contents: &HashMap<String, String>,
-    selected_interactable: Option<usize>,
+    ctx: &RenderContext,
     interactable_idx: &mut usize,
 ) -> (Vec<RatLine<'static>>, Vec<Hitbox>) {
     let mut lines: Vec<RatLine> = Vec::new();
@@ -63,15 +70,7 @@ fn render_partial_content(
 
     for line in &doc.lines {
         let row = start_row + lines.len();
-        let (rendered, mut hits) = render_line_with_hitboxes(
-            line,
-            row,
-            width,
-            form_state,
-            partial_contents,
-            selected_interactable,
-            interactable_idx,
-        );
+        let (rendered, mut hits) = render_line_with_hitboxes(line, row, ctx, interactable_idx);
         lines.extend(rendered);
         hitboxes.append(&mut hits);
     }
@@ -79,8 +78,331 @@ fn render_partial_content(
     (lines, hitboxes)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Theme {
+    #[default]
+    Default,
+    HighContrast,
+}
+
+impl Theme {
+    fn text(&self, style: &Style) -> RatStyle {
+        match self {
+            Theme::Default => convert_style(style),
+            Theme::HighContrast => RatStyle::default().add_modifier(style_modifiers(style)),
+        }
+    }
+
+    fn heading(&self, level: u8) -> RatStyle {
+        match self {
+            Theme::Default => {
+                let hs = heading_style(level);
+                RatStyle::default().fg(hs.fg).bg(hs.bg)
+            }
+            Theme::HighContrast => {
+                RatStyle::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+            }
+        }
+    }
+
+    fn link(&self, style: &Style) -> RatStyle {
+        match self {
+            Theme::Default => convert_style(style).add_modifier(Modifier::UNDERLINED),
+            Theme::HighContrast => self
+                .text(style)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        }
+    }
+
+    fn field(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default().fg(RatColor::Black).bg(RatColor::White),
+            Theme::HighContrast => RatStyle::default().add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    fn placeholder(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default().fg(RatColor::DarkGray),
+            Theme::HighContrast => RatStyle::default(),
+        }
+    }
+
+    fn hover(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default().fg(RatColor::Cyan),
+            Theme::HighContrast => RatStyle::default().add_modifier(Modifier::ITALIC),
+        }
+    }
+
+    fn progress(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default()
+                .fg(RatColor::Black)
+                .bg(RatColor::Rgb(0x99, 0xbb, 0xff)),
+            Theme::HighContrast => {
+                RatStyle::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+            }
+        }
+    }
+
+    fn hint(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default()
+                .fg(RatColor::Black)
+                .bg(RatColor::Yellow)
+                .add_modifier(Modifier::BOLD),
+            Theme::HighContrast => {
+                RatStyle::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+            }
+        }
+    }
+
+    fn progress_bar(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default().fg(RatColor::Green),
+            Theme::HighContrast => RatStyle::default(),
+        }
+    }
+
+    fn selection(&self) -> RatStyle {
+        match self {
+            Theme::Default => RatStyle::default()
+                .fg(RatColor::White)
+                .bg(RatColor::Rgb(0x33, 0x55, 0x99)),
+            Theme::HighContrast => RatStyle::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorMode {
+    #[default]
+    TrueColor,
+    Indexed,
+    Mono,
+}
+
+impl ColorMode {
+    fn map(&self, color: RatColor) -> RatColor {
+        match (self, color) {
+            (ColorMode::Indexed, RatColor::Rgb(r, g, b)) => RatColor::Indexed(xterm_index(r, g, b)),
+            (ColorMode::Mono, RatColor::Rgb(..) | RatColor::Indexed(_)) => RatColor::Reset,
+            _ => color,
+        }
+    }
+
+    fn apply(&self, lines: &mut [RatLine<'static>]) {
+        map_colors(lines, |color| self.map(color));
+    }
+}
+
+/// Daltonizes rgb colors so that hues a viewer with the given deficiency
+/// can't tell apart are shifted into ones they can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorVision {
+    #[default]
+    Normal,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl ColorVision {
+    fn map(&self, color: RatColor) -> RatColor {
+        let RatColor::Rgb(r, g, b) = color else {
+            return color;
+        };
+        let (r, g, b) = (r as f32, g as f32, b as f32);
+        let l = 17.8824 * r + 43.5161 * g + 4.11935 * b;
+        let m = 3.45565 * r + 27.1554 * g + 3.86714 * b;
+        let s = 0.0299566 * r + 0.184309 * g + 1.46709 * b;
+        let (l, m, s) = match self {
+            ColorVision::Normal => return color,
+            ColorVision::Protanopia => (2.02344 * m - 2.52581 * s, m, s),
+            ColorVision::Deuteranopia => (l, 0.494207 * l + 1.24827 * s, s),
+            ColorVision::Tritanopia => (l, m, -0.395913 * l + 0.801109 * m),
+        };
+        let seen_r = 0.08094445 * l - 0.1305044 * m + 0.1167211 * s;
+        let seen_g = -0.01024853 * l + 0.05401933 * m - 0.1136147 * s;
+        let seen_b = -0.000365297 * l - 0.004121615 * m + 0.6935114 * s;
+        let lost_r = r - seen_r;
+        let channel = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+        RatColor::Rgb(
+            channel(r),
+            channel(g + 0.7 * lost_r + (g - seen_g)),
+            channel(b + 0.7 * lost_r + (b - seen_b)),
+        )
+    }
+
+    fn apply(&self, lines: &mut [RatLine<'static>]) {
+        map_colors(lines, |color| self.map(color));
+    }
+}
+
+fn map_colors(lines: &mut [RatLine<'static>], map: impl Fn(RatColor) -> RatColor) {
+    let map_style = |style: &mut RatStyle| {
+        style.fg = style.fg.map(&map);
+        style.bg = style.bg.map(&map);
+    };
+    for line in lines {
+        map_style(&mut line.style);
+        for span in &mut line.spans {
+            map_style(&mut span.style);
+        }
+    }
+}
+
+fn xterm_index(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..8 => 16,
+            239.. => 231,
+            v => 232 + (v - 8) / 10,
+        };
+    }
+    let level = |v: u8| {
+        if v < 48 {
+            0
+        } else if v < 115 {
+            1
+        } else {
+            (v - 35) / 40
+        }
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PageAlignment {
+    #[default]
+    Center,
+    Left {
+        margin: u16,
+    },
+}
+
 #[derive(Debug, Clone, Default)]
-pub struct RatatuiRenderer;
+pub struct RatatuiRenderer {
+    pub theme: Theme,
+    pub max_width: Option<u16>,
+    pub page_alignment: PageAlignment,
+    pub no_wrap: bool,
+    pub color_mode: ColorMode,
+    pub color_vision: ColorVision,
+    layout_cache: LayoutCache,
+}
+
+impl RatatuiRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn max_width(mut self, max_width: u16) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn page_alignment(mut self, alignment: PageAlignment) -> Self {
+        self.page_alignment = alignment;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.no_wrap = !wrap;
+        self
+    }
+
+    pub fn color_mode(mut self, mode: ColorMode) -> Self {
+        self.color_mode = mode;
+        self
+    }
+
+    pub fn color_vision(mut self, vision: ColorVision) -> Self {
+        self.color_vision = vision;
+        self
+    }
+
+    pub fn set_color_vision(&mut self, vision: ColorVision) {
+        self.color_vision = vision;
+    }
+
+    /// The key the layout cache is checked against when rendering at `width`.
+    pub fn layout_key(&self, width: u16) -> LayoutKey {
+        LayoutKey {
+            width: self.page_layout(width).1,
+            wrap: !self.no_wrap,
+            theme: self.theme,
+        }
+    }
+
+    /// Per-line cache keys for a render at `width`; `None` for lines holding
+    /// a partial, which are always laid out again. Together with the line
+    /// itself and `layout_key` this decides whether a line's layout can be
+    /// reused.
+    pub fn line_keys(
+        &self,
+        doc: &Document,
+        width: u16,
+        form_state: &FormState,
+        partial_contents: &HashMap<String, String>,
+        view: &ViewState,
+    ) -> Vec<Option<LineKey>> {
+        let ctx = self.context(
+            self.page_layout(width).1,
+            form_state,
+            partial_contents,
+            view,
+        );
+        line_keys(doc, &ctx)
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect()
+    }
+
+    fn context<'a>(
+        &self,
+        width: u16,
+        form_state: &'a FormState,
+        partial_contents: &'a HashMap<String, String>,
+        view: &ViewState,
+    ) -> RenderContext<'a> {
+        RenderContext {
+            width,
+            wrap: !self.no_wrap,
+            theme: self.theme,
+            form_state,
+            partial_contents,
+            selected_interactable: view.selected_interactable,
+            hovered_interactable: view.hovered_interactable,
+        }
+    }
+
+    fn page_layout(&self, width: u16) -> (u16, u16) {
+        let Some(max_width) = self.max_width.filter(|&max| max > 0 && max < width) else {
+            return (0, width);
+        };
+        let spare = width - max_width;
+        let gutter = match self.page_alignment {
+            PageAlignment::Center => spare / 2,
+            PageAlignment::Left { margin } => margin.min(spare),
+        };
+        (gutter, max_width)
+    }
+}
 
 impl Renderer for RatatuiRenderer {
     type Output = Paragraph<'static>;
@@ -92,50 +414,118 @@ impl Renderer for RatatuiRenderer {
         scroll: u16,
         form_state: &FormState,
         partial_contents: &HashMap<String, String>,
-        selected_interactable: Option<usize>,
+        view: &ViewState,
     ) -> RenderOutput<Self::Output> {
-        render_document(
-            doc,
-            width,
-            scroll,
-            form_state,
-            partial_contents,
-            selected_interactable,
-        )
+        let (gutter, layout_width) = self.page_layout(width);
+        let ctx = self.context(layout_width, form_state, partial_contents, view);
+        let mut output = render_document(doc, &ctx, &self.layout_cache);
+        if gutter > 0 {
+            offset_page(&mut output, gutter as usize);
+        }
+        if let Some(editing) = &view.editing {
+            apply_caret(&mut output, editing, form_state);
+        }
+        if let Some(selection) = &view.selection {
+            apply_selection(&mut output.content, selection, self.theme.selection());
+        }
+        for hint in &view.hints {
+            let Some(hitbox) = output
+                .hitboxes
+                .iter()
+                .find(|hb| hb.interactable_idx == hint.interactable_idx)
+            else {
+                continue;
+            };
+            if let Some(line) = output.content.get_mut(hitbox.line) {
+                overlay_columns(line, hitbox.col_start, &hint.label, self.theme.hint());
+            }
+        }
+        if let PageState::Loading { url, progress } = &view.page_state {
+            let row = scroll as usize;
+            if output.content.len() <= row {
+                output.content.resize(row + 1, RatLine::default());
+            }
+            output.content[row] = RatLine::from(vec![
+                Span::raw(" ".repeat(view.scroll_x as usize)),
+                Span::styled(
+                    loading_status(url, *progress, width as usize),
+                    self.theme.progress(),
+                ),
+            ]);
+            // The status covers this row, so nothing under it stays clickable
+            output.hitboxes.retain(|hitbox| hitbox.line != row);
+        }
+        if self.color_vision != ColorVision::Normal {
+            self.color_vision.apply(&mut output.content);
+        }
+        if self.color_mode != ColorMode::TrueColor {
+            self.color_mode.apply(&mut output.content);
+        }
+        RenderOutput {
+            height: output.height,
+            width: output.width + gutter,
+            content: Paragraph::new(Text::from(output.content)).scroll((scroll, view.scroll_x)),
+            hitboxes: output.hitboxes,
+            line_rows: output.line_rows,
+        }
     }
 }
 
-fn render_document(
-    doc: &Document,
-    width: u16,
-    scroll: u16,
-    form_state: &FormState,
-    partial_contents: &HashMap<String, String>,
-    selected_interactable: Option<usize>,
-) -> RenderOutput<Paragraph<'static>> {
-    let mut lines: Vec<RatLine> = Vec::new();
-    let mut hitboxes: Vec<Hitbox> = Vec::new();
-    let mut interactable_idx = 0usize;
+#[derive(Debug, Clone)]
+pub struct MicronText<'a> {
+    pub document: &'a Document,
+    pub renderer: RatatuiRenderer,
+    pub scroll: u16,
+}
 
-    for line in &doc.lines {
-        let row = lines.len();
-        let (rendered, mut hits) = render_line_with_hitboxes(
-            line,
-            row,
+impl<'a> MicronText<'a> {
+    pub fn new(document: &'a Document) -> Self {
+        Self {
+            document,
+            renderer: RatatuiRenderer::new(),
+            scroll: 0,
+        }
+    }
+
+    pub fn renderer(mut self, renderer: RatatuiRenderer) -> Self {
+        self.renderer = renderer;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.renderer.theme = theme;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.renderer.no_wrap = !wrap;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: u16) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn height(&self, width: u16) -> u16 {
+        self.render_output(width).height
+    }
+
+    fn render_output(&self, width: u16) -> RenderOutput<Paragraph<'static>> {
+        self.renderer.render(
+            self.document,
             width,
-            form_state,
-            partial_contents,
-            selected_interactable,
-            &mut interactable_idx,
-        );
-        lines.extend(rendered);
-        hitboxes.append(&mut hits);
+            self.scroll,
+            &FormState::default(),
+            &HashMap::new(),
+            &ViewState::default(),
+        )
     }
+}
 
-    RenderOutput {
-        height: lines.len() as u16,
-        content: Paragraph::new(Text::from(lines)).scroll((scroll, 0)),
-        hitboxes,
+impl Widget for MicronText<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.render_output(area.width).content.render(area, buf);
     }
 }
 
@@ -161,6 +551,414 @@ fn heading_style(level: u8) -> HeadingStyle {
     }
 }
 
+struct RenderContext<'a> {
+    width: u16,
+    wrap: bool,
+    theme: Theme,
+    form_state: &'a FormState,
+    partial_contents: &'a HashMap<String, String>,
+    selected_interactable: Option<usize>,
+    hovered_interactable: Option<usize>,
+}
+
+impl RenderContext<'_> {
+    fn layout_key(&self) -> LayoutKey {
+        LayoutKey {
+            width: self.width,
+            wrap: self.wrap,
+            theme: self.theme,
+        }
+    }
+}
+
+fn render_document(
+    doc: &Document,
+    ctx: &RenderContext,
+    cache: &LayoutCache,
+) -> RenderOutput<Vec<RatLine<'static>>> {
+    let mut lines: Vec<RatLine> = Vec::new();
+    let mut hitboxes: Vec<Hitbox> = Vec::new();
+    let mut line_rows: Vec<usize> = Vec::with_capacity(doc.lines.len());
+
+    for (rendered, hits) in render_lines(doc, ctx, cache) {
+        let row = lines.len();
+        line_rows.push(row);
+        lines.extend(rendered);
+        hitboxes.extend(hits.into_iter().map(|mut hit| {
+            hit.line += row;
+            hit
+        }));
+    }
+
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+    RenderOutput {
+        height: lines.len() as u16,
+        width: width as u16,
+        content: lines,
+        hitboxes,
+        line_rows,
+    }
+}
+
+type RenderedLine = (Vec<RatLine<'static>>, Vec<Hitbox>);
+
+/// Per-line layouts from the previous render. A line is laid out again only
+/// when its content, its first interactable index, the selection or hover
+/// inside it, or the displayed value of one of its fields changed.
+#[derive(Default)]
+struct LayoutCache(Mutex<CachedLayout>);
+
+#[derive(Default)]
+struct CachedLayout {
+    key: LayoutKey,
+    lines: Vec<Option<CachedLine>>,
+}
+
+struct CachedLine {
+    line: Line,
+    key: LineKey,
+    rendered: RenderedLine,
+}
+
+/// Settings that change the layout of every line; a render with a different
+/// key can't reuse any cached line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutKey {
+    pub width: u16,
+    pub wrap: bool,
+    pub theme: Theme,
+}
+
+/// What besides its content decides how a single line is laid out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineKey {
+    pub first_idx: usize,
+    pub selected: Option<usize>,
+    pub hovered: Option<usize>,
+    /// The displayed value of each field in the line.
+    pub fields: Vec<String>,
+}
+
+impl Clone for LayoutCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for LayoutCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayoutCache").finish_non_exhaustive()
+    }
+}
+
+impl LineKey {
+    fn new(line: &Line, first_idx: usize, count: usize, ctx: &RenderContext) -> Option<Self> {
+        let in_line =
+            |idx: Option<usize>| idx.filter(|idx| (first_idx..first_idx + count).contains(idx));
+        let mut fields = Vec::new();
+        for element in &line.elements {
+            match element {
+                Element::Partial(_) => return None,
+                Element::Field(field) => {
+                    fields.push(render_field(field, ctx, false).content.into_owned())
+                }
+                _ => {}
+            }
+        }
+        Some(Self {
+            first_idx,
+            selected: in_line(ctx.selected_interactable),
+            hovered: in_line(ctx.hovered_interactable),
+            fields,
+        })
+    }
+}
+
+/// Lays out each line at row 0; `render_document` shifts them into place.
+fn render_lines(doc: &Document, ctx: &RenderContext, cache: &LayoutCache) -> Vec<RenderedLine> {
+    let mut cache = cache.0.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.key != ctx.layout_key() {
+        *cache = CachedLayout {
+            key: ctx.layout_key(),
+            lines: Vec::new(),
+        };
+    }
+
+    let keyed = line_keys(doc, ctx);
+    let cached = &cache.lines;
+    let layout = |(i, (line, (first_idx, key))): (usize, (&Line, &(usize, Option<LineKey>)))| {
+        let hit = cached
+            .get(i)
+            .and_then(Option::as_ref)
+            .filter(|cached| key.as_ref() == Some(&cached.key) && cached.line == *line);
+        match hit {
+            Some(cached) => cached.rendered.clone(),
+            None => render_line_with_hitboxes(line, 0, ctx, &mut first_idx.clone()),
+        }
+    };
+    #[cfg(feature = "rayon")]
+    let rendered: Vec<RenderedLine> = {
+        use rayon::prelude::*;
+        doc.lines
+            .par_iter()
+            .zip(&keyed)
+            .enumerate()
+            .map(layout)
+            .collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let rendered: Vec<RenderedLine> = doc
+        .lines
+        .iter()
+        .zip(&keyed)
+        .enumerate()
+        .map(layout)
+        .collect();
+
+    cache.lines = doc
+        .lines
+        .iter()
+        .zip(keyed)
+        .zip(&rendered)
+        .map(|((line, (_, key)), rendered)| {
+            Some(CachedLine {
+                line: line.clone(),
+                key: key?,
+                rendered: rendered.clone(),
+            })
+        })
+        .collect();
+    rendered
+}
+
+/// Each line's first interactable index and, unless it holds a partial, its
+/// cache key.
+fn line_keys(doc: &Document, ctx: &RenderContext) -> Vec<(usize, Option<LineKey>)> {
+    let mut next = 0usize;
+    doc.lines
+        .iter()
+        .map(|line| {
+            let first_idx = next;
+            let count = interactable_count(line, ctx);
+            next += count;
+            (first_idx, LineKey::new(line, first_idx, count, ctx))
+        })
+        .collect()
+}
+
+/// How far `render_line_with_hitboxes` advances the interactable index for
+/// `line`, so lines can be laid out independently.
+fn interactable_count(line: &Line, ctx: &RenderContext) -> usize {
+    let indent = line.indent_depth.saturating_sub(1) as u16 * SECTION_INDENT;
+    if line.kind != LineKind::Normal || ctx.width <= indent {
+        return 0;
+    }
+    match line_content(line, ctx) {
+        LineContent::Partial(doc) => doc
+            .lines
+            .iter()
+            .map(|line| interactable_count(line, ctx))
+            .sum(),
+        LineContent::Elements(elements) => elements
+            .iter()
+            .filter(|element| matches!(element, Element::Link(_) | Element::Field(_)))
+            .count(),
+    }
+}
+
+/// What a normal line lays out: its own elements, or the document of its
+/// first partial that has loaded.
+enum LineContent<'a> {
+    Elements(&'a [Element]),
+    Partial(Document),
+}
+
+fn line_content<'a>(line: &'a Line, ctx: &RenderContext) -> LineContent<'a> {
+    line.elements
+        .iter()
+        .find_map(|element| match element {
+            Element::Partial(partial) => ctx.partial_contents.get(&compute_partial_id(partial)),
+            _ => None,
+        })
+        .map_or(LineContent::Elements(&line.elements), |content| {
+            LineContent::Partial(parse(content))
+        })
+}
+
+fn offset_page(output: &mut RenderOutput<Vec<RatLine<'static>>>, gutter: usize) {
+    for line in &mut output.content {
+        if !line.spans.is_empty() {
+            line.spans.insert(0, Span::raw(" ".repeat(gutter)));
+        }
+    }
+    for hitbox in &mut output.hitboxes {
+        hitbox.col_start += gutter;
+        hitbox.col_end += gutter;
+    }
+}
+
+fn apply_selection(lines: &mut [RatLine<'static>], selection: &TextSelection, style: RatStyle) {
+    for (row, line) in lines.iter_mut().enumerate() {
+        if let Some(cols) = selection.columns(row) {
+            highlight_columns(line, cols, style);
+        }
+    }
+}
+
+fn loading_status(url: &str, progress: Option<f32>, width: usize) -> String {
+    let status = match progress {
+        Some(progress) => {
+            let filled = (progress * LOADING_BAR_WIDTH as f32).round() as usize;
+            format!(
+                " Loading {} [{}{}] {:>3}% ",
+                url,
+                "#".repeat(filled),
+                " ".repeat(LOADING_BAR_WIDTH - filled),
+                (progress * 100.0).round() as u32
+            )
+        }
+        None => format!(" Loading {}... ", url),
+    };
+
+    let mut line = String::new();
+    let mut col = 0;
+    for g in status.graphemes(true) {
+        let w = grapheme_width(g);
+        if col + w > width {
+            break;
+        }
+        line.push_str(g);
+        col += w;
+    }
+    line.push_str(&" ".repeat(width - col));
+    line
+}
+
+fn apply_caret(
+    output: &mut RenderOutput<Vec<RatLine<'static>>>,
+    editing: &FieldCursor,
+    form_state: &FormState,
+) {
+    let field_boxes: Vec<(usize, std::ops::Range<usize>, bool)> = output
+        .hitboxes
+        .iter()
+        .filter_map(|hb| match &hb.interactable {
+            Interactable::TextField { name, masked, .. } if *name == editing.name => Some((
+                hb.line,
+                hb.col_start..hb.col_end,
+                *masked && !form_state.revealed.contains(name),
+            )),
+            _ => None,
+        })
+        .collect();
+    let Some(&(_, _, masked)) = field_boxes.first() else {
+        return;
+    };
+
+    let value = form_state
+        .fields
+        .get(&editing.name)
+        .map(|s| s.as_str())
+        .unwrap_or("");
+    let before_cursor: String = value.chars().take(editing.cursor).collect();
+    let field_width: usize = field_boxes.iter().map(|(_, cols, _)| cols.len()).sum();
+    let mut offset = if masked {
+        before_cursor.chars().count()
+    } else {
+        display_width(&before_cursor)
+    };
+    offset = offset.min(field_width.saturating_sub(1));
+
+    let caret = RatStyle::default()
+        .add_modifier(Modifier::UNDERLINED)
+        .remove_modifier(Modifier::REVERSED);
+    for (row, cols, _) in field_boxes {
+        if offset < cols.len() {
+            if let Some(line) = output.content.get_mut(row) {
+                let col = cols.start + offset;
+                highlight_columns(line, col..col + 1, caret);
+            }
+            return;
+        }
+        offset -= cols.len();
+    }
+}
+
+/// Writes `text` over the columns starting at `start`.
+fn overlay_columns(line: &mut RatLine<'static>, start: usize, text: &str, style: RatStyle) {
+    let end = start + display_width(text);
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    let mut col = 0usize;
+    for span in line.spans.drain(..) {
+        let (mut head, mut tail) = (String::new(), String::new());
+        for g in span.content.graphemes(true) {
+            if col < start {
+                head.push_str(g);
+            } else if col >= end {
+                tail.push_str(g);
+            }
+            col += grapheme_width(g);
+        }
+        if !head.is_empty() {
+            before.push(Span::styled(head, span.style));
+        }
+        if !tail.is_empty() {
+            after.push(Span::styled(tail, span.style));
+        }
+    }
+    if col < start {
+        before.push(Span::raw(" ".repeat(start - col)));
+    }
+    before.push(Span::styled(text.to_string(), style));
+    before.extend(after);
+    line.spans = before;
+}
+
+fn highlight_columns(line: &mut RatLine<'static>, cols: std::ops::Range<usize>, style: RatStyle) {
+    let mut spans: Vec<Span<'static>> = Vec::with_capacity(line.spans.len());
+    let mut col = 0usize;
+
+    for span in line.spans.drain(..) {
+        let span_width = display_width(&span.content);
+        let span_end = col + span_width;
+        if span_end <= cols.start || col >= cols.end {
+            spans.push(span);
+            col = span_end;
+            continue;
+        }
+
+        let mut before = String::new();
+        let mut inside = String::new();
+        let mut after = String::new();
+        for g in span.content.graphemes(true) {
+            let target = if col < cols.start {
+                &mut before
+            } else if col < cols.end {
+                &mut inside
+            } else {
+                &mut after
+            };
+            target.push_str(g);
+            col += grapheme_width(g);
+        }
+
+        for (text, part_style) in [
+            (before, span.style),
+            (inside, span.style.patch(style)),
+            (after, span.style),
+        ] {
+            if !text.is_empty() {
+                spans.push(Span::styled(text, part_style));
+            }
+        }
+    }
+
+    line.spans = spans;
+}
+
 fn convert_color(color: Option<Color>) -> RatColor {
     match color {
         Some(c) => RatColor::Rgb(c.r, c.g, c.b),
@@ -169,10 +967,13 @@ fn convert_color(color: Option<Color>) -> RatColor {
 }
 
 fn convert_style(style: &Style) -> RatStyle {
-    let mut rat_style = RatStyle::default()
+    RatStyle::default()
         .fg(convert_color(style.fg))
-        .bg(convert_color(style.bg));
+        .bg(convert_color(style.bg))
+        .add_modifier(style_modifiers(style))
+}
 
+fn style_modifiers(style: &Style) -> Modifier {
     let mut modifiers = Modifier::empty();
     if style.bold {
         modifiers |= Modifier::BOLD;
@@ -183,32 +984,22 @@ fn convert_style(style: &Style) -> RatStyle {
     if style.underline {
         modifiers |= Modifier::UNDERLINED;
     }
-    rat_style = rat_style.add_modifier(modifiers);
-    rat_style
+    modifiers
 }
 
 fn render_line_with_hitboxes(
     line: &Line,
     row: usize,
-    width: u16,
-    form_state: &FormState,
-    partial_contents: &HashMap<String, String>,
-    selected_interactable: Option<usize>,
+    ctx: &RenderContext,
     interactable_idx: &mut usize,
 ) -> (Vec<RatLine<'static>>, Vec<Hitbox>) {
     match line.kind {
-        LineKind::Comment => (vec![], vec![]),
-        LineKind::Divider(ch) => (render_divider(ch, line.indent_depth, width), vec![]),
-        LineKind::Heading(level) => (render_heading(line, level, width), vec![]),
-        LineKind::Normal => render_normal_with_hitboxes(
-            line,
-            row,
-            width,
-            form_state,
-            partial_contents,
-            selected_interactable,
-            interactable_idx,
-        ),
+        LineKind::Comment | LineKind::Directive | LineKind::LiteralStart | LineKind::LiteralEnd => {
+            (vec![], vec![])
+        }
+        LineKind::Divider(ch) => (render_divider(ch, line.indent_depth, ctx.width), vec![]),
+        LineKind::Heading(level) => (render_heading(line, level, ctx), vec![]),
+        LineKind::Normal => render_normal_with_hitboxes(line, row, ctx, interactable_idx),
     }
 }
 
@@ -226,15 +1017,14 @@ fn render_divider(ch: char, depth: u8, width: u16) -> Vec<RatLine<'static>> {
     vec![RatLine::from(spans)]
 }
 
-fn render_heading(line: &Line, level: u8, width: u16) -> Vec<RatLine<'static>> {
+fn render_heading(line: &Line, level: u8, ctx: &RenderContext) -> Vec<RatLine<'static>> {
     let indent = line.indent_depth.saturating_sub(1) as u16 * SECTION_INDENT;
-    let content_width = width.saturating_sub(indent) as usize;
-    let hs = heading_style(level);
+    let content_width = ctx.width.saturating_sub(indent) as usize;
 
     let text_content = collect_text(&line.elements);
     let padded = pad_to_width(&text_content, content_width, line.alignment);
 
-    let style = RatStyle::default().fg(hs.fg).bg(hs.bg);
+    let style = ctx.theme.heading(level);
 
     let mut spans = Vec::new();
     if indent > 0 {
@@ -245,110 +1035,148 @@ fn render_heading(line: &Line, level: u8, width: u16) -> Vec<RatLine<'static>> {
     vec![RatLine::from(spans)]
 }
 
-struct WrappedSpan {
-    text: String,
+struct WrappedSpan<'a> {
+    text: Cow<'a, str>,
     style: RatStyle,
-    interactable: Option<(usize, Interactable)>,
+    interactable: Option<(usize, Interactable, Option<u16>)>,
 }
 
 fn render_normal_with_hitboxes(
     line: &Line,
     row: usize,
-    width: u16,
-    form_state: &FormState,
-    partial_contents: &HashMap<String, String>,
-    selected_interactable: Option<usize>,
+    ctx: &RenderContext,
     interactable_idx: &mut usize,
 ) -> (Vec<RatLine<'static>>, Vec<Hitbox>) {
     let indent = line.indent_depth.saturating_sub(1) as u16 * SECTION_INDENT;
-    let content_width = (width as usize).saturating_sub(indent as usize);
+    let content_width = (ctx.width as usize).saturating_sub(indent as usize);
 
     if content_width == 0 {
         return (vec![RatLine::from("")], vec![]);
     }
 
+    let elements = match line_content(line, ctx) {
+        LineContent::Partial(doc) => {
+            return render_partial_content(&doc, row, ctx, interactable_idx);
+        }
+        LineContent::Elements(elements) => elements,
+    };
     let mut wrapped_spans: Vec<WrappedSpan> = Vec::new();
+    let mut bars: Vec<(usize, &Progress)> = Vec::new();
 
-    for element in &line.elements {
+    for element in elements {
         match element {
             Element::Text(styled) => {
                 wrapped_spans.push(WrappedSpan {
-                    text: styled.text.clone(),
-                    style: convert_style(&styled.style),
+                    text: Cow::Borrowed(&styled.text),
+                    style: ctx.theme.text(&styled.style),
                     interactable: None,
                 });
             }
             Element::Link(link) => {
                 let idx = *interactable_idx;
-                let selected = selected_interactable == Some(idx);
+                let selected = ctx.selected_interactable == Some(idx);
                 *interactable_idx += 1;
-                let mut style = convert_style(&link.style);
-                style = style.add_modifier(Modifier::UNDERLINED);
+                let mut style = ctx.theme.link(&link.style);
                 if selected {
                     style = style.add_modifier(Modifier::REVERSED);
                 }
+                if ctx.hovered_interactable == Some(idx) {
+                    style = style.patch(ctx.theme.hover());
+                }
                 wrapped_spans.push(WrappedSpan {
-                    text: link.label.clone(),
+                    text: Cow::Borrowed(&link.label),
                     style,
                     interactable: Some((
                         idx,
                         Interactable::Link {
                             url: link.url.clone(),
                             fields: link.fields.clone(),
+                            required: link.required.clone(),
                         },
+                        None,
                     )),
                 });
             }
             Element::Field(field) => {
                 let idx = *interactable_idx;
-                let selected = selected_interactable == Some(idx);
+                let selected = ctx.selected_interactable == Some(idx);
                 *interactable_idx += 1;
-                let span = render_field(field, form_state, selected);
+                let mut span = render_field(field, ctx, selected);
+                if ctx.hovered_interactable == Some(idx) {
+                    span.style = span.style.patch(ctx.theme.hover());
+                }
                 let interactable = match &field.kind {
-                    FieldKind::Text => Interactable::TextField {
-                        name: field.name.clone(),
-                        masked: field.masked,
-                        default: field.default.clone(),
-                    },
-                    FieldKind::Checkbox { .. } => Interactable::Checkbox {
+                    FieldKind::Text | FieldKind::Number { .. } | FieldKind::Date => {
+                        Interactable::TextField {
+                            name: field.name.clone(),
+                            masked: field.masked,
+                            default: field.default.clone(),
+                        }
+                    }
+                    FieldKind::Checkbox { value, checked } => Interactable::Checkbox {
                         name: field.name.clone(),
+                        value: value.clone(),
+                        checked: *checked,
                     },
-                    FieldKind::Radio { value, .. } => Interactable::Radio {
+                    FieldKind::Radio { value, checked } => Interactable::Radio {
                         name: field.name.clone(),
                         value: value.clone(),
+                        checked: *checked,
                     },
                 };
                 wrapped_spans.push(WrappedSpan {
-                    text: span.content.to_string(),
+                    text: span.content,
                     style: span.style,
-                    interactable: Some((idx, interactable)),
+                    interactable: Some((idx, interactable, field.tab_index)),
                 });
             }
-            Element::Partial(partial) => {
-                let partial_id = compute_partial_id(partial);
-                if let Some(content) = partial_contents.get(&partial_id) {
-                    let partial_doc = parse(content);
-                    let (partial_lines, partial_hitboxes) = render_partial_content(
-                        &partial_doc,
-                        row,
-                        width,
-                        form_state,
-                        partial_contents,
-                        selected_interactable,
-                        interactable_idx,
-                    );
-                    return (partial_lines, partial_hitboxes);
-                } else {
+            Element::Progress(progress) => {
+                if !progress.label.is_empty() {
                     wrapped_spans.push(WrappedSpan {
-                        text: "\u{29D6}".to_string(),
-                        style: RatStyle::default().fg(RatColor::DarkGray),
+                        text: Cow::Owned(format!("{} ", progress.label)),
+                        style: ctx.theme.text(&Style::default()),
                         interactable: None,
                     });
                 }
+                bars.push((wrapped_spans.len(), progress));
+                for style in [ctx.theme.progress_bar(), ctx.theme.placeholder()] {
+                    wrapped_spans.push(WrappedSpan {
+                        text: Cow::Borrowed(""),
+                        style,
+                        interactable: None,
+                    });
+                }
+                wrapped_spans.push(WrappedSpan {
+                    text: Cow::Owned(format!(
+                        " {:>3}%",
+                        progress_percent(progress.value, progress.max)
+                    )),
+                    style: ctx.theme.text(&Style::default()),
+                    interactable: None,
+                });
+            }
+            Element::Partial(_) => {
+                wrapped_spans.push(WrappedSpan {
+                    text: Cow::Borrowed("\u{29D6}"),
+                    style: ctx.theme.placeholder(),
+                    interactable: None,
+                });
             }
         }
     }
 
+    if !bars.is_empty() {
+        // bars share whatever the rest of the line leaves free
+        let fixed: usize = wrapped_spans.iter().map(|ws| display_width(&ws.text)).sum();
+        let bar_width = (content_width.saturating_sub(fixed) / bars.len()).max(MIN_PROGRESS_WIDTH);
+        for (at, progress) in bars {
+            let percent = progress_percent(progress.value, progress.max) as usize;
+            let filled = (percent * bar_width + 50) / 100;
+            wrapped_spans[at].text = Cow::Owned("\u{2588}".repeat(filled));
+            wrapped_spans[at + 1].text = Cow::Owned("\u{2591}".repeat(bar_width - filled));
+        }
+    }
+
     let total_content_width: usize = wrapped_spans.iter().map(|ws| display_width(&ws.text)).sum();
     let left_pad = compute_left_pad(line.alignment, content_width, total_content_width);
 
@@ -364,13 +1192,26 @@ fn render_normal_with_hitboxes(
     }
 
     for ws in wrapped_spans {
-        let graphemes: Vec<&str> = ws.text.graphemes(true).collect();
-        let mut grapheme_idx = 0;
+        let mut rest: &str = &ws.text;
 
-        while grapheme_idx < graphemes.len() {
-            let remaining_width = content_width.saturating_sub(current_col);
+        while !rest.is_empty() {
+            let remaining_width = if ctx.wrap {
+                content_width.saturating_sub(current_col)
+            } else {
+                usize::MAX
+            };
+
+            let (mut len, mut chunk_width) = match remaining_width {
+                0 => (0, 0),
+                width => prefix_by_width(rest, width),
+            };
+            if len == 0 && current_col == 0 {
+                // a grapheme wider than the whole line still has to go somewhere
+                let g = rest.graphemes(true).next().unwrap_or(rest);
+                (len, chunk_width) = (g.len(), grapheme_width(g));
+            }
 
-            if remaining_width == 0 {
+            if len == 0 {
                 lines.push(RatLine::from(std::mem::take(&mut current_line_spans)));
                 current_row += 1;
                 current_col = 0;
@@ -380,22 +1221,21 @@ fn render_normal_with_hitboxes(
                 continue;
             }
 
-            let (chunk, chunk_width, graphemes_taken) =
-                take_graphemes_by_width(&graphemes[grapheme_idx..], remaining_width);
-
-            if let Some((idx, ref interactable)) = ws.interactable {
+            let (chunk, tail) = rest.split_at(len);
+            if let Some((idx, ref interactable, tab_index)) = ws.interactable {
                 hitboxes.push(Hitbox {
                     line: current_row,
                     col_start: current_col + line_start_pad,
                     col_end: current_col + line_start_pad + chunk_width,
                     interactable: interactable.clone(),
                     interactable_idx: idx,
+                    tab_index,
                 });
             }
 
-            current_line_spans.push(Span::styled(chunk, ws.style));
+            current_line_spans.push(Span::styled(chunk.to_string(), ws.style));
             current_col += chunk_width;
-            grapheme_idx += graphemes_taken;
+            rest = tail;
         }
     }
 
@@ -406,9 +1246,10 @@ fn render_normal_with_hitboxes(
     (lines, hitboxes)
 }
 
-fn render_field(field: &Field, form_state: &FormState, selected: bool) -> Span<'static> {
+fn render_field(field: &Field, ctx: &RenderContext, selected: bool) -> Span<'static> {
+    let form_state = ctx.form_state;
     let width = field.width.unwrap_or(DEFAULT_FIELD_WIDTH) as usize;
-    let mut style = RatStyle::default().fg(RatColor::Black).bg(RatColor::White);
+    let mut style = ctx.theme.field();
     if selected {
         style = style.add_modifier(Modifier::REVERSED);
     }
@@ -421,7 +1262,7 @@ fn render_field(field: &Field, form_state: &FormState, selected: bool) -> Span<'
                 .map(|s| s.as_str())
                 .unwrap_or(&field.default);
 
-            let display = if field.masked {
+            let display = if field.masked && !form_state.revealed.contains(&field.name) {
                 "*".repeat(value.len().min(width))
             } else {
                 let mut s = value.to_string();
@@ -432,7 +1273,20 @@ fn render_field(field: &Field, form_state: &FormState, selected: bool) -> Span<'
             let padded = format!("{:<width$}", display, width = width);
             Span::styled(padded, style)
         }
-        FieldKind::Checkbox { checked } => {
+        FieldKind::Number { .. } | FieldKind::Date => {
+            let width = match field.kind {
+                FieldKind::Date => DATE_WIDTH,
+                _ => field.width.unwrap_or(DEFAULT_NUMBER_WIDTH),
+            } as usize;
+            let value = form_state
+                .fields
+                .get(&field.name)
+                .map(|s| s.as_str())
+                .unwrap_or(&field.default);
+            let display: String = value.chars().take(width).collect();
+            Span::styled(format!("{:<width$}\u{2195}", display, width = width), style)
+        }
+        FieldKind::Checkbox { checked, .. } => {
             let is_checked = form_state
                 .checkboxes
                 .get(&field.name)
@@ -466,20 +1320,18 @@ fn collect_text(elements: &[Element]) -> String {
         .collect()
 }
 
-fn take_graphemes_by_width(graphemes: &[&str], max_width: usize) -> (String, usize, usize) {
-    let mut result = String::new();
+/// Byte length and display width of the longest grapheme prefix of `text`
+/// that fits in `max_width` columns.
+fn prefix_by_width(text: &str, max_width: usize) -> (usize, usize) {
     let mut width = 0;
-    let mut count = 0;
-    for &g in graphemes {
+    for (i, g) in text.grapheme_indices(true) {
         let g_width = grapheme_width(g);
         if width + g_width > max_width {
-            break;
+            return (i, width);
         }
-        result.push_str(g);
         width += g_width;
-        count += 1;
     }
-    (result, width, count)
+    (text.len(), width)
 }
 
 fn compute_left_pad(alignment: Alignment, available: usize, content: usize) -> usize {
@@ -513,6 +1365,487 @@ mod tests {
         HashMap::new()
     }
 
+    fn render_document(
+        doc: &Document,
+        width: u16,
+        scroll: u16,
+        form_state: &FormState,
+        partial_contents: &HashMap<String, String>,
+        selected_interactable: Option<usize>,
+    ) -> RenderOutput<Paragraph<'static>> {
+        let view = ViewState {
+            selected_interactable,
+            ..Default::default()
+        };
+        RatatuiRenderer::new().render(doc, width, scroll, form_state, partial_contents, &view)
+    }
+
+    fn render_to_buffer(output: RenderOutput<Paragraph<'static>>, width: u16) -> Buffer {
+        let area = Rect::new(0, 0, width, output.height.max(1));
+        let mut buf = Buffer::empty(area);
+        output.content.render(area, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn test_layout_cache_matches_fresh_render() {
+        let doc = parse(
+            ">Form\nName `<8|name`Ada> `[Go`/go`name]\nplain text that wraps around\n`<?|ok|1`Ok> `[Next`/next]",
+        );
+        let renderer = RatatuiRenderer::new();
+        let mut form = FormState::default();
+        let mut view = ViewState::default();
+        let steps: [fn(&mut FormState, &mut ViewState); 5] = [
+            |_, _| {},
+            |_, view| view.selected_interactable = Some(1),
+            |form, _| {
+                form.fields.insert("name".to_string(), "Grace".to_string());
+            },
+            |_, view| view.hovered_interactable = Some(3),
+            |form, _| {
+                form.checkboxes.insert("ok".to_string(), true);
+            },
+        ];
+        for width in [30, 12, 30] {
+            for step in steps {
+                step(&mut form, &mut view);
+                let cached = renderer.render(&doc, width, 0, &form, &no_partials(), &view);
+                let fresh =
+                    RatatuiRenderer::new().render(&doc, width, 0, &form, &no_partials(), &view);
+                assert_eq!(cached.hitboxes.len(), fresh.hitboxes.len());
+                assert_eq!(
+                    render_to_buffer(cached, width),
+                    render_to_buffer(fresh, width)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_keys_follow_form_and_selection() {
+        use std::collections::HashSet;
+
+        let doc = parse("`[A`/a]\n`<|name`Ada>\n`{/live}");
+        let renderer = RatatuiRenderer::new().max_width(40);
+        let mut form = FormState::default();
+        let mut view = ViewState::default();
+        let before = renderer.line_keys(&doc, 80, &form, &no_partials(), &view);
+        assert_eq!(before[1].as_ref().unwrap().fields[0].trim_end(), "Ada");
+        assert_eq!(before[1].as_ref().unwrap().first_idx, 1);
+        assert!(before[2].is_none());
+
+        form.fields.insert("name".to_string(), "Grace".to_string());
+        view.selected_interactable = Some(0);
+        let after = renderer.line_keys(&doc, 80, &form, &no_partials(), &view);
+        let keys: HashSet<_> = before.iter().chain(&after).flatten().collect();
+        assert_eq!(keys.len(), 4);
+
+        assert_eq!(renderer.layout_key(80).width, 40);
+        assert_eq!(renderer.layout_key(80), renderer.layout_key(100));
+        assert_ne!(renderer.layout_key(80), renderer.layout_key(20));
+    }
+
+    #[test]
+    fn test_interactable_indices_across_lines() {
+        let mut page = String::new();
+        for i in 0..100 {
+            page.push_str(&format!(
+                ">>S{}\nsome wrapping text `[L{}`/l{}] `<4|f{}`x>\n",
+                i, i, i, i
+            ));
+        }
+        page.push_str("`{/status}\ntail `[End`/end]");
+        let doc = parse(&page);
+        let Element::Partial(partial) = &doc.lines[200].elements[0] else {
+            panic!("expected partial");
+        };
+        let partials =
+            HashMap::from([(compute_partial_id(partial), "`[A`/a] `[B`/b]".to_string())]);
+        let output = render_document(&doc, 12, 0, &FormState::default(), &partials, Some(150));
+
+        let mut last = None;
+        for hitbox in &output.hitboxes {
+            let expected = last.map_or(0, |idx: usize| idx + 1);
+            assert!(hitbox.interactable_idx == expected || Some(hitbox.interactable_idx) == last);
+            last = Some(hitbox.interactable_idx);
+        }
+        assert_eq!(last, Some(202));
+        let selected = output
+            .hitboxes
+            .iter()
+            .find(|h| h.interactable_idx == 150)
+            .unwrap();
+        assert!((output.line_rows[151]..output.line_rows[152]).contains(&selected.line));
+        assert!(
+            output
+                .hitboxes
+                .iter()
+                .any(|h| h.interactable_idx == 201 && h.line == output.line_rows[200])
+        );
+    }
+
+    #[test]
+    fn test_high_contrast_ignores_page_colors() {
+        let doc = parse("`Ff00`B00fred on blue `[Link`/a]");
+        let renderer = RatatuiRenderer::new().theme(Theme::HighContrast);
+        let output = renderer.render(
+            &doc,
+            40,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &ViewState::default(),
+        );
+        let buf = render_to_buffer(output, 40);
+
+        let text_cell = &buf[(0, 0)];
+        assert_eq!(text_cell.fg, RatColor::Reset);
+        assert_eq!(text_cell.bg, RatColor::Reset);
+
+        let link_cell = &buf[(12, 0)];
+        assert_eq!(link_cell.symbol(), "L");
+        assert!(
+            link_cell
+                .modifier
+                .contains(Modifier::BOLD | Modifier::UNDERLINED)
+        );
+    }
+
+    #[test]
+    fn test_selection_spans_wrapped_lines() {
+        let doc = parse("abcdefghij klmnop");
+        let view = ViewState {
+            selection: Some(TextSelection::new((1, 3), (0, 8))),
+            ..Default::default()
+        };
+        let output = RatatuiRenderer::new().render(
+            &doc,
+            10,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &view,
+        );
+        assert_eq!(output.height, 2);
+        let buf = render_to_buffer(output, 10);
+        let selected_bg = Theme::Default.selection().bg.unwrap();
+
+        assert_ne!(buf[(7, 0)].bg, selected_bg);
+        assert_eq!(buf[(8, 0)].bg, selected_bg);
+        assert_eq!(buf[(9, 0)].bg, selected_bg);
+        assert_eq!(buf[(0, 1)].bg, selected_bg);
+        assert_eq!(buf[(2, 1)].bg, selected_bg);
+        assert_ne!(buf[(3, 1)].bg, selected_bg);
+        assert_eq!(buf[(3, 1)].symbol(), "m");
+    }
+
+    #[test]
+    fn test_caret_drawn_in_edited_field() {
+        let doc = parse("Name: `<8|name`>");
+        let mut form_state = FormState::default();
+        form_state
+            .fields
+            .insert("name".to_string(), "Ada".to_string());
+        let view = ViewState {
+            selected_interactable: Some(0),
+            editing: Some(FieldCursor {
+                name: "name".to_string(),
+                cursor: 1,
+                original: "Ada".to_string(),
+            }),
+            ..Default::default()
+        };
+        let output = RatatuiRenderer::new().render(&doc, 20, 0, &form_state, &no_partials(), &view);
+        let buf = render_to_buffer(output, 20);
+
+        assert!(buf[(6, 0)].modifier.contains(Modifier::REVERSED));
+        assert_eq!(buf[(7, 0)].symbol(), "d");
+        assert!(buf[(7, 0)].modifier.contains(Modifier::UNDERLINED));
+        assert!(!buf[(7, 0)].modifier.contains(Modifier::REVERSED));
+        assert!(buf[(8, 0)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_hovered_link_style() {
+        let doc = parse("`[One`/1] `[Two`/2]");
+        let view = ViewState {
+            hovered_interactable: Some(1),
+            ..Default::default()
+        };
+        let output = RatatuiRenderer::new().render(
+            &doc,
+            20,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &view,
+        );
+        let buf = render_to_buffer(output, 20);
+        assert_ne!(buf[(0, 0)].fg, RatColor::Cyan);
+        assert_eq!(buf[(4, 0)].fg, RatColor::Cyan);
+        assert!(buf[(4, 0)].modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn test_progress_bar_fills_line() {
+        let text = |width: u16, source: &str| -> String {
+            let doc = parse(source);
+            let output =
+                render_document(&doc, width, 0, &FormState::default(), &no_partials(), None);
+            let buf = render_to_buffer(output, width);
+            (0..width).map(|x| buf[(x, 0)].symbol()).collect()
+        };
+        assert_eq!(text(20, "Disk `(50`100)"), "Disk █████░░░░░  50%");
+        assert_eq!(text(20, "`(3`4`Sync)"), "Sync ████████░░  75%");
+        assert_eq!(text(12, "`(1`1)"), "███████ 100%");
+    }
+
+    #[test]
+    fn test_line_rows_follow_wrapping() {
+        let doc = parse("abcdefghij klmnop\n>Heading\nend");
+        let output = render_document(&doc, 10, 0, &FormState::default(), &no_partials(), None);
+        assert_eq!(output.line_rows, [0, 2, 3]);
+        assert_eq!(output.document_line_range(0), Some(0..2));
+        assert_eq!(output.document_line_range(2), Some(3..4));
+        assert_eq!(output.document_line_range(3), None);
+        assert_eq!(output.document_line_at_row(1), Some(0));
+        assert_eq!(output.document_line_at_row(4), None);
+
+        let doc = parse("a\n# hidden\nb");
+        let output = render_document(&doc, 10, 0, &FormState::default(), &no_partials(), None);
+        assert_eq!(output.document_line_range(1), Some(1..1));
+        assert_eq!(output.document_line_at_row(1), Some(2));
+    }
+
+    #[test]
+    #[cfg(feature = "browser")]
+    fn test_resize_keeps_scroll_anchor() {
+        let content: Vec<String> = (0..20)
+            .map(|i| format!(">H{}\nsome long text that wraps", i))
+            .collect();
+        let mut browser = crate::micronaut::Browser::new(RatatuiRenderer::new());
+        browser.resize(80, 5);
+        browser.set_content("/test", &content.join("\n"));
+        browser.scroll_to(browser.headings()[6].row as u16);
+
+        browser.resize(12, 5);
+        assert!(browser.headings()[6].row > 6);
+        assert_eq!(browser.scroll() as usize, browser.headings()[6].row);
+
+        browser.resize(80, 5);
+        assert_eq!(browser.scroll() as usize, browser.headings()[6].row);
+    }
+
+    #[test]
+    #[cfg(feature = "browser")]
+    fn test_color_modes() {
+        assert_eq!(xterm_index(0xff, 0x00, 0x00), 196);
+        assert_eq!(xterm_index(0x80, 0x80, 0x80), 244);
+        assert_eq!(xterm_index(0x00, 0x00, 0x00), 16);
+
+        let fg_at = |mode: ColorMode| {
+            let mut browser =
+                crate::micronaut::Browser::new(RatatuiRenderer::new().color_mode(mode));
+            browser.resize(10, 2);
+            browser.set_content("/", "`Ff00red");
+            let area = Rect::new(0, 0, 10, 1);
+            let mut buf = Buffer::empty(area);
+            browser.render().unwrap().clone().render(area, &mut buf);
+            buf[(0, 0)].fg
+        };
+        assert_eq!(fg_at(ColorMode::TrueColor), RatColor::Rgb(0xff, 0, 0));
+        assert_eq!(fg_at(ColorMode::Indexed), RatColor::Indexed(196));
+        assert_eq!(fg_at(ColorMode::Mono), RatColor::Reset);
+    }
+
+    #[test]
+    fn test_color_vision_separates_red_and_green() {
+        let red = RatColor::Rgb(0xff, 0, 0);
+        let green = RatColor::Rgb(0, 0x80, 0);
+        let gray = RatColor::Rgb(0x80, 0x80, 0x80);
+        assert_eq!(ColorVision::Normal.map(red), red);
+        assert_eq!(
+            ColorVision::Deuteranopia.map(RatColor::Cyan),
+            RatColor::Cyan
+        );
+        for vision in [
+            ColorVision::Protanopia,
+            ColorVision::Deuteranopia,
+            ColorVision::Tritanopia,
+        ] {
+            let RatColor::Rgb(r, g, b) = vision.map(gray) else {
+                panic!("expected rgb");
+            };
+            assert!(r.abs_diff(0x80) <= 2 && g.abs_diff(0x80) <= 2 && b.abs_diff(0x80) <= 2);
+        }
+        let RatColor::Rgb(_, _, blue) = ColorVision::Deuteranopia.map(red) else {
+            panic!("expected rgb");
+        };
+        assert!(blue > 0x80, "red gains blue to stand apart from green");
+        assert_ne!(ColorVision::Deuteranopia.map(green), green);
+
+        let doc = crate::parse("`Ff00red");
+        let renderer = RatatuiRenderer::new().color_vision(ColorVision::Deuteranopia);
+        let output = renderer.render(
+            &doc,
+            10,
+            0,
+            &FormState::default(),
+            &HashMap::new(),
+            &ViewState::default(),
+        );
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+        output.content.render(area, &mut buf);
+        assert_eq!(buf[(0, 0)].fg, ColorVision::Deuteranopia.map(red));
+    }
+
+    #[test]
+    #[cfg(feature = "browser")]
+    fn test_no_wrap_scrolls_horizontally() {
+        let mut browser = crate::micronaut::Browser::new(RatatuiRenderer::new().wrap(false));
+        browser.resize(10, 5);
+        browser.set_content("/wide", "abcdefghijklmnopqrst `[Link`/l]");
+        assert_eq!(browser.viewport().content_height, 1);
+
+        browser.scroll_horizontal_by(100);
+        assert_eq!(browser.scroll_x(), 15);
+        let visible = browser.visible_hitboxes();
+        assert_eq!((visible[0].col_start, visible[0].col_end), (6, 10));
+        assert!(matches!(
+            browser.click(7, 0),
+            Some(crate::micronaut::Interaction::Link(link)) if link.url == "/l"
+        ));
+
+        let output = browser.render().unwrap().clone();
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+        output.render(area, &mut buf);
+        let row: String = (0..10).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(row, "pqrst Link");
+
+        browser.scroll_horizontal_by(-100);
+        assert_eq!(browser.scroll_x(), 0);
+        browser.select_next();
+        assert_eq!(browser.scroll_x(), 15);
+    }
+
+    #[test]
+    fn test_loading_overlay_keeps_page() {
+        let doc = parse("first\nsecond\nthird");
+        let view = ViewState {
+            page_state: PageState::Loading {
+                url: "/next".to_string(),
+                progress: Some(0.5),
+            },
+            ..Default::default()
+        };
+        let output = RatatuiRenderer::new().render(
+            &doc,
+            50,
+            1,
+            &FormState::default(),
+            &no_partials(),
+            &view,
+        );
+        assert_eq!(output.height, 3);
+        let buf = render_to_buffer(output, 50);
+        let status: String = (0..50).map(|x| buf[(x, 0)].symbol().to_string()).collect();
+        assert_eq!(status, " Loading /next [##########          ]  50%        ");
+        assert_eq!(buf[(0, 1)].symbol(), "t");
+    }
+
+    #[test]
+    fn test_loading_overlay_hides_covered_links() {
+        let doc = parse("`[Old`/old]\n`[Kept`/kept]");
+        let view = ViewState {
+            page_state: PageState::Loading {
+                url: "/next".to_string(),
+                progress: None,
+            },
+            ..Default::default()
+        };
+        let output = RatatuiRenderer::new().render(
+            &doc,
+            50,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &view,
+        );
+        assert_eq!(output.hitboxes.len(), 1);
+        assert_eq!(output.hitboxes[0].line, 1);
+    }
+
+    #[test]
+    fn test_max_width_centers_page() {
+        let doc = parse("`[Link`/a] and some more text that wraps");
+        let renderer = RatatuiRenderer::new().max_width(20);
+        let output = renderer.render(
+            &doc,
+            60,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &ViewState::default(),
+        );
+
+        assert_eq!(output.height, 2, "text wraps at the capped width");
+        assert_eq!(output.hitboxes[0].col_start, 20);
+        assert_eq!(output.hitboxes[0].col_end, 24);
+
+        let buf = render_to_buffer(output, 60);
+        assert_eq!(buf[(19, 0)].symbol(), " ");
+        assert_eq!(buf[(20, 0)].symbol(), "L");
+    }
+
+    #[test]
+    fn test_max_width_left_margin() {
+        let doc = parse("`[Link`/a]");
+        let renderer = RatatuiRenderer::new()
+            .max_width(20)
+            .page_alignment(PageAlignment::Left { margin: 2 });
+        let output = renderer.render(
+            &doc,
+            60,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &ViewState::default(),
+        );
+        assert_eq!(output.hitboxes[0].col_start, 2);
+
+        let narrow = renderer.render(
+            &doc,
+            15,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &ViewState::default(),
+        );
+        assert_eq!(narrow.hitboxes[0].col_start, 0, "no gutter when narrower");
+    }
+
+    #[test]
+    fn test_high_contrast_headings_and_fields() {
+        let doc = parse(">Title\n`<|name`>");
+        let renderer = RatatuiRenderer::new().theme(Theme::HighContrast);
+        let output = renderer.render(
+            &doc,
+            30,
+            0,
+            &FormState::default(),
+            &no_partials(),
+            &ViewState::default(),
+        );
+        let buf = render_to_buffer(output, 30);
+
+        assert!(buf[(0, 0)].modifier.contains(Modifier::REVERSED));
+        assert_eq!(buf[(0, 0)].bg, RatColor::Reset);
+        assert!(buf[(0, 1)].modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(buf[(0, 1)].bg, RatColor::Reset);
+    }
+
     #[test]
     fn test_hitbox_positions_simple() {
         let doc = parse("Hello `[Link`http://x]");
@@ -553,6 +1886,18 @@ mod tests {
         assert_eq!(hb.col_end, 5, "Go is 2 chars wide");
     }
 
+    #[test]
+    fn test_wide_grapheme_at_line_end_wraps() {
+        let doc = parse("ab🦀`[🦀`http://x]");
+        let output = render_document(&doc, 3, 0, &FormState::default(), &no_partials(), None);
+        assert_eq!(output.height, 3);
+        let hb = &output.hitboxes[0];
+        assert_eq!((hb.line, hb.col_start, hb.col_end), (2, 0, 2));
+
+        let output = render_document(&doc, 1, 0, &FormState::default(), &no_partials(), None);
+        assert_eq!(output.height, 4);
+    }
+
     #[test]
     fn test_hitbox_link_starts_on_wrapped_line() {
         let doc = parse("0123456789`[Link`http://x]");
@@ -660,6 +2005,21 @@ mod tests {
         assert_eq!(output.hitboxes[1].col_end, 6);
     }
 
+    #[test]
+    fn test_revealed_masked_field() {
+        let doc = parse("`<!6|pin`1234>");
+        let mut form_state = FormState::default();
+        let row = |form_state: &FormState| {
+            let output = render_document(&doc, 20, 0, form_state, &no_partials(), None);
+            let buf = render_to_buffer(output, 20);
+            (0..4).map(|x| buf[(x, 0)].symbol()).collect::<String>()
+        };
+        assert_eq!(row(&form_state), "****");
+
+        form_state.revealed.insert("pin".to_string());
+        assert_eq!(row(&form_state), "1234");
+    }
+
     #[test]
     fn test_field_renders_with_visible_content() {
         let content = "`<20|username`Guest_ccbc>`[Submit`:/page/test.mu`username]";
@@ -747,4 +2107,35 @@ mod tests {
         let expected_pad = (80 - 4) / 2;
         assert_eq!(hb.col_start, expected_pad, "first link should be centered");
     }
+
+    #[test]
+    fn micron_text_wraps_and_scrolls() {
+        let doc = crate::parse("`!Bold`! words wrap here\nlast");
+        let text = MicronText::new(&doc);
+        assert_eq!(text.height(10), 3);
+
+        let area = Rect::new(0, 0, 10, 2);
+        let mut buf = Buffer::empty(area);
+        text.clone().scroll(1).render(area, &mut buf);
+        let rows: Vec<String> = (0..2)
+            .map(|y| (0..10).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert_eq!(rows, vec![" wrap here", "last      "]);
+
+        let mut buf = Buffer::empty(area);
+        text.wrap(false).render(area, &mut buf);
+        assert_eq!(buf[(0, 0)].symbol(), "B");
+        assert!(buf[(0, 0)].modifier.contains(Modifier::BOLD));
+        assert_eq!(buf[(0, 1)].symbol(), "l");
+    }
+
+    #[test]
+    fn number_default_truncates_by_char() {
+        let doc = crate::parse("`<+|qty`a\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}>");
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+        MicronText::new(&doc).render(area, &mut buf);
+        let row: String = (0..8).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(row, "a\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{2195} ");
+    }
 }
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::micronaut::ast::Document;
+use crate::micronaut::types::{FormState, Hitbox, ViewState};
+
+pub trait Renderer {
+    type Output;
+    fn render(
+        &self,
+        doc: &Document,
+        width: u16,
+        scroll: u16,
+        form_state: &FormState,
+        partial_contents: &HashMap<String, String>,
+        view: &ViewState,
+    ) -> RenderOutput<Self::Output>;
+}
+
+pub struct RenderOutput<T> {
+    pub content: T,
+    pub hitboxes: Vec<Hitbox>,
+    pub height: u16,
+    pub width: u16,
+    /// The first row of each line in `Document::lines`.
+    pub line_rows: Vec<usize>,
+}
+
+impl<T> RenderOutput<T> {
+    /// The rows `doc.lines[line]` was laid out on, empty for lines that draw
+    /// nothing, like comments. This indexes the parsed `Document`, not the
+    /// source text: `#!` metadata and `` `= `` markers have no line of their own.
+    pub fn document_line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range(&self.line_rows, self.height as usize, line)
+    }
+
+    /// The index into `Document::lines` of the line drawn on `row`.
+    pub fn document_line_at_row(&self, row: usize) -> Option<usize> {
+        line_at_row(&self.line_rows, self.height as usize, row)
+    }
+}
+
+pub(crate) fn line_range(line_rows: &[usize], height: usize, line: usize) -> Option<Range<usize>> {
+    let start = *line_rows.get(line)?;
+    let end = line_rows.get(line + 1).copied().unwrap_or(height);
+    Some(start..end.max(start))
+}
+
+pub(crate) fn line_at_row(line_rows: &[usize], height: usize, row: usize) -> Option<usize> {
+    if row >= height {
+        return None;
+    }
+    line_rows
+        .partition_point(|&start| start <= row)
+        .checked_sub(1)
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::future::Future;
+
+use crate::micronaut::loader::PageLoader;
+use crate::micronaut::types::Link;
+
+pub const NOMADNET_SCHEME: &str = "nomadnetwork://";
+
+pub type DestinationHash = [u8; 16];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NomadUrl {
+    pub destination: Option<DestinationHash>,
+    pub path: String,
+}
+
+impl NomadUrl {
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix(NOMADNET_SCHEME).unwrap_or(url);
+        let (hash, path) = rest.split_once(':')?;
+        if !path.starts_with('/') {
+            return None;
+        }
+        let destination = if hash.is_empty() {
+            None
+        } else {
+            Some(parse_destination_hash(hash)?)
+        };
+        Some(Self {
+            destination,
+            path: path.to_string(),
+        })
+    }
+}
+
+pub fn parse_destination_hash(hex: &str) -> Option<DestinationHash> {
+    if hex.len() != 32 || !hex.is_ascii() {
+        return None;
+    }
+    let mut hash = [0u8; 16];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// The Reticulum side of [`RnsLoader`], which micronaut doesn't implement:
+/// establish (or reuse) a link to `destination`, send a NomadNet page
+/// request for `path` carrying `form_data`, and return the response body.
+/// Bindings to an RNS stack, or a bridge to a local `rnsd`, go here.
+pub trait RnsTransport {
+    type Error: Display;
+
+    fn request(
+        &self,
+        destination: &DestinationHash,
+        path: &str,
+        form_data: &HashMap<String, String>,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>>;
+}
+
+#[derive(Debug)]
+pub enum RnsError<E> {
+    InvalidUrl(String),
+    NoDestination(String),
+    InvalidContent,
+    Transport(E),
+}
+
+impl<E: Display> Display for RnsError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RnsError::InvalidUrl(url) => write!(f, "invalid NomadNet url: {}", url),
+            RnsError::NoDestination(url) => write!(f, "no destination for {}", url),
+            RnsError::InvalidContent => f.write_str("page is not valid UTF-8"),
+            RnsError::Transport(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Resolves `nomadnetwork://<hash>:/page/...` urls (or `:/page/...` against
+/// the [`node`](Self::node) being browsed) and fetches them through an
+/// [`RnsTransport`].
+pub struct RnsLoader<T> {
+    transport: T,
+    node: Option<DestinationHash>,
+}
+
+impl<T: RnsTransport> RnsLoader<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            node: None,
+        }
+    }
+
+    pub fn node(mut self, destination: DestinationHash) -> Self {
+        self.node = Some(destination);
+        self
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: RnsTransport> PageLoader for RnsLoader<T> {
+    type Error = RnsError<T::Error>;
+
+    async fn load(&self, link: &Link) -> Result<String, Self::Error> {
+        let url =
+            NomadUrl::parse(&link.url).ok_or_else(|| RnsError::InvalidUrl(link.url.clone()))?;
+        let destination = url
+            .destination
+            .or(self.node)
+            .ok_or_else(|| RnsError::NoDestination(link.url.clone()))?;
+        let bytes = self
+            .transport
+            .request(&destination, &url.path, &link.form_data)
+            .await
+            .map_err(RnsError::Transport)?;
+        String::from_utf8(bytes).map_err(|_| RnsError::InvalidContent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::micronaut::loader::block_on;
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef";
+
+    struct EchoTransport;
+
+    impl RnsTransport for EchoTransport {
+        type Error = String;
+
+        async fn request(
+            &self,
+            destination: &DestinationHash,
+            path: &str,
+            form_data: &HashMap<String, String>,
+        ) -> Result<Vec<u8>, String> {
+            if path == "/page/missing.mu" {
+                return Err("timed out".to_string());
+            }
+            let name = form_data.get("field_name").map_or("", String::as_str);
+            Ok(format!("{:02x} {} {}", destination[0], path, name).into_bytes())
+        }
+    }
+
+    #[test]
+    fn parse_nomadnet_url() {
+        let url = NomadUrl::parse(&format!("nomadnetwork://{}:/page/index.mu", HASH)).unwrap();
+        assert_eq!(url.destination.unwrap()[..2], [0x01, 0x23]);
+        assert_eq!(url.path, "/page/index.mu");
+
+        let url = NomadUrl::parse(&format!("{}:/file/a.txt", HASH)).unwrap();
+        assert_eq!(url.path, "/file/a.txt");
+
+        let url = NomadUrl::parse(":/page/local.mu").unwrap();
+        assert_eq!(url.destination, None);
+
+        assert_eq!(NomadUrl::parse("nomadnetwork://abc:/page/index.mu"), None);
+        assert_eq!(NomadUrl::parse("https://example.com"), None);
+    }
+
+    #[test]
+    fn loader_resolves_destination_and_fields() {
+        let node = parse_destination_hash("ff00000000000000000000000000000f").unwrap();
+        let loader = RnsLoader::new(EchoTransport).node(node);
+
+        let mut link = Link::new(format!("nomadnetwork://{}:/page/index.mu", HASH));
+        link.form_data
+            .insert("field_name".to_string(), "ada".to_string());
+        assert_eq!(
+            block_on(loader.load(&link)).unwrap(),
+            "01 /page/index.mu ada"
+        );
+
+        let local = Link::new(":/page/local.mu");
+        assert_eq!(block_on(loader.load(&local)).unwrap(), "ff /page/local.mu ");
+
+        let err = block_on(RnsLoader::new(EchoTransport).load(&local)).unwrap_err();
+        assert!(matches!(err, RnsError::NoDestination(_)));
+
+        let err = block_on(loader.load(&Link::new(":/page/missing.mu"))).unwrap_err();
+        assert_eq!(err.to_string(), "timed out");
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{Color, Document, Element, LineKind, Metadata, Style, StyledText};
+
+/// What `Document::sanitize` strips from a page, e.g. an untrusted fragment
+/// about to be embedded in a host page. The default keeps everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SanitizePolicy {
+    pub foreground: ColorPolicy,
+    pub background: ColorPolicy,
+    /// Drop bold, italic and underline.
+    pub formatting: bool,
+    /// Turn links to other nodes, `http(s)://` and `lxmf@` into plain text.
+    pub external_links: bool,
+    /// Remove fields, and the field lists links would submit.
+    pub fields: bool,
+    pub partials: bool,
+    /// Drop `#@` directives, comments and `#!` metadata.
+    pub directives: bool,
+    /// Turn headings and dividers into plain lines, so a fragment can't
+    /// restructure the page around it.
+    pub structure: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorPolicy {
+    #[default]
+    Keep,
+    Strip,
+    /// Every colored run gets this color instead.
+    Replace(Color),
+}
+
+impl SanitizePolicy {
+    /// Plain text and links within the host node only.
+    pub fn strict() -> Self {
+        Self {
+            foreground: ColorPolicy::Strip,
+            background: ColorPolicy::Strip,
+            formatting: true,
+            external_links: true,
+            fields: true,
+            partials: true,
+            directives: true,
+            structure: true,
+        }
+    }
+
+    fn style(&self, style: Style) -> Style {
+        Style {
+            fg: self.foreground.apply(style.fg),
+            bg: self.background.apply(style.bg),
+            bold: style.bold && !self.formatting,
+            italic: style.italic && !self.formatting,
+            underline: style.underline && !self.formatting,
+        }
+    }
+}
+
+impl ColorPolicy {
+    fn apply(&self, color: Option<Color>) -> Option<Color> {
+        match self {
+            ColorPolicy::Keep => color,
+            ColorPolicy::Strip => None,
+            ColorPolicy::Replace(replacement) => color.map(|_| *replacement),
+        }
+    }
+}
+
+impl Document {
+    pub fn sanitize(&mut self, policy: &SanitizePolicy) {
+        if policy.directives {
+            self.metadata = Metadata::default();
+            self.lines
+                .retain(|line| !matches!(line.kind, LineKind::Directive | LineKind::Comment));
+        }
+        for line in &mut self.lines {
+            if policy.structure && matches!(line.kind, LineKind::Heading(_) | LineKind::Divider(_))
+            {
+                line.kind = LineKind::Normal;
+            }
+            line.elements.retain_mut(|element| match element {
+                Element::Text(text) => {
+                    text.style = policy.style(text.style);
+                    true
+                }
+                Element::Link(link) => {
+                    link.style = policy.style(link.style);
+                    if policy.fields {
+                        link.fields.clear();
+                        link.required.clear();
+                    }
+                    if policy.external_links && is_external(&link.url) {
+                        *element = Element::Text(StyledText {
+                            text: std::mem::take(&mut link.label),
+                            style: link.style,
+                        });
+                    }
+                    true
+                }
+                Element::Field(_) => !policy.fields,
+                Element::Partial(_) => !policy.partials,
+                Element::Progress(_) => true,
+            });
+        }
+    }
+}
+
+fn is_external(url: &str) -> bool {
+    url.contains("://") || url.starts_with("lxmf@") || url.find(":/").is_some_and(|i| i > 0)
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_policy_leaves_local_links_and_text() {
+        let mut doc = crate::parse(concat!(
+            "`F0f0`B00f`!`_Hi`_`! `[Home`:/page/index.mu`*|!name] `[Out`https://example.com]\n",
+            "`[Node`abcd1234:/page/index.mu] `<|name`x> `{/live`5}",
+        ));
+        doc.sanitize(&SanitizePolicy::strict());
+        assert_eq!(doc.to_string(), "Hi `[Home`:/page/index.mu] Out\nNode  ");
+        let mut links = doc.lines[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                Element::Link(link) => Some(link),
+                _ => None,
+            });
+        assert!(links.all(|link| link.required.is_empty()));
+    }
+
+    #[test]
+    fn colors_can_be_normalized() {
+        let gray = Color {
+            r: 0x99,
+            g: 0x99,
+            b: 0x99,
+        };
+        let mut doc = crate::parse("`Ff00red`f plain `B00fback");
+        doc.sanitize(&SanitizePolicy {
+            foreground: ColorPolicy::Replace(gray),
+            ..Default::default()
+        });
+        let styles: Vec<_> = doc.lines[0]
+            .elements
+            .iter()
+            .map(|element| match element {
+                Element::Text(text) => (text.style.fg, text.style.bg.is_some()),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(styles, [(Some(gray), false), (None, false), (None, true)]);
+    }
+
+    #[test]
+    fn directives_and_metadata_are_dropped() {
+        let mut doc = crate::parse("#!title=Phish\n#@include /steal.mu\n# note\nkept");
+        doc.sanitize(&SanitizePolicy {
+            directives: true,
+            ..Default::default()
+        });
+        assert_eq!(doc.metadata.get("title"), None);
+        assert_eq!(doc.to_string(), "kept");
+    }
+
+    #[test]
+    fn headings_become_plain_lines() {
+        let mut doc = crate::parse(">>Fake section");
+        doc.sanitize(&SanitizePolicy {
+            structure: true,
+            ..Default::default()
+        });
+        assert_eq!(doc.lines[0].kind, LineKind::Normal);
+        assert_eq!(doc.to_string(), "Fake section");
+    }
+
+    #[test]
+    fn dividers_become_blank_lines() {
+        let mut doc = crate::parse("above\n-=\nbelow");
+        doc.sanitize(&SanitizePolicy {
+            structure: true,
+            ..Default::default()
+        });
+        assert_eq!(doc.lines[1].kind, LineKind::Normal);
+        assert_eq!(doc.to_string(), "above\n\nbelow");
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt::{self, Write};
 
 use crate::{
     Alignment, Color, Document, Element, Field, FieldKind, Line, LineKind, LinkElement, Partial,
-    Style, StyledText,
+    Progress, Style, StyledText,
 };
 
 #[derive(Default)]
@@ -13,17 +13,46 @@ struct SerializeState {
     italic: bool,
     underline: bool,
     alignment: Alignment,
+    depth: u8,
+    literal: bool,
 }
 
 impl fmt::Display for Document {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut state = SerializeState::default();
+        for (key, value) in &self.metadata.entries {
+            writeln!(f, "#!{}={}", key, value)?;
+        }
         for (i, line) in self.lines.iter().enumerate() {
+            // Lines like dividers and empty lines can't start with an
+            // alignment change, they take the one in effect. Switch it at the
+            // end of the line before, where it no longer affects that line, or
+            // else on a line of its own, which the parser drops.
+            let switch = takes_alignment(line, &state);
+            let carried = switch && i > 0 && carries_alignment(&self.lines[i - 1]);
+            if carried {
+                write_alignment(line.alignment, f)?;
+            }
             if i > 0 {
                 f.write_char('\n')?;
             }
+            if switch && !carried {
+                write_alignment(line.alignment, f)?;
+                f.write_char('\n')?;
+            }
+            if switch {
+                state.alignment = line.alignment;
+            }
             serialize_line(line, &mut state, f)?;
         }
+        // `str::lines` drops a trailing empty line, so spell it out.
+        if self
+            .lines
+            .last()
+            .is_some_and(|line| line.kind == LineKind::Normal && line.elements.is_empty())
+        {
+            f.write_char('\n')?;
+        }
         Ok(())
     }
 }
@@ -33,12 +62,31 @@ fn serialize_line(
     state: &mut SerializeState,
     f: &mut fmt::Formatter<'_>,
 ) -> fmt::Result {
+    let mut reset_depth = false;
     match line.kind {
-        LineKind::Normal => {}
+        LineKind::Normal if state.literal => {
+            for element in &line.elements {
+                if let Element::Text(text) = element {
+                    match text.text.as_str() {
+                        "`=" => f.write_str("\\`=")?,
+                        text => f.write_str(text)?,
+                    }
+                }
+            }
+            return Ok(());
+        }
+        LineKind::Normal => {
+            if line.indent_depth == 0 && state.depth > 0 {
+                f.write_char('<')?;
+                state.depth = 0;
+                reset_depth = true;
+            }
+        }
         LineKind::Heading(level) => {
             for _ in 0..level {
                 f.write_char('>')?;
             }
+            state.depth = line.indent_depth;
         }
         LineKind::Divider(ch) => {
             f.write_char('-')?;
@@ -47,6 +95,17 @@ fn serialize_line(
             }
             return Ok(());
         }
+        LineKind::LiteralStart | LineKind::LiteralEnd => {
+            state.literal = line.kind == LineKind::LiteralStart;
+            return f.write_str("`=");
+        }
+        LineKind::Directive => {
+            f.write_str("#@")?;
+            if let Some(Element::Text(text)) = line.elements.first() {
+                f.write_str(&text.text)?;
+            }
+            return Ok(());
+        }
         LineKind::Comment => {
             f.write_char('#')?;
             if let Some(Element::Text(text)) = line.elements.first() {
@@ -58,11 +117,9 @@ fn serialize_line(
 
     if line.alignment != state.alignment {
         state.alignment = line.alignment;
-        match line.alignment {
-            Alignment::Left => f.write_str("`a")?,
-            Alignment::Center => f.write_str("`c")?,
-            Alignment::Right => f.write_str("`r")?,
-        }
+        write_alignment(line.alignment, f)?;
+    } else if !reset_depth && starts_with_marker(line, state) {
+        f.write_char('\\')?;
     }
 
     for element in &line.elements {
@@ -72,6 +129,50 @@ fn serialize_line(
     Ok(())
 }
 
+fn takes_alignment(line: &Line, state: &SerializeState) -> bool {
+    let from_state = match line.kind {
+        LineKind::Normal | LineKind::Heading(_) => line.elements.is_empty(),
+        _ => true,
+    };
+    from_state && !state.literal && line.alignment != state.alignment
+}
+
+fn carries_alignment(line: &Line) -> bool {
+    match line.kind {
+        LineKind::Normal => !line.elements.is_empty(),
+        LineKind::Heading(_) => true,
+        _ => false,
+    }
+}
+
+fn write_alignment(alignment: Alignment, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match alignment {
+        Alignment::Left => f.write_str("`a"),
+        Alignment::Center => f.write_str("`c"),
+        Alignment::Right => f.write_str("`r"),
+    }
+}
+
+/// Whether the line's text would otherwise be read back as a heading,
+/// divider, comment or depth reset, e.g. a paragraph starting with `#tag`.
+fn starts_with_marker(line: &Line, state: &SerializeState) -> bool {
+    let markers: &[char] = match line.kind {
+        LineKind::Heading(_) => &['>'],
+        _ => &['>', '-', '#', '<'],
+    };
+    match line.elements.first() {
+        Some(Element::Text(text)) => {
+            text.text.starts_with(markers)
+                && text.style.bold == state.bold
+                && text.style.italic == state.italic
+                && text.style.underline == state.underline
+                && text.style.fg == state.fg
+                && text.style.bg == state.bg
+        }
+        _ => false,
+    }
+}
+
 fn serialize_element(
     element: &Element,
     state: &mut SerializeState,
@@ -82,6 +183,7 @@ fn serialize_element(
         Element::Link(link) => serialize_link(link, state, f),
         Element::Field(field) => serialize_field(field, f),
         Element::Partial(partial) => serialize_partial(partial, f),
+        Element::Progress(progress) => serialize_progress(progress, f),
     }
 }
 
@@ -135,8 +237,8 @@ fn emit_style_changes(
 }
 
 fn write_color(color: Color, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    if color.r == color.g && color.g == color.b {
-        let pct = (color.r as u32 * 99 + 127) / 255;
+    let pct = (color.r as u32 * 99 + 127) / 255;
+    if color.r == color.g && color.g == color.b && pct * 255 / 99 == color.r as u32 {
         write!(f, "g{:02}", pct)
     } else {
         let r = (color.r as u32 + 8) / 17;
@@ -157,6 +259,18 @@ fn escape_text(text: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     Ok(())
 }
 
+/// Escapes the delimiters of link, field and partial data, e.g. `a|b` in a
+/// link field becomes `a\\|b`.
+fn escape_data(data: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for ch in data.chars() {
+        if matches!(ch, '\\' | '`' | '|' | ']' | '>' | '}') {
+            f.write_char('\\')?;
+        }
+        f.write_char(ch)?;
+    }
+    Ok(())
+}
+
 fn serialize_link(
     link: &LinkElement,
     state: &mut SerializeState,
@@ -166,17 +280,23 @@ fn serialize_link(
 
     f.write_str("`[")?;
     if link.label != link.url {
-        escape_text(&link.label, f)?;
+        escape_data(&link.label, f)?;
+        f.write_char('`')?;
+    } else if !link.fields.is_empty() {
+        // An empty label, so the url isn't read as the label.
         f.write_char('`')?;
     }
-    escape_text(&link.url, f)?;
+    escape_data(&link.url, f)?;
     if !link.fields.is_empty() {
         f.write_char('`')?;
         for (i, field) in link.fields.iter().enumerate() {
             if i > 0 {
                 f.write_char('|')?;
             }
-            f.write_str(field)?;
+            if link.required.contains(field) {
+                f.write_char('!')?;
+            }
+            escape_data(field, f)?;
         }
     }
     f.write_char(']')
@@ -193,42 +313,75 @@ fn serialize_field(field: &Field, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             if let Some(width) = field.width {
                 write!(f, "{}|", width)?;
             }
-            f.write_str(&field.name)?;
-            if !field.default.is_empty() {
-                f.write_char('`')?;
-                f.write_str(&field.default)?;
-            }
+            serialize_field_name(field, f)?;
+            f.write_char('`')?;
+            escape_data(&field.default, f)?;
         }
-        FieldKind::Checkbox { checked } => {
+        FieldKind::Checkbox { value, checked } => {
             f.write_str("?|")?;
-            f.write_str(&field.name)?;
+            serialize_field_name(field, f)?;
             f.write_char('|')?;
-            f.write_str(&field.default)?;
+            escape_data(value, f)?;
             if *checked {
                 f.write_str("|*")?;
             }
+            f.write_char('`')?;
+            escape_data(&field.default, f)?;
+        }
+        FieldKind::Number { min, max, step } => {
+            f.write_char('+')?;
+            if let Some(width) = field.width {
+                write!(f, "{}", width)?;
+            }
+            f.write_char('|')?;
+            serialize_field_name(field, f)?;
+            if min.is_some() || max.is_some() || *step != 1 {
+                let bound = |bound: &Option<i64>| bound.map(|n| n.to_string()).unwrap_or_default();
+                write!(f, "|{}|{}|{}", bound(min), bound(max), step)?;
+            }
+            f.write_char('`')?;
+            escape_data(&field.default, f)?;
+        }
+        FieldKind::Date => {
+            f.write_str("@|")?;
+            serialize_field_name(field, f)?;
+            f.write_char('`')?;
+            escape_data(&field.default, f)?;
         }
         FieldKind::Radio { value, checked } => {
             f.write_str("^|")?;
-            f.write_str(&field.name)?;
+            serialize_field_name(field, f)?;
             f.write_char('|')?;
-            f.write_str(value)?;
+            escape_data(value, f)?;
             if *checked {
                 f.write_str("|*")?;
             }
-            if !field.default.is_empty() {
-                f.write_char('`')?;
-                f.write_str(&field.default)?;
-            }
+            f.write_char('`')?;
+            escape_data(&field.default, f)?;
         }
     }
 
     f.write_char('>')
 }
 
+fn serialize_field_name(field: &Field, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if field.kind == FieldKind::Text
+        && field.width.is_none()
+        && field.name.starts_with(['!', '?', '^', '+', '@'])
+    {
+        // An empty width keeps the name from reading as a field type.
+        f.write_char('|')?;
+    }
+    escape_data(&field.name, f)?;
+    if let Some(index) = field.tab_index {
+        write!(f, "#{}", index)?;
+    }
+    Ok(())
+}
+
 fn serialize_partial(partial: &Partial, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     f.write_str("`{")?;
-    f.write_str(&partial.url)?;
+    escape_data(&partial.url, f)?;
     if partial.refresh.is_some() || !partial.fields.is_empty() {
         f.write_char('`')?;
         if let Some(refresh) = partial.refresh {
@@ -240,15 +393,52 @@ fn serialize_partial(partial: &Partial, f: &mut fmt::Formatter<'_>) -> fmt::Resu
                 if i > 0 {
                     f.write_char('|')?;
                 }
-                f.write_str(field)?;
+                escape_data(field, f)?;
             }
         }
     }
     f.write_char('}')
 }
 
-#[cfg(test)]
+fn serialize_progress(progress: &Progress, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "`({}`{}", progress.value, progress.max)?;
+    if !progress.label.is_empty() {
+        f.write_char('`')?;
+        for ch in progress.label.chars() {
+            if matches!(ch, '\\' | '`' | ')') {
+                f.write_char('\\')?;
+            }
+            f.write_char(ch)?;
+        }
+    }
+    f.write_char(')')
+}
+
+#[cfg(all(test, feature = "parser"))]
 mod tests {
+    use super::*;
+
+    /// The lines with neighbouring text of the same style joined: the
+    /// serializer writes such runs as one, though the parser can split them.
+    fn text_runs(lines: &[Line]) -> Vec<Line> {
+        let mut lines = lines.to_vec();
+        for line in &mut lines {
+            let mut elements: Vec<Element> = Vec::new();
+            for element in line.elements.drain(..) {
+                match (elements.last_mut(), element) {
+                    (Some(Element::Text(last)), Element::Text(text))
+                        if last.style == text.style =>
+                    {
+                        last.text.push_str(&text.text)
+                    }
+                    (_, element) => elements.push(element),
+                }
+            }
+            line.elements = elements;
+        }
+        lines
+    }
+
     #[test]
     fn roundtrip_simple() {
         let input = "Hello world";
@@ -277,6 +467,13 @@ mod tests {
         assert_eq!(doc.to_string(), input);
     }
 
+    #[test]
+    fn roundtrip_metadata() {
+        let input = "#!title=Home\n#!c=60\n>Heading 1";
+        let doc = crate::parse(input);
+        assert_eq!(doc.to_string(), input);
+    }
+
     #[test]
     fn roundtrip_escape() {
         let input = "backtick: \\` backslash: \\\\";
@@ -284,6 +481,59 @@ mod tests {
         assert_eq!(doc.to_string(), input);
     }
 
+    #[test]
+    fn roundtrip_field_tab_index() {
+        let input = "`<12|user#2`guest>`<^|opt#1|yes`Opt>";
+        let options = crate::ParseOptions {
+            tab_indices: true,
+            ..Default::default()
+        };
+        let doc = crate::parse_with(input, &options);
+        assert!(matches!(&doc.lines[0].elements[0], Element::Field(f) if f.tab_index == Some(2)));
+        assert_eq!(doc.to_string(), input);
+    }
+
+    #[test]
+    fn roundtrip_checkbox() {
+        let input = "`<?|news|weekly|*`Weekly news>`<?|terms|`Terms>";
+        let doc = crate::parse(input);
+        assert_eq!(doc.to_string(), input);
+    }
+
+    #[test]
+    fn roundtrip_keeps_meaning() {
+        for input in [
+            "\\>not a heading",
+            "\\-x",
+            "\\#tag",
+            "\\<x",
+            "a`>b",
+            ">\\>quoted",
+            "`!bold`! then \\`tick",
+            "a`!`!b",
+            "`ccentered`` left",
+            "`c\n-",
+            "text`r\n# comment",
+            "a\n\n",
+            ">Heading\n<top level",
+            "`<|?odd name`v>",
+            "`[`:/p.mu`a=1]",
+            "`Fbbbgray",
+            "`<broken field",
+            ">quoted \\`<b",
+        ] {
+            let doc = crate::parse(input);
+            let again = crate::parse(&doc.to_string());
+            assert_eq!(
+                text_runs(&again.lines),
+                text_runs(&doc.lines),
+                "{:?} -> {:?}",
+                input,
+                doc.to_string()
+            );
+        }
+    }
+
     #[test]
     fn roundtrip_divider() {
         let input = "-";
@@ -297,4 +547,39 @@ mod tests {
         let doc = crate::parse(input);
         assert_eq!(doc.to_string(), input);
     }
+
+    #[test]
+    fn roundtrip_literal_block() {
+        let input = "a\n`=\n`!raw `[x]\n\\`=\n`=\nb";
+        let options = crate::ParseOptions {
+            literal_markers: true,
+            ..Default::default()
+        };
+        let doc = crate::parse_with(input, &options);
+        assert_eq!(doc.to_string(), input);
+    }
+
+    #[test]
+    fn roundtrip_delimiters_in_data() {
+        let mut doc = Document::new();
+        doc.push(
+            Line::normal()
+                .link(LinkElement {
+                    label: "a]b`c".to_string(),
+                    url: ":/page/q.mu".to_string(),
+                    fields: vec!["note=x|y>z".to_string(), "path=C:\\tmp".to_string()],
+                    required: Vec::new(),
+                    style: Style::default(),
+                })
+                .field(Field {
+                    name: "msg".to_string(),
+                    default: "1 > 0 | `x`".to_string(),
+                    width: None,
+                    masked: false,
+                    tab_index: None,
+                    kind: FieldKind::Text,
+                }),
+        );
+        assert_eq!(crate::parse(&doc.to_string()), doc);
+    }
 }
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::Document;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Request {
+    pub path: String,
+    pub remote_identity: Option<String>,
+    pub link_id: Option<String>,
+    pub fields: HashMap<String, String>,
+    pub vars: HashMap<String, String>,
+}
+
+impl Request {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the request NomadNet passes to a page script. Variables whose
+    /// names aren't UTF-8 are skipped and values are converted lossily, so a
+    /// stray byte in the environment can't abort the page.
+    pub fn from_env() -> Self {
+        let path = std::env::args_os()
+            .next()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::from_vars(path, utf8_vars(std::env::vars_os()))
+    }
+
+    pub fn from_vars(
+        path: impl Into<String>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let mut request = Self::new(path);
+        for (key, value) in vars {
+            if let Some(name) = key.strip_prefix("field_") {
+                request.fields.insert(name.to_string(), value);
+            } else if let Some(name) = key.strip_prefix("var_") {
+                request.vars.insert(name.to_string(), value);
+            } else if key == "remote_identity" {
+                request.remote_identity = Some(value);
+            } else if key == "link_id" {
+                request.link_id = Some(value);
+            }
+        }
+        request
+    }
+
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    pub fn var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    pub fn is_identified(&self) -> bool {
+        self.remote_identity.is_some()
+    }
+}
+
+fn utf8_vars(
+    vars: impl IntoIterator<Item = (OsString, OsString)>,
+) -> impl Iterator<Item = (String, String)> {
+    vars.into_iter().filter_map(|(key, value)| {
+        let key = key.into_string().ok()?;
+        Some((key, value.to_string_lossy().into_owned()))
+    })
+}
+
+pub trait Handler {
+    fn handle(&self, request: &Request) -> Document;
+}
+
+impl<F: Fn(&Request) -> Document> Handler for F {
+    fn handle(&self, request: &Request) -> Document {
+        self(request)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub document: Document,
+    pub cache: Option<u32>,
+}
+
+impl Response {
+    pub fn new(document: Document) -> Self {
+        Self {
+            document,
+            cache: None,
+        }
+    }
+
+    pub fn cache(mut self, seconds: u32) -> Self {
+        self.cache = Some(seconds);
+        self
+    }
+
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{}", self)
+    }
+}
+
+impl From<Document> for Response {
+    fn from(document: Document) -> Self {
+        Self::new(document)
+    }
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cache {
+            Some(cache) => {
+                let mut document = self.document.clone();
+                document.metadata.set("c", cache.to_string());
+                write!(f, "{}", document)
+            }
+            None => write!(f, "{}", self.document),
+        }
+    }
+}
+
+pub fn serve(handler: &impl Handler) -> io::Result<()> {
+    let request = Request::from_env();
+    Response::new(handler.handle(&request)).write_to(&mut io::stdout().lock())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Line;
+
+    fn greet(request: &Request) -> Document {
+        let mut doc = Document::new();
+        let name = request.field("name").unwrap_or("stranger");
+        doc.push(Line::heading(1).text(&format!("Hello {}", name)));
+        if let Some(identity) = &request.remote_identity {
+            doc.push(Line::normal().text(&format!("Identified as {}", identity)));
+        }
+        doc
+    }
+
+    #[test]
+    fn request_from_nomadnet_env() {
+        let request = Request::from_vars(
+            "/page/index.mu",
+            [
+                ("field_name", "Ada"),
+                ("var_mode", "fast"),
+                ("remote_identity", "abcd"),
+                ("link_id", "1234"),
+                ("PATH", "/usr/bin"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        assert_eq!(request.field("name"), Some("Ada"));
+        assert_eq!(request.var("mode"), Some("fast"));
+        assert_eq!(request.remote_identity.as_deref(), Some("abcd"));
+        assert_eq!(request.link_id.as_deref(), Some("1234"));
+        assert_eq!(request.fields.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_env_is_tolerated() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let vars = [
+            (
+                OsString::from("field_name"),
+                OsString::from_vec(b"Ad\xffa".to_vec()),
+            ),
+            (
+                OsString::from_vec(b"field_\xff".to_vec()),
+                OsString::from("x"),
+            ),
+            (OsString::from("var_mode"), OsString::from("fast")),
+        ];
+        let request = Request::from_vars("/page/index.mu", utf8_vars(vars));
+        assert_eq!(request.field("name"), Some("Ad\u{fffd}a"));
+        assert_eq!(request.fields.len(), 1);
+        assert_eq!(request.var("mode"), Some("fast"));
+    }
+
+    #[test]
+    fn handler_response_serialization() {
+        let request = Request::new("/page/index.mu");
+        assert_eq!(
+            Response::new(greet.handle(&request)).to_string(),
+            ">Hello stranger"
+        );
+
+        let mut request = request;
+        request.fields.insert("name".to_string(), "Ada".to_string());
+        request.remote_identity = Some("abcd".to_string());
+        let response = Response::from(greet.handle(&request)).cache(0);
+        let mut out = Vec::new();
+        response.write_to(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "#!c=0\n>Hello Ada\nIdentified as abcd\n"
+        );
+    }
+}
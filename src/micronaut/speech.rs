@@ -0,0 +1,177 @@
+#[cfg(feature = "render")]
+use crate::FormState;
+use crate::micronaut::ast::progress_percent;
+use crate::{Document, Element, Field, FieldKind, LineKind, Progress};
+
+/// Current form values to announce in place of a page's declared defaults.
+pub trait FormValues {
+    fn text(&self, name: &str) -> Option<&str>;
+    fn checked(&self, name: &str) -> Option<bool>;
+    fn radio(&self, name: &str) -> Option<&str>;
+}
+
+impl FormValues for () {
+    fn text(&self, _: &str) -> Option<&str> {
+        None
+    }
+
+    fn checked(&self, _: &str) -> Option<bool> {
+        None
+    }
+
+    fn radio(&self, _: &str) -> Option<&str> {
+        None
+    }
+}
+
+#[cfg(feature = "render")]
+impl FormValues for FormState {
+    fn text(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    fn checked(&self, name: &str) -> Option<bool> {
+        self.checkboxes.get(name).copied()
+    }
+
+    fn radio(&self, name: &str) -> Option<&str> {
+        self.radios.get(name).map(String::as_str)
+    }
+}
+
+impl Document {
+    /// The page as a linear list of announcements in reading order, e.g.
+    /// `heading level 2: About`, `link: Messages` or
+    /// `text field 'username', value 'guest'`, for screen readers.
+    pub fn to_speech(&self) -> Vec<String> {
+        self.to_speech_with(&())
+    }
+
+    pub fn to_speech_with(&self, values: &impl FormValues) -> Vec<String> {
+        let mut out = Vec::new();
+        for line in &self.lines {
+            match line.kind {
+                LineKind::Comment
+                | LineKind::Directive
+                | LineKind::LiteralStart
+                | LineKind::LiteralEnd => {}
+                LineKind::Divider(_) => out.push("separator".to_string()),
+                LineKind::Heading(level) => {
+                    let text: String = line
+                        .elements
+                        .iter()
+                        .filter_map(|element| match element {
+                            Element::Text(text) => Some(text.text.as_str()),
+                            Element::Link(link) => Some(link.label.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    out.push(format!("heading level {}: {}", level, text.trim()));
+                }
+                LineKind::Normal => {
+                    let mut text = String::new();
+                    for element in &line.elements {
+                        if let Element::Text(run) = element {
+                            text.push_str(&run.text);
+                            continue;
+                        }
+                        flush_text(&mut text, &mut out);
+                        match element {
+                            Element::Link(link) => out.push(format!("link: {}", link.label)),
+                            Element::Field(field) => out.push(announce_field(field, values)),
+                            Element::Partial(_) => out.push("live region".to_string()),
+                            Element::Progress(progress) => out.push(announce_progress(progress)),
+                            Element::Text(_) => {}
+                        }
+                    }
+                    flush_text(&mut text, &mut out);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn flush_text(text: &mut String, out: &mut Vec<String>) {
+    let trimmed = text.trim();
+    if !trimmed.is_empty() {
+        out.push(trimmed.to_string());
+    }
+    text.clear();
+}
+
+fn announce_progress(progress: &Progress) -> String {
+    let percent = progress_percent(progress.value, progress.max);
+    if progress.label.is_empty() {
+        format!("progress {}%", percent)
+    } else {
+        format!("progress '{}', {}%", progress.label, percent)
+    }
+}
+
+fn announce_field(field: &Field, values: &impl FormValues) -> String {
+    match &field.kind {
+        FieldKind::Text if field.masked => format!("password field '{}'", field.name),
+        FieldKind::Text => {
+            let value = values.text(&field.name).unwrap_or(&field.default);
+            format!("text field '{}', value '{}'", field.name, value)
+        }
+        FieldKind::Number { .. } => {
+            let value = values.text(&field.name).unwrap_or(&field.default);
+            format!("number field '{}', value {}", field.name, value)
+        }
+        FieldKind::Date => {
+            let value = values.text(&field.name).unwrap_or(&field.default);
+            format!("date field '{}', value '{}'", field.name, value)
+        }
+        FieldKind::Checkbox { checked, .. } => {
+            let checked = values.checked(&field.name).unwrap_or(*checked);
+            format!(
+                "checkbox '{}', {}",
+                field.default,
+                if checked { "checked" } else { "not checked" }
+            )
+        }
+        FieldKind::Radio { value, checked } => {
+            let selected = values
+                .radio(&field.name)
+                .map_or(*checked, |current| current == value);
+            format!(
+                "radio button '{}' in '{}', {}",
+                field.default,
+                field.name,
+                if selected { "selected" } else { "not selected" }
+            )
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    #[test]
+    fn announces_in_reading_order() {
+        let doc = crate::parse(concat!(
+            ">>About\n",
+            "Hello `!there`! `[Messages`:/page/msg.mu] bye\n",
+            "-\n",
+            "# hidden\n",
+            "`<|username`guest> `<!|pass`>\n",
+            "`<?|news|1|*`Newsletter> `<^|size|s`Small>`<^|size|m|*`Medium>",
+        ));
+        assert_eq!(
+            doc.to_speech(),
+            [
+                "heading level 2: About",
+                "Hello there",
+                "link: Messages",
+                "bye",
+                "separator",
+                "text field 'username', value 'guest'",
+                "password field 'pass'",
+                "checkbox 'Newsletter', checked",
+                "radio button 'Small' in 'size', not selected",
+                "radio button 'Medium' in 'size', selected",
+            ]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::micronaut::browser::Browser;
+use crate::micronaut::render::Renderer;
+
+pub struct Tabs<R: Renderer + Clone> {
+    renderer: R,
+    tabs: Vec<Browser<R>>,
+    active: usize,
+    size: Option<(u16, u16)>,
+}
+
+impl<R: Renderer + Clone> Tabs<R> {
+    pub fn new(renderer: R) -> Self {
+        Self {
+            tabs: vec![Browser::new(renderer.clone())],
+            renderer,
+            active: 0,
+            size: None,
+        }
+    }
+
+    pub fn open(&mut self) -> usize {
+        let mut browser = Browser::new(self.renderer.clone());
+        if let Some((width, height)) = self.size {
+            browser.resize(width, height);
+        }
+        self.active += 1;
+        self.tabs.insert(self.active, browser);
+        self.active
+    }
+
+    pub fn close(&mut self, idx: usize) -> bool {
+        if idx >= self.tabs.len() {
+            return false;
+        }
+        if self.tabs.len() == 1 {
+            self.tabs[0] = Browser::new(self.renderer.clone());
+            if let Some((width, height)) = self.size {
+                self.tabs[0].resize(width, height);
+            }
+            return true;
+        }
+        self.tabs.remove(idx);
+        if self.active > idx || self.active == self.tabs.len() {
+            self.active -= 1;
+        }
+        true
+    }
+
+    pub fn close_active(&mut self) -> bool {
+        self.close(self.active)
+    }
+
+    pub fn switch(&mut self, idx: usize) -> bool {
+        if idx < self.tabs.len() {
+            self.active = idx;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.active = (self.active + 1) % self.tabs.len();
+    }
+
+    pub fn prev(&mut self) {
+        self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+    }
+
+    pub fn active(&self) -> &Browser<R> {
+        &self.tabs[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Browser<R> {
+        &mut self.tabs[self.active]
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Browser<R>> {
+        self.tabs.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Browser<R>> {
+        self.tabs.get_mut(idx)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Browser<R>> {
+        self.tabs.iter()
+    }
+
+    pub fn title(&self, idx: usize) -> Option<String> {
+        let browser = self.tabs.get(idx)?;
+        Some(
+            browser
+                .title()
+                .or_else(|| browser.url().map(|url| url.to_string()))
+                .unwrap_or_else(|| "New Tab".to_string()),
+        )
+    }
+
+    pub fn titles(&self) -> Vec<String> {
+        (0..self.tabs.len())
+            .filter_map(|idx| self.title(idx))
+            .collect()
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.size = Some((width, height));
+        for browser in &mut self.tabs {
+            browser.resize(width, height);
+        }
+    }
+
+    pub fn renderer(&self) -> &R {
+        &self.renderer
+    }
+
+    pub fn update_renderer(&mut self, f: impl Fn(&mut R)) {
+        f(&mut self.renderer);
+        for browser in &mut self.tabs {
+            browser.update_renderer(&f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Document;
+    use crate::micronaut::render::RenderOutput;
+    use crate::micronaut::types::{FormState, ViewState};
+    use std::collections::HashMap;
+
+    #[derive(Clone, Default)]
+    struct WidthRenderer {
+        generation: u32,
+    }
+
+    impl Renderer for WidthRenderer {
+        type Output = (u16, u32);
+
+        fn render(
+            &self,
+            doc: &Document,
+            width: u16,
+            _scroll: u16,
+            _form_state: &FormState,
+            _partial_contents: &HashMap<String, String>,
+            _view: &ViewState,
+        ) -> RenderOutput<(u16, u32)> {
+            RenderOutput {
+                content: (width, self.generation),
+                hitboxes: Vec::new(),
+                height: doc.lines.len() as u16,
+                width,
+                line_rows: Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    fn open_switch_close() {
+        let mut tabs = Tabs::new(WidthRenderer::default());
+        tabs.active_mut().set_content("/a", ">Alpha");
+        assert_eq!(tabs.open(), 1);
+        tabs.active_mut().set_content("/b", "no title");
+        tabs.open();
+        assert_eq!(tabs.titles(), ["Alpha", "/b", "New Tab"]);
+
+        tabs.next();
+        assert_eq!(tabs.active_index(), 0);
+        tabs.prev();
+        assert_eq!(tabs.active_index(), 2);
+        assert!(tabs.switch(1));
+        assert!(!tabs.switch(3));
+
+        assert!(tabs.close(0));
+        assert_eq!(tabs.active().url(), Some("/b"));
+        assert!(tabs.close_active());
+        assert_eq!(tabs.len(), 1);
+        assert!(tabs.close_active());
+        assert_eq!(tabs.len(), 1);
+        assert_eq!(tabs.active().url(), None);
+    }
+
+    #[test]
+    fn renderer_and_size_are_shared() {
+        let mut tabs = Tabs::new(WidthRenderer::default());
+        tabs.resize(40, 10);
+        tabs.active_mut().set_content("/a", "A");
+        tabs.open();
+        tabs.active_mut().set_content("/b", "B");
+        assert_eq!(tabs.active_mut().render(), Some(&(40, 0)));
+
+        tabs.update_renderer(|r| r.generation += 1);
+        assert_eq!(tabs.renderer().generation, 1);
+        for idx in 0..tabs.len() {
+            assert_eq!(tabs.get_mut(idx).unwrap().render(), Some(&(40, 1)));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+use std::ops::Range;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color as RatColor, Modifier, Style as RatStyle};
+use ratatui::widgets::{StatefulWidget, Widget};
+
+use crate::Document;
+use crate::micronaut::browser::Browser;
+use crate::micronaut::ratatui::RatatuiRenderer;
+use crate::micronaut::widget::BrowserWidget;
+
+/// A drawn grid of cells: one string per row, plus the styled runs in it.
+/// Displays as `|row|` lines, each followed by ` start..end style` lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridSnapshot {
+    pub rows: Vec<String>,
+    pub styles: Vec<StyledRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledRun {
+    pub row: usize,
+    pub columns: Range<usize>,
+    /// e.g. `fg=#ff0000 bold`
+    pub style: String,
+}
+
+pub fn render_grid(doc: &Document, width: u16) -> String {
+    let mut browser = Browser::new(RatatuiRenderer::new());
+    browser.resize(width, 1);
+    browser.set_document("snapshot", doc.clone());
+    let height = browser.viewport().content_height.max(1);
+    browser.resize(width, height);
+    browser_grid(&mut browser)
+}
+
+pub fn browser_grid(browser: &mut Browser<RatatuiRenderer>) -> String {
+    let viewport = browser.viewport();
+    let area = Rect::new(0, 0, viewport.width, viewport.height);
+    let mut buf = Buffer::empty(area);
+    if let Some(paragraph) = browser.render() {
+        paragraph.clone().render(area, &mut buf);
+    }
+    buffer_grid(&buf)
+}
+
+pub fn buffer_grid(buf: &Buffer) -> String {
+    GridSnapshot::from_buffer(buf).to_string()
+}
+
+impl GridSnapshot {
+    pub fn from_buffer(buf: &Buffer) -> Self {
+        let area = buf.area;
+        let mut snapshot = GridSnapshot {
+            rows: Vec::new(),
+            styles: Vec::new(),
+        };
+        for (row, y) in (area.top()..area.bottom()).enumerate() {
+            snapshot.rows.push(
+                (area.left()..area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect(),
+            );
+
+            let mut run: Option<(u16, RatStyle)> = None;
+            for x in area.left()..=area.right() {
+                let style = (x < area.right()).then(|| buf[(x, y)].style());
+                match (run, style) {
+                    (Some((_, current)), Some(style)) if current == style => {}
+                    _ => {
+                        if let Some((start, current)) = run.take() {
+                            let style = describe(current);
+                            if !style.is_empty() {
+                                snapshot.styles.push(StyledRun {
+                                    row,
+                                    columns: start as usize..x as usize,
+                                    style,
+                                });
+                            }
+                        }
+                        run = style.map(|style| (x, style));
+                    }
+                }
+            }
+        }
+        snapshot
+    }
+
+    /// The style of the run covering `(column, row)`, if it has one.
+    pub fn style_at(&self, column: usize, row: usize) -> Option<&str> {
+        self.styles
+            .iter()
+            .find(|run| run.row == row && run.columns.contains(&column))
+            .map(|run| run.style.as_str())
+    }
+}
+
+impl fmt::Display for GridSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (row, text) in self.rows.iter().enumerate() {
+            writeln!(f, "|{}|", text)?;
+            for run in self.styles.iter().filter(|run| run.row == row) {
+                writeln!(
+                    f,
+                    " {}..{} {}",
+                    run.columns.start, run.columns.end, run.style
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Browser<RatatuiRenderer> {
+    /// Exactly what a plain `BrowserWidget` draws over the current viewport,
+    /// selection, caret and field values included.
+    pub fn snapshot_grid(&mut self) -> GridSnapshot {
+        let viewport = self.viewport();
+        let area = Rect::new(0, 0, viewport.width, viewport.height);
+        let mut buf = Buffer::empty(area);
+        BrowserWidget::new().render(area, &mut buf, self);
+        GridSnapshot::from_buffer(&buf)
+    }
+}
+
+fn describe(style: RatStyle) -> String {
+    let mut parts = Vec::new();
+    if let Some(fg) = style.fg.filter(|c| *c != RatColor::Reset) {
+        parts.push(format!("fg={}", color_name(fg)));
+    }
+    if let Some(bg) = style.bg.filter(|c| *c != RatColor::Reset) {
+        parts.push(format!("bg={}", color_name(bg)));
+    }
+    for (modifier, name) in [
+        (Modifier::BOLD, "bold"),
+        (Modifier::ITALIC, "italic"),
+        (Modifier::UNDERLINED, "underline"),
+        (Modifier::REVERSED, "reversed"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            parts.push(name.to_string());
+        }
+    }
+    parts.join(" ")
+}
+
+fn color_name(color: RatColor) -> String {
+    match color {
+        RatColor::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        RatColor::Indexed(idx) => format!("idx{}", idx),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_with_style_annotations() {
+        let doc = crate::parse("`!Hi`! there\n`Ff00red");
+        assert_eq!(
+            render_grid(&doc, 10),
+            "|Hi there  |\n 0..2 bold\n|red       |\n 0..3 fg=#ff0000\n"
+        );
+    }
+
+    #[test]
+    fn snapshot_grid_shows_selection_and_field_values() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.resize(12, 2);
+        browser.set_content("/", "`[Go`/go]\n`<6|q`>");
+        browser.set_field_value("q", "rust".to_string());
+        let snapshot = browser.snapshot_grid();
+        assert_eq!(snapshot.rows.len(), 2);
+        assert!(snapshot.rows[1].contains("rust"));
+        let selected = snapshot.style_at(0, 0).map(str::to_string);
+
+        browser.select_next();
+        let snapshot = browser.snapshot_grid();
+        assert_ne!(snapshot.style_at(0, 0).map(str::to_string), selected);
+        assert_eq!(snapshot.to_string(), browser_grid(&mut browser));
+    }
+
+    #[test]
+    fn browser_grid_is_viewport_sized() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.resize(6, 2);
+        browser.set_content("/", "one\ntwo\nthree");
+        browser.scroll_by(1);
+        assert_eq!(browser_grid(&mut browser), "|two   |\n|three |\n");
+    }
+}
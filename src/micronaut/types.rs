@@ -1,10 +1,253 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::time::SystemTime;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FormState {
     pub fields: HashMap<String, String>,
     pub checkboxes: HashMap<String, bool>,
     pub radios: HashMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub revealed: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ViewState {
+    pub selected_interactable: Option<usize>,
+    pub hovered_interactable: Option<usize>,
+    pub selection: Option<TextSelection>,
+    pub editing: Option<FieldCursor>,
+    pub page_state: PageState,
+    pub scroll_x: u16,
+    /// Hint labels still matching what has been typed in hint mode.
+    pub hints: Vec<Hint>,
+}
+
+/// A short label that picks an interactable in hint mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub label: String,
+    pub interactable_idx: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryLimits {
+    pub max_entries: Option<usize>,
+    pub max_bytes: Option<usize>,
+    pub store_content: bool,
+}
+
+impl Default for HistoryLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: None,
+            max_bytes: None,
+            store_content: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefetchLimits {
+    pub max_concurrent: usize,
+    pub max_bytes: usize,
+}
+
+impl Default for PrefetchLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            max_bytes: 512 * 1024,
+        }
+    }
+}
+
+/// Throttles partial loads. The default applies no rate limits and only
+/// debounces field changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialLimits {
+    /// Most partial loads across the page in any 60 second window.
+    pub max_per_minute: Option<u32>,
+    /// Floor on every partial's declared refresh interval.
+    pub min_interval_secs: u32,
+    /// Up to this many seconds are added to each refresh so partials with
+    /// the same interval don't all fire together.
+    pub jitter_secs: u32,
+    /// Hold timed refreshes of partials scrolled out of view.
+    pub pause_offscreen: bool,
+    /// How long a field has to stay unchanged before partials sending it
+    /// are due again, counted down by `Browser::tick`. Zero makes every
+    /// keystroke reload them. Defaults to 300 ms.
+    pub field_debounce: std::time::Duration,
+}
+
+impl Default for PartialLimits {
+    fn default() -> Self {
+        Self {
+            max_per_minute: None,
+            min_interval_secs: 0,
+            jitter_secs: 0,
+            pause_offscreen: false,
+            field_debounce: std::time::Duration::from_millis(300),
+        }
+    }
+}
+
+/// The part of a date field that stepping changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSegment {
+    Year,
+    Month,
+    Day,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TabFilter {
+    #[default]
+    All,
+    Links,
+    Fields,
+}
+
+impl TabFilter {
+    pub fn allows(&self, interactable: &Interactable) -> bool {
+        match self {
+            TabFilter::All => true,
+            TabFilter::Links => matches!(interactable, Interactable::Link { .. }),
+            TabFilter::Fields => !matches!(interactable, Interactable::Link { .. }),
+        }
+    }
+}
+
+/// What a form submission sends for a checkbox that isn't checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UncheckedCheckbox {
+    #[default]
+    Omit,
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RadioDefault {
+    None,
+    #[default]
+    First,
+    PageDeclared,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum PageState {
+    #[default]
+    Ok,
+    Loading {
+        url: String,
+        progress: Option<f32>,
+    },
+    Error {
+        url: String,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Viewport {
+    pub width: u16,
+    pub height: u16,
+    pub content_height: u16,
+    pub scroll: u16,
+    pub max_scroll: u16,
+}
+
+impl Viewport {
+    pub fn visible_lines(&self) -> Range<usize> {
+        let start = self.scroll as usize;
+        let end = (start + self.height as usize).min(self.content_height as usize);
+        start..end.max(start)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub row: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageLink {
+    pub url: String,
+    pub fields: Vec<String>,
+    pub row: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldCursor {
+    pub name: String,
+    pub cursor: usize,
+    pub original: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextSelection {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+impl TextSelection {
+    pub fn new(start: (usize, usize), end: (usize, usize)) -> Self {
+        Self { start, end }
+    }
+
+    pub fn ordered(&self) -> ((usize, usize), (usize, usize)) {
+        if self.start <= self.end {
+            (self.start, self.end)
+        } else {
+            (self.end, self.start)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn columns(&self, line: usize) -> Option<Range<usize>> {
+        let ((start_line, start_col), (end_line, end_col)) = self.ordered();
+        if self.is_empty() || line < start_line || line > end_line {
+            return None;
+        }
+        let from = if line == start_line { start_col } else { 0 };
+        let to = if line == end_line {
+            end_col
+        } else {
+            usize::MAX
+        };
+        Some(from..to)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryRecord {
+    pub url: String,
+    pub scroll: u16,
+    pub content_hash: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryItem {
+    pub url: String,
+    pub title: Option<String>,
+    pub visited: Option<SystemTime>,
+    pub offset: isize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedHistory {
+    pub back: Vec<HistoryRecord>,
+    pub current: Option<HistoryRecord>,
+    pub forward: Vec<HistoryRecord>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -15,11 +258,16 @@ pub struct PartialInfo {
     pub fields: Vec<String>,
 }
 
+#[cfg(feature = "browser")]
 #[derive(Debug)]
 pub(crate) struct PartialState {
     pub info: PartialInfo,
     pub content: Option<String>,
     pub last_updated_secs: Option<u64>,
+    /// Document lines the partial appears on, as of the last rebuild.
+    pub lines: Vec<usize>,
+    /// Time left before a field change makes the partial due.
+    pub debounce: Option<std::time::Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +277,7 @@ pub struct Hitbox {
     pub col_end: usize,
     pub interactable: Interactable,
     pub interactable_idx: usize,
+    pub tab_index: Option<u16>,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +285,7 @@ pub enum Interactable {
     Link {
         url: String,
         fields: Vec<String>,
+        required: Vec<String>,
     },
     TextField {
         name: String,
@@ -44,18 +294,89 @@ pub enum Interactable {
     },
     Checkbox {
         name: String,
+        value: String,
+        checked: bool,
     },
     Radio {
         name: String,
         value: String,
+        checked: bool,
     },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectedElement {
+    Link {
+        url: String,
+        fields: Vec<String>,
+    },
+    TextField {
+        name: String,
+        value: String,
+        masked: bool,
+    },
+    Checkbox {
+        name: String,
+        value: String,
+        checked: bool,
+    },
+    Radio {
+        name: String,
+        value: String,
+        checked: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Link {
     pub url: String,
     pub fields: Vec<String>,
     pub form_data: HashMap<String, String>,
+    /// Request headers, like auth added by `Browser::on_navigate`. Loaders
+    /// that speak HTTP send them; others ignore them.
+    pub headers: HashMap<String, String>,
+}
+
+impl Link {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            fields: Vec::new(),
+            form_data: HashMap::new(),
+            headers: HashMap::new(),
+        }
+    }
+}
+
+/// A load started by `Browser::begin_follow` or `Browser::begin_reload`.
+/// Fetch `link`, then hand the result to `Browser::finish_load`.
+#[cfg(feature = "browser")]
+#[derive(Debug, Clone)]
+pub struct PendingLoad {
+    /// The link to fetch, as rewritten by `Browser::on_navigate`.
+    pub link: Link,
+    /// The page's own url, kept in history rather than the rewritten one.
+    pub(crate) url: String,
+    pub(crate) reload: bool,
+}
+
+/// What changed in a `Browser`, queued for `Browser::poll_events`.
+#[derive(Debug, Clone)]
+pub enum PageEvent {
+    NavigationRequested(Link),
+    /// A link to a NomadNet `/file/` path rather than a page.
+    DownloadRequested(Link),
+    FieldChanged {
+        name: String,
+        value: String,
+    },
+    CheckboxToggled {
+        name: String,
+        checked: bool,
+    },
+    /// A partial appeared or a field it sends changed. Timed refreshes are
+    /// still found with `next_partial_refresh`.
+    PartialRefreshDue(PartialInfo),
 }
 
 #[derive(Debug, Clone)]
@@ -69,5 +390,14 @@ pub struct TextField {
 pub enum Interaction {
     Link(Link),
     EditField(TextField),
+    Toggled {
+        name: String,
+        value: Option<String>,
+        checked: bool,
+    },
+    Submitted(TextField),
     RefreshPartials(Vec<String>),
+    SubmitBlocked {
+        missing: Vec<String>,
+    },
 }
@@ -0,0 +1,157 @@
+use crate::{Color, Document, Element, Style};
+
+/// Reader overrides laid over a page's own styling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UserStyle {
+    pub no_backgrounds: bool,
+    pub text_color: Option<Color>,
+    pub link_color: Option<Color>,
+    /// Lighten or darken text until its WCAG contrast ratio against its
+    /// background reaches this, e.g. `4.5`.
+    pub min_contrast: Option<f32>,
+    /// What text without a background of its own is drawn on.
+    pub page_background: Color,
+}
+
+impl Default for UserStyle {
+    fn default() -> Self {
+        Self {
+            no_backgrounds: false,
+            text_color: None,
+            link_color: None,
+            min_contrast: None,
+            page_background: Color { r: 0, g: 0, b: 0 },
+        }
+    }
+}
+
+impl UserStyle {
+    fn apply(&self, style: &mut Style, link: bool) {
+        if self.no_backgrounds {
+            style.bg = None;
+        }
+        let forced = if link {
+            self.link_color
+        } else {
+            self.text_color
+        };
+        if forced.is_some() {
+            style.fg = forced;
+        }
+        if let (Some(min), Some(fg)) = (self.min_contrast, style.fg) {
+            style.fg = Some(with_contrast(
+                fg,
+                style.bg.unwrap_or(self.page_background),
+                min,
+            ));
+        }
+    }
+}
+
+impl Document {
+    pub fn apply_user_style(&mut self, user_style: &UserStyle) {
+        for line in &mut self.lines {
+            for element in &mut line.elements {
+                match element {
+                    Element::Text(text) => user_style.apply(&mut text.style, false),
+                    Element::Link(link) => user_style.apply(&mut link.style, true),
+                    Element::Field(_) | Element::Partial(_) | Element::Progress(_) => {}
+                }
+            }
+        }
+    }
+}
+
+fn luminance(color: Color) -> f32 {
+    let channel = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+}
+
+fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Blends `fg` toward white or black, whichever stands out more against
+/// `bg`, in the smallest step that reaches `min`.
+fn with_contrast(fg: Color, bg: Color, min: f32) -> Color {
+    if contrast_ratio(fg, bg) >= min {
+        return fg;
+    }
+    let white = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+    let black = Color { r: 0, g: 0, b: 0 };
+    let target = if contrast_ratio(white, bg) >= contrast_ratio(black, bg) {
+        white
+    } else {
+        black
+    };
+    let blend = |t: f32| {
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+        Color {
+            r: mix(fg.r, target.r),
+            g: mix(fg.g, target.g),
+            b: mix(fg.b, target.b),
+        }
+    };
+    (1..=20)
+        .map(|step| blend(step as f32 / 20.0))
+        .find(|color| contrast_ratio(*color, bg) >= min)
+        .unwrap_or(target)
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_and_contrast() {
+        let navy = Color {
+            r: 0,
+            g: 0,
+            b: 0x44,
+        };
+        let mut doc = crate::parse("`F004`Bff0dark`f`b `[Home`/]");
+        doc.apply_user_style(&UserStyle {
+            no_backgrounds: true,
+            link_color: Some(Color {
+                r: 0,
+                g: 0xff,
+                b: 0xff,
+            }),
+            min_contrast: Some(4.5),
+            ..Default::default()
+        });
+        let styles: Vec<Style> = doc.lines[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                Element::Text(text) if !text.text.trim().is_empty() => Some(text.style),
+                Element::Link(link) => Some(link.style),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(styles[0].bg, None);
+        let text = styles[0].fg.unwrap();
+        assert_ne!(text, navy);
+        assert!(contrast_ratio(text, Color { r: 0, g: 0, b: 0 }) >= 4.5);
+        assert_eq!(
+            styles[1].fg,
+            Some(Color {
+                r: 0,
+                g: 0xff,
+                b: 0xff
+            })
+        );
+    }
+}
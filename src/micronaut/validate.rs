@@ -0,0 +1,206 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::{Document, Element, FieldKind, LineKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    EmptyLinkUrl,
+    UnknownLinkField(String),
+    EmptyFieldName,
+    DuplicateField(String),
+    MultipleRadioChecked(String),
+    EmptyPartialUrl,
+    UnterminatedLiteral,
+    UnmatchedLiteralEnd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub line: usize,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line + 1)?;
+        match &self.kind {
+            IssueKind::EmptyLinkUrl => f.write_str("link has no url"),
+            IssueKind::UnknownLinkField(name) => {
+                write!(f, "link submits unknown field `{}`", name)
+            }
+            IssueKind::EmptyFieldName => f.write_str("field has no name"),
+            IssueKind::DuplicateField(name) => write!(f, "duplicate field `{}`", name),
+            IssueKind::MultipleRadioChecked(name) => {
+                write!(f, "radio group `{}` has more than one checked option", name)
+            }
+            IssueKind::EmptyPartialUrl => f.write_str("partial has no url"),
+            IssueKind::UnterminatedLiteral => {
+                f.write_str("literal block is never closed and runs to the end of the page")
+            }
+            IssueKind::UnmatchedLiteralEnd => f.write_str("literal block end without a start"),
+        }
+    }
+}
+
+impl Document {
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let mut fields: HashSet<&str> = HashSet::new();
+        let mut text_fields: HashSet<&str> = HashSet::new();
+        let mut checked_radios: HashMap<&str, usize> = HashMap::new();
+
+        for (line, element) in self.elements() {
+            let Element::Field(field) = element else {
+                continue;
+            };
+            if field.name.is_empty() {
+                issues.push(Issue {
+                    line,
+                    kind: IssueKind::EmptyFieldName,
+                });
+                continue;
+            }
+            fields.insert(&field.name);
+            match &field.kind {
+                FieldKind::Text | FieldKind::Number { .. } | FieldKind::Date
+                    if !text_fields.insert(&field.name) =>
+                {
+                    issues.push(Issue {
+                        line,
+                        kind: IssueKind::DuplicateField(field.name.clone()),
+                    })
+                }
+                FieldKind::Radio { checked: true, .. } => {
+                    let count = checked_radios.entry(&field.name).or_default();
+                    *count += 1;
+                    if *count == 2 {
+                        issues.push(Issue {
+                            line,
+                            kind: IssueKind::MultipleRadioChecked(field.name.clone()),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (line, element) in self.elements() {
+            let (url, specs, empty_url) = match element {
+                Element::Link(link) => (&link.url, &link.fields, IssueKind::EmptyLinkUrl),
+                Element::Partial(partial) => {
+                    (&partial.url, &partial.fields, IssueKind::EmptyPartialUrl)
+                }
+                _ => continue,
+            };
+            if url.is_empty() {
+                issues.push(Issue {
+                    line,
+                    kind: empty_url,
+                });
+            }
+            for spec in specs {
+                if spec == "*" || spec.contains('=') {
+                    continue;
+                }
+                if !fields.contains(spec.as_str()) {
+                    issues.push(Issue {
+                        line,
+                        kind: IssueKind::UnknownLinkField(spec.clone()),
+                    });
+                }
+            }
+        }
+
+        let mut open_literal = None;
+        for (line, kind) in self.lines.iter().map(|line| line.kind).enumerate() {
+            match kind {
+                LineKind::LiteralStart => open_literal = Some(line),
+                LineKind::LiteralEnd if open_literal.take().is_none() => issues.push(Issue {
+                    line,
+                    kind: IssueKind::UnmatchedLiteralEnd,
+                }),
+                _ => {}
+            }
+        }
+        if let Some(line) = open_literal {
+            issues.push(Issue {
+                line,
+                kind: IssueKind::UnterminatedLiteral,
+            });
+        }
+
+        issues.sort_by_key(|issue| issue.line);
+        issues
+    }
+
+    fn elements(&self) -> impl Iterator<Item = (usize, &Element)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, line)| line.elements.iter().map(move |element| (idx, element)))
+    }
+}
+
+#[cfg(all(test, feature = "parser"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_document_has_no_issues() {
+        let doc = crate::parse(
+            "`<name`>\n`<^|size|s|*`S>`<^|size|m`M>\n`[Go`:/page/go.mu`name|!size|*|mode=fast]\n`{:/page/p.mu`0`name}",
+        );
+        assert_eq!(doc.validate(), vec![]);
+    }
+
+    #[test]
+    fn reports_issues_by_line() {
+        let doc = crate::parse(
+            "`<name`>\n`<name`>\n`<^|size|s|*`S>`<^|size|m|*`M>\n`[Go`:/page/go.mu`nmae]",
+        );
+        let issues = doc.validate();
+        assert_eq!(
+            issues,
+            vec![
+                Issue {
+                    line: 1,
+                    kind: IssueKind::DuplicateField("name".to_string()),
+                },
+                Issue {
+                    line: 2,
+                    kind: IssueKind::MultipleRadioChecked("size".to_string()),
+                },
+                Issue {
+                    line: 3,
+                    kind: IssueKind::UnknownLinkField("nmae".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            issues[2].to_string(),
+            "line 4: link submits unknown field `nmae`"
+        );
+    }
+
+    #[test]
+    fn reports_unterminated_literal() {
+        let options = crate::ParseOptions {
+            literal_markers: true,
+            ..Default::default()
+        };
+        let doc = crate::parse_with("intro\n`=\nraw", &options);
+        let issues = doc.validate();
+        assert_eq!(
+            issues,
+            vec![Issue {
+                line: 1,
+                kind: IssueKind::UnterminatedLiteral,
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "line 2: literal block is never closed and runs to the end of the page"
+        );
+    }
+}
@@ -1,36 +1,700 @@
-// use ratatui::buffer::Buffer;
-// use ratatui::layout::Rect;
-// use ratatui::style::{Color, Style};
-// use ratatui::text::Text;
-// use ratatui::widgets::{Paragraph, Widget};
-
-// use crate::micronaut::browser::Browser;
-// use crate::micronaut::ratatui::RatatuiRenderer;
-
-// pub struct BrowserWidget<'a> {
-//     browser: &'a mut Browser<RatatuiRenderer>,
-// }
-
-// impl<'a> BrowserWidget<'a> {
-//     pub fn new(browser: &'a mut Browser<RatatuiRenderer>) -> Self {
-//         Self { browser }
-//     }
-// }
-
-// impl Widget for BrowserWidget<'_> {
-//     fn render(self, area: Rect, buf: &mut Buffer) {
-//         self.browser.resize(area.width, area.height);
-//         let scroll = self.browser.scroll();
-
-//         if let Some(content) = self.browser.render().cloned() {
-//             Paragraph::new(content)
-//                 .scroll((scroll, 0))
-//                 .render(area, buf);
-//         } else {
-//             let content = Text::styled("No content", Style::default().fg(Color::DarkGray));
-//             Paragraph::new(content)
-//                 .alignment(ratatui::layout::Alignment::Center)
-//                 .render(area, buf);
-//         }
-//     }
-// }
+use std::cell::Cell;
+
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Alignment, Margin, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::scrollbar;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, StatefulWidget, Widget};
+
+use crate::micronaut::browser::Browser;
+use crate::micronaut::keys::BrowserEvent;
+use crate::micronaut::ratatui::RatatuiRenderer;
+use crate::micronaut::render::Renderer;
+use crate::micronaut::types::{Interaction, PageState, Viewport};
+
+#[derive(Debug, Clone)]
+pub struct BrowserWidget<'a> {
+    pub scrollbar: bool,
+    pub status_line: bool,
+    pub placeholder: Line<'a>,
+    pub edit_overlay: Option<EditOverlay<'a>>,
+    /// Row within the scrollbar thumb held by an ongoing drag.
+    scrollbar_grab: Cell<Option<u16>>,
+}
+
+impl Default for BrowserWidget<'_> {
+    fn default() -> Self {
+        Self {
+            scrollbar: false,
+            status_line: false,
+            placeholder: Line::styled("No content", Style::default().fg(Color::DarkGray)),
+            edit_overlay: None,
+            scrollbar_grab: Cell::new(None),
+        }
+    }
+}
+
+impl<'a> BrowserWidget<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn scrollbar(mut self, enabled: bool) -> Self {
+        self.scrollbar = enabled;
+        self
+    }
+
+    pub fn status_line(mut self, enabled: bool) -> Self {
+        self.status_line = enabled;
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<Line<'a>>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn edit_overlay(mut self, overlay: EditOverlay<'a>) -> Self {
+        self.edit_overlay = Some(overlay);
+        self
+    }
+
+    pub fn last_area(&self, browser: &Browser<RatatuiRenderer>) -> Rect {
+        let (x, y) = browser.origin();
+        let viewport = browser.viewport();
+        Rect::new(
+            x,
+            y,
+            viewport.width + self.scrollbar as u16,
+            viewport.height + self.status_line as u16,
+        )
+    }
+
+    pub fn handle_mouse(
+        &self,
+        browser: &mut Browser<RatatuiRenderer>,
+        event: MouseEvent,
+    ) -> BrowserEvent {
+        if browser.editing().is_some()
+            && let Some(overlay) = &self.edit_overlay
+        {
+            return match event.kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    let area = self.last_area(browser);
+                    overlay.handle_click(area, browser, event.column, event.row)
+                }
+                _ => BrowserEvent::Ignored,
+            };
+        }
+
+        if self.scrollbar
+            && let Some(event) = self.handle_scrollbar(browser, event)
+        {
+            return event;
+        }
+
+        let local = browser.to_local(event.column, event.row);
+        match (event.kind, local) {
+            (MouseEventKind::Down(MouseButton::Left), Some((x, y))) => {
+                browser.start_selection(x, y);
+                match browser.click(x, y) {
+                    Some(interaction) => BrowserEvent::Interaction(interaction),
+                    None => BrowserEvent::Handled,
+                }
+            }
+            (MouseEventKind::Drag(MouseButton::Left), _) if browser.selection().is_some() => {
+                let (origin_x, origin_y) = browser.origin();
+                let viewport = browser.viewport();
+                let x = event.column.saturating_sub(origin_x);
+                let y = event.row.saturating_sub(origin_y);
+                browser.extend_selection(
+                    x.min(viewport.width.saturating_sub(1)),
+                    y.min(viewport.height.saturating_sub(1)),
+                );
+                BrowserEvent::Handled
+            }
+            (MouseEventKind::Moved, local) => {
+                let changed = match local {
+                    Some((x, y)) => browser.hover(x, y),
+                    None => browser.clear_hover(),
+                };
+                match changed {
+                    true => BrowserEvent::Handled,
+                    false => BrowserEvent::Ignored,
+                }
+            }
+            (MouseEventKind::ScrollDown, Some(_)) => {
+                browser.scroll_by(1);
+                BrowserEvent::Handled
+            }
+            (MouseEventKind::ScrollUp, Some(_)) => {
+                browser.scroll_by(-1);
+                BrowserEvent::Handled
+            }
+            (MouseEventKind::ScrollRight, Some(_)) => {
+                browser.scroll_horizontal_by(2);
+                BrowserEvent::Handled
+            }
+            (MouseEventKind::ScrollLeft, Some(_)) => {
+                browser.scroll_horizontal_by(-2);
+                BrowserEvent::Handled
+            }
+            _ => BrowserEvent::Ignored,
+        }
+    }
+
+    fn handle_scrollbar(
+        &self,
+        browser: &mut Browser<RatatuiRenderer>,
+        event: MouseEvent,
+    ) -> Option<BrowserEvent> {
+        let (x, y) = browser.origin();
+        let viewport = browser.viewport();
+        let track = Rect::new(x + viewport.width, y, 1, viewport.height);
+        let row = event.row.saturating_sub(track.y);
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left)
+                if track.contains(Position::new(event.column, event.row)) =>
+            {
+                let (thumb_start, thumb_len) = thumb(&viewport);
+                let grab = match row >= thumb_start && row < thumb_start + thumb_len {
+                    true => row - thumb_start,
+                    false => thumb_len / 2,
+                };
+                self.scrollbar_grab.set(Some(grab));
+                drag_thumb(browser, row, grab);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                drag_thumb(browser, row, self.scrollbar_grab.get()?);
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.scrollbar_grab.take()?;
+            }
+            _ => return None,
+        }
+        Some(BrowserEvent::Handled)
+    }
+
+    pub fn content_area(&self, area: Rect) -> Rect {
+        let mut content = area;
+        if self.status_line {
+            content.height = content.height.saturating_sub(1);
+        }
+        if self.scrollbar {
+            content.width = content.width.saturating_sub(1);
+        }
+        content
+    }
+}
+
+impl StatefulWidget for BrowserWidget<'_> {
+    type State = Browser<RatatuiRenderer>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, browser: &mut Self::State) {
+        let content = self.content_area(area);
+        browser.set_origin(content.x, content.y);
+        browser.resize(content.width, content.height);
+
+        match browser.render() {
+            Some(paragraph) => paragraph.render(content, buf),
+            None => Paragraph::new(self.placeholder.clone())
+                .alignment(Alignment::Center)
+                .render(content, buf),
+        }
+
+        if self.scrollbar && area.width > 0 {
+            let track = Rect::new(area.right() - 1, content.y, 1, content.height);
+            let (thumb_start, thumb_len) = thumb(&browser.viewport());
+            let style = Style::default().fg(Color::DarkGray);
+            for (row, y) in (track.top()..track.bottom()).enumerate() {
+                let row = row as u16;
+                let symbol = match row >= thumb_start && row < thumb_start + thumb_len {
+                    true => scrollbar::DOUBLE_VERTICAL.thumb,
+                    false => scrollbar::DOUBLE_VERTICAL.track,
+                };
+                buf[(track.x, y)].set_symbol(symbol).set_style(style);
+            }
+        }
+
+        if self.status_line && area.height > 0 {
+            let row = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+            Paragraph::new(status(browser))
+                .style(Style::default().add_modifier(Modifier::REVERSED))
+                .render(row, buf);
+        }
+
+        if let Some(overlay) = self.edit_overlay {
+            overlay.render(area, buf, browser);
+        }
+    }
+}
+
+impl<R: Renderer> Browser<R> {
+    pub fn click_in_area(&mut self, area: Rect, x: u16, y: u16) -> Option<Interaction> {
+        if !area.contains(Position::new(x, y)) {
+            return None;
+        }
+        self.click(x - area.x, y - area.y)
+    }
+
+    pub fn hover_in_area(&mut self, area: Rect, x: u16, y: u16) -> bool {
+        if !area.contains(Position::new(x, y)) {
+            return self.clear_hover();
+        }
+        self.hover(x - area.x, y - area.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayButton {
+    Cancel,
+    Confirm,
+}
+
+#[derive(Debug, Clone)]
+pub struct EditOverlay<'a> {
+    pub width: u16,
+    pub border_color: Color,
+    pub cancel_label: &'a str,
+    pub confirm_label: &'a str,
+}
+
+impl Default for EditOverlay<'_> {
+    fn default() -> Self {
+        Self {
+            width: 50,
+            border_color: Color::Cyan,
+            cancel_label: "Cancel",
+            confirm_label: "Confirm",
+        }
+    }
+}
+
+impl<'a> EditOverlay<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn border_color(mut self, color: Color) -> Self {
+        self.border_color = color;
+        self
+    }
+
+    pub fn labels(mut self, cancel: &'a str, confirm: &'a str) -> Self {
+        self.cancel_label = cancel;
+        self.confirm_label = confirm;
+        self
+    }
+
+    pub fn popup_area(&self, area: Rect) -> Rect {
+        let width = self.width.min(area.width.saturating_sub(4));
+        let height = 5.min(area.height);
+        Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        )
+    }
+
+    pub fn button_areas(&self, area: Rect) -> [(OverlayButton, Rect); 2] {
+        let inner = self.popup_area(area).inner(Margin::new(1, 1));
+        let cancel = self.cancel_label.chars().count() as u16 + 2;
+        let confirm = self.confirm_label.chars().count() as u16 + 2;
+        let y = inner.bottom().saturating_sub(1);
+        let x = inner.x + inner.width.saturating_sub(cancel + 2 + confirm) / 2;
+        [
+            (OverlayButton::Cancel, Rect::new(x, y, cancel, 1)),
+            (
+                OverlayButton::Confirm,
+                Rect::new(x + cancel + 2, y, confirm, 1),
+            ),
+        ]
+    }
+
+    pub fn cursor_position(
+        &self,
+        area: Rect,
+        browser: &Browser<RatatuiRenderer>,
+    ) -> Option<Position> {
+        let editing = browser.editing()?;
+        let inner = self.popup_area(area).inner(Margin::new(1, 1));
+        if inner.width == 0 || inner.height == 0 {
+            return None;
+        }
+        let (_, offset) = self.visible_value(inner.width, browser)?;
+        Some(Position::new(
+            inner.x + (editing.cursor - offset) as u16,
+            inner.y,
+        ))
+    }
+
+    pub fn handle_click(
+        &self,
+        area: Rect,
+        browser: &mut Browser<RatatuiRenderer>,
+        x: u16,
+        y: u16,
+    ) -> BrowserEvent {
+        if browser.editing().is_none() {
+            return BrowserEvent::Ignored;
+        }
+        let position = Position::new(x, y);
+        let button = self
+            .button_areas(area)
+            .into_iter()
+            .find(|(_, rect)| rect.contains(position))
+            .map(|(button, _)| button);
+        match button {
+            Some(OverlayButton::Confirm) => match browser.stop_editing() {
+                Some(field) => BrowserEvent::Interaction(Interaction::Submitted(field)),
+                None => BrowserEvent::Handled,
+            },
+            Some(OverlayButton::Cancel) => {
+                browser.cancel_editing();
+                BrowserEvent::Handled
+            }
+            None if self.popup_area(area).contains(position) => BrowserEvent::Handled,
+            None => BrowserEvent::Ignored,
+        }
+    }
+
+    fn visible_value(
+        &self,
+        width: u16,
+        browser: &Browser<RatatuiRenderer>,
+    ) -> Option<(String, usize)> {
+        let editing = browser.editing()?;
+        let value = browser.field_value(&editing.name).unwrap_or_default();
+        let masked = browser.editing_masked() && !browser.is_revealed(&editing.name);
+        let offset = editing
+            .cursor
+            .saturating_sub(width.saturating_sub(1) as usize);
+        let visible = value
+            .chars()
+            .skip(offset)
+            .take(width as usize)
+            .map(|c| if masked { '*' } else { c })
+            .collect();
+        Some((visible, offset))
+    }
+}
+
+impl StatefulWidget for EditOverlay<'_> {
+    type State = Browser<RatatuiRenderer>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, browser: &mut Self::State) {
+        let Some(editing) = browser.editing() else {
+            return;
+        };
+        let popup = self.popup_area(area);
+        Clear.render(popup, buf);
+        let block = Block::default()
+            .title(format!(" {} ", editing.name))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.border_color));
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+        if inner.height == 0 {
+            return;
+        }
+
+        if let Some((visible, _)) = self.visible_value(inner.width, browser) {
+            buf.set_stringn(
+                inner.x,
+                inner.y,
+                visible,
+                inner.width as usize,
+                Style::default(),
+            );
+        }
+        for (button, rect) in self.button_areas(area) {
+            let (label, color) = match button {
+                OverlayButton::Cancel => (self.cancel_label, Color::DarkGray),
+                OverlayButton::Confirm => (self.confirm_label, Color::Green),
+            };
+            buf.set_stringn(
+                rect.x,
+                rect.y,
+                format!(" {} ", label),
+                rect.width.min(inner.right().saturating_sub(rect.x)) as usize,
+                Style::default().fg(Color::Black).bg(color),
+            );
+        }
+    }
+}
+
+fn thumb(viewport: &Viewport) -> (u16, u16) {
+    let track = viewport.height;
+    if track == 0 || viewport.max_scroll == 0 || viewport.content_height == 0 {
+        return (0, track);
+    }
+    let len = (track as u32 * track as u32 / viewport.content_height as u32).clamp(1, track as u32);
+    let travel = track as u32 - len;
+    let start = (viewport.scroll.min(viewport.max_scroll) as u32 * travel
+        + viewport.max_scroll as u32 / 2)
+        / viewport.max_scroll as u32;
+    (start as u16, len as u16)
+}
+
+fn drag_thumb(browser: &mut Browser<RatatuiRenderer>, row: u16, grab: u16) {
+    let viewport = browser.viewport();
+    let (_, len) = thumb(&viewport);
+    let travel = viewport.height.saturating_sub(len);
+    if travel == 0 {
+        return;
+    }
+    let top = row.saturating_sub(grab).min(travel) as u32;
+    let max = viewport.max_scroll as u32;
+    browser.scroll_to(((top * max + travel as u32 / 2) / travel as u32) as u16);
+}
+
+fn status(browser: &Browser<RatatuiRenderer>) -> Line<'static> {
+    let (label, url) = match browser.state() {
+        PageState::Loading { url, .. } => ("Loading ", url.as_str()),
+        PageState::Error { url, message } => {
+            return Line::from(vec![
+                Span::styled("Error ", Style::default().fg(Color::Red)),
+                Span::raw(format!("{}: {}", url, message)),
+            ]);
+        }
+        PageState::Ok => ("", browser.url().unwrap_or_default()),
+    };
+    let position = match browser.viewport().max_scroll {
+        0 => "All".to_string(),
+        _ => format!("{}%", browser.scroll_percent()),
+    };
+    Line::from(vec![
+        Span::raw(format!(" {}{} ", label, url)),
+        Span::raw(format!("[{}] ", position)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(
+        widget: BrowserWidget,
+        browser: &mut Browser<RatatuiRenderer>,
+        area: Rect,
+    ) -> Vec<String> {
+        let mut buf = Buffer::empty(area);
+        widget.render(area, &mut buf, browser);
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn placeholder_without_content() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        let rows = draw(BrowserWidget::new(), &mut browser, Rect::new(0, 0, 12, 2));
+        assert_eq!(rows, vec![" No content ", "            "]);
+    }
+
+    #[test]
+    fn resizes_browser_to_content_area() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/page/index.mu", "one\ntwo\nthree\nfour");
+        let widget = BrowserWidget::new().scrollbar(true).status_line(true);
+        let rows = draw(widget, &mut browser, Rect::new(2, 1, 20, 3));
+        let viewport = browser.viewport();
+        assert_eq!((viewport.width, viewport.height), (19, 2));
+        assert!(rows[0].starts_with("one"));
+        assert!(rows[1].starts_with("two"));
+        assert_ne!(rows[0].chars().last(), Some(' '));
+        assert_eq!(rows[2].trim_end(), " /page/index.mu [0%]");
+
+        browser.scroll_end();
+        let widget = BrowserWidget::new().status_line(true);
+        let rows = draw(widget, &mut browser, Rect::new(0, 0, 24, 3));
+        assert!(rows[0].starts_with("three"));
+        assert_eq!(rows[2].trim_end(), " /page/index.mu [100%]");
+    }
+
+    #[test]
+    fn status_line_reports_state() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/a.mu", "hi");
+        browser.set_loading("/b.mu", None);
+        let widget = BrowserWidget::new().status_line(true);
+        let rows = draw(widget.clone(), &mut browser, Rect::new(0, 0, 20, 2));
+        assert_eq!(rows[1].trim_end(), " Loading /b.mu [All]");
+
+        browser.set_error("/b.mu", "timed out");
+        let rows = draw(widget, &mut browser, Rect::new(0, 0, 30, 2));
+        assert_eq!(rows[1].trim_end(), "Error /b.mu: timed out");
+    }
+    #[test]
+    fn edit_overlay_shows_masked_value_and_cursor() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`<!8|pin`1234>");
+        let overlay = EditOverlay::new().width(12);
+        let area = Rect::new(0, 0, 20, 7);
+        let widget = BrowserWidget::new().edit_overlay(overlay.clone());
+        assert!(!draw(widget.clone(), &mut browser, area)[1].contains("pin"));
+        assert_eq!(overlay.cursor_position(area, &browser), None);
+
+        browser.begin_editing("pin");
+        browser.cursor_left();
+        let rows = draw(widget, &mut browser, area);
+        assert_eq!(rows[1], "    ┌ pin ─────┐    ");
+        assert_eq!(rows[2], "    │****      │    ");
+        assert_eq!(
+            overlay.cursor_position(area, &browser),
+            Some(Position::new(8, 2))
+        );
+    }
+
+    #[test]
+    fn edit_overlay_buttons() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`<|name`Ada>");
+        let overlay = EditOverlay::new();
+        let area = Rect::new(0, 0, 40, 10);
+        let [(_, cancel), (_, confirm)] = overlay.button_areas(area);
+
+        assert!(matches!(
+            overlay.handle_click(area, &mut browser, cancel.x, cancel.y),
+            BrowserEvent::Ignored
+        ));
+
+        browser.begin_editing("name");
+        browser.input_str(" L");
+        assert!(matches!(
+            overlay.handle_click(area, &mut browser, cancel.x, cancel.y),
+            BrowserEvent::Handled
+        ));
+        assert_eq!(browser.field_value("name"), Some("Ada"));
+        assert!(browser.editing().is_none());
+
+        browser.begin_editing("name");
+        browser.input_str(" L");
+        let popup = overlay.popup_area(area);
+        assert!(matches!(
+            overlay.handle_click(area, &mut browser, popup.x + 1, popup.y + 1),
+            BrowserEvent::Handled
+        ));
+        let BrowserEvent::Interaction(Interaction::Submitted(field)) =
+            overlay.handle_click(area, &mut browser, confirm.x, confirm.y)
+        else {
+            panic!("Expected Submitted interaction");
+        };
+        assert_eq!(field.value, "Ada L");
+    }
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: ratatui::crossterm::event::KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn click_in_area_translates_coordinates() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.resize(20, 3);
+        browser.set_content("/", "`[One`/one] `[Two`/two]");
+        let area = Rect::new(10, 5, 20, 3);
+        assert!(browser.click_in_area(area, 1, 0).is_none());
+        let Some(Interaction::Link(link)) = browser.click_in_area(area, 16, 5) else {
+            panic!("Expected Link interaction");
+        };
+        assert_eq!(link.url, "/two");
+        assert!(browser.hover_in_area(area, 11, 5));
+        assert_eq!(browser.hovered_link(), Some("/one"));
+        assert!(browser.hover_in_area(area, 0, 0));
+        assert_eq!(browser.hovered_link(), None);
+    }
+
+    #[test]
+    fn widget_records_area_for_mouse_events() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "`[One`/one] `[Two`/two]\n2\n3\n4\n5");
+        let widget = BrowserWidget::new().scrollbar(true).status_line(true);
+        draw(widget.clone(), &mut browser, Rect::new(4, 2, 21, 4));
+        assert_eq!(browser.origin(), (4, 2));
+        assert_eq!(widget.last_area(&browser), Rect::new(4, 2, 21, 4));
+
+        assert!(matches!(
+            widget.handle_mouse(
+                &mut browser,
+                mouse(MouseEventKind::Down(MouseButton::Left), 1, 2)
+            ),
+            BrowserEvent::Ignored
+        ));
+        let BrowserEvent::Interaction(Interaction::Link(link)) = widget.handle_mouse(
+            &mut browser,
+            mouse(MouseEventKind::Down(MouseButton::Left), 10, 2),
+        ) else {
+            panic!("Expected Link interaction");
+        };
+        assert_eq!(link.url, "/two");
+
+        assert!(matches!(
+            widget.handle_mouse(&mut browser, mouse(MouseEventKind::ScrollDown, 4, 5)),
+            BrowserEvent::Ignored
+        ));
+        widget.handle_mouse(&mut browser, mouse(MouseEventKind::ScrollDown, 4, 4));
+        assert_eq!(browser.scroll(), 1);
+    }
+
+    #[test]
+    fn scrollbar_track_jumps_and_thumb_drags() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        let content: Vec<String> = (0..20).map(|n| n.to_string()).collect();
+        browser.set_content("/", &content.join("\n"));
+        let widget = BrowserWidget::new().scrollbar(true);
+        let area = Rect::new(0, 0, 10, 5);
+        let rows = draw(widget.clone(), &mut browser, area);
+        assert!(rows[0].ends_with('█'));
+        assert!(rows[1].ends_with('║'));
+
+        let down = MouseEventKind::Down(MouseButton::Left);
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        widget.handle_mouse(&mut browser, mouse(down, 9, 4));
+        assert_eq!(browser.scroll(), 15);
+
+        widget.handle_mouse(&mut browser, mouse(drag, 9, 2));
+        assert_eq!(browser.scroll(), 8);
+        let rows = draw(widget.clone(), &mut browser, area);
+        assert!(rows[2].ends_with('█'));
+
+        widget.handle_mouse(&mut browser, mouse(drag, 30, 0));
+        assert_eq!(browser.scroll(), 0);
+        assert!(matches!(
+            widget.handle_mouse(
+                &mut browser,
+                mouse(MouseEventKind::Up(MouseButton::Left), 9, 0)
+            ),
+            BrowserEvent::Handled
+        ));
+        assert!(matches!(
+            widget.handle_mouse(&mut browser, mouse(drag, 9, 4)),
+            BrowserEvent::Ignored
+        ));
+        assert_eq!(browser.scroll(), 0);
+    }
+
+    #[test]
+    fn scrollbar_without_content_rows() {
+        let mut browser = Browser::new(RatatuiRenderer::new());
+        browser.set_content("/", "one\ntwo\nthree");
+        let widget = BrowserWidget::new().scrollbar(true).status_line(true);
+        let rows = draw(widget, &mut browser, Rect::new(0, 0, 10, 1));
+        assert_eq!(rows.len(), 1);
+    }
+}